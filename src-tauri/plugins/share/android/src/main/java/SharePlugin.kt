@@ -0,0 +1,44 @@
+package app.tauri.share
+
+import android.app.Activity
+import android.content.Intent
+import androidx.core.content.FileProvider
+import app.tauri.annotation.Command
+import app.tauri.annotation.InvokeArg
+import app.tauri.annotation.TauriPlugin
+import app.tauri.plugin.Invoke
+import app.tauri.plugin.Plugin
+import java.io.File
+
+@InvokeArg
+class ShareFileArgs {
+    lateinit var path: String
+    lateinit var mimeType: String
+}
+
+@TauriPlugin
+class SharePlugin(private val activity: Activity) : Plugin(activity) {
+    @Command
+    fun shareFile(invoke: Invoke) {
+        val args = invoke.parseArgs(ShareFileArgs::class.java)
+        val file = File(args.path)
+        if (!file.isFile) {
+            invoke.reject("No file to share at ${args.path}.")
+            return
+        }
+        // Only files under the cache paths in share_paths.xml can be handed out.
+        val uri = try {
+            FileProvider.getUriForFile(activity, "${activity.packageName}.share", file)
+        } catch (e: IllegalArgumentException) {
+            invoke.reject("${args.path} can't be shared.")
+            return
+        }
+        val send = Intent(Intent.ACTION_SEND).apply {
+            type = args.mimeType
+            putExtra(Intent.EXTRA_STREAM, uri)
+            addFlags(Intent.FLAG_GRANT_READ_URI_PERMISSION)
+        }
+        activity.startActivity(Intent.createChooser(send, null))
+        invoke.resolve()
+    }
+}
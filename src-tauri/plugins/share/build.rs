@@ -0,0 +1,10 @@
+// The share dialog is only opened from the app's own commands, so the plugin exposes none to
+// the webview.
+const COMMANDS: &[&str] = &[];
+
+fn main() {
+  tauri_plugin::Builder::new(COMMANDS)
+    .android_path("android")
+    .ios_path("ios")
+    .build();
+}
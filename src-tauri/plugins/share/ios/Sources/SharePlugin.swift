@@ -0,0 +1,38 @@
+import SwiftRs
+import Tauri
+import UIKit
+import WebKit
+
+class ShareFileArgs: Decodable {
+  let path: String
+  let mimeType: String
+}
+
+class SharePlugin: Plugin {
+  @objc public func shareFile(_ invoke: Invoke) throws {
+    let args = try invoke.parseArgs(ShareFileArgs.self)
+    guard FileManager.default.fileExists(atPath: args.path) else {
+      invoke.reject("No file to share at \(args.path).")
+      return
+    }
+    let url = URL(fileURLWithPath: args.path)
+    DispatchQueue.main.async {
+      guard let root = self.manager.viewController else {
+        invoke.reject("There's no view to show the share sheet from.")
+        return
+      }
+      let sheet = UIActivityViewController(activityItems: [url], applicationActivities: nil)
+      // On iPad the sheet is a popover and needs something to point at.
+      sheet.popoverPresentationController?.sourceView = root.view
+      sheet.popoverPresentationController?.sourceRect = CGRect(
+        x: root.view.bounds.midX, y: root.view.bounds.midY, width: 0, height: 0)
+      root.present(sheet, animated: true)
+      invoke.resolve()
+    }
+  }
+}
+
+@_cdecl("init_plugin_share")
+func initPlugin() -> Plugin {
+  return SharePlugin()
+}
@@ -0,0 +1,80 @@
+use tauri::{
+  plugin::{Builder, TauriPlugin},
+  Manager, Runtime,
+};
+
+// Opens the platform share dialog (the Android sharesheet, the iOS share sheet) for a file
+// the app wrote, so a converted PNG can go straight to another app. Desktop platforms have no
+// such dialog; there `share_file` fails.
+
+#[cfg(target_os = "ios")]
+tauri::ios_plugin_binding!(init_plugin_share);
+
+#[cfg(mobile)]
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareFileArgs<'a> {
+  path: &'a str,
+  mime_type: &'a str,
+}
+
+/// Access to the share dialog; see [`ShareExt`].
+pub struct Share<R: Runtime> {
+  #[cfg(mobile)]
+  handle: tauri::plugin::PluginHandle<R>,
+  #[cfg(desktop)]
+  _runtime: std::marker::PhantomData<fn() -> R>,
+}
+
+impl<R: Runtime> Share<R> {
+  /// Shows the share dialog for the file at `path`, of type `mime_type`. Returns once the
+  /// dialog is up, not when the user has picked a target.
+  pub fn share_file(&self, path: &str, mime_type: &str) -> Result<(), String> {
+    #[cfg(mobile)]
+    {
+      self
+        .handle
+        .run_mobile_plugin::<()>("shareFile", ShareFileArgs { path, mime_type })
+        .map_err(|e| e.to_string())
+    }
+    #[cfg(desktop)]
+    {
+      let _ = (path, mime_type);
+      Err("Sharing is only available on Android and iOS.".into())
+    }
+  }
+}
+
+pub trait ShareExt<R: Runtime> {
+  fn share(&self) -> &Share<R>;
+}
+
+impl<R: Runtime, T: Manager<R>> ShareExt<R> for T {
+  fn share(&self) -> &Share<R> {
+    self.state::<Share<R>>().inner()
+  }
+}
+
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+  Builder::new("share")
+    .setup(|app, api| {
+      #[cfg(target_os = "android")]
+      let share = Share {
+        handle: api.register_android_plugin("app.tauri.share", "SharePlugin")?,
+      };
+      #[cfg(target_os = "ios")]
+      let share = Share {
+        handle: api.register_ios_plugin(init_plugin_share)?,
+      };
+      #[cfg(desktop)]
+      let share = {
+        let _ = api;
+        Share {
+          _runtime: std::marker::PhantomData,
+        }
+      };
+      app.manage(share);
+      Ok(())
+    })
+    .build()
+}
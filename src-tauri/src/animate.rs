@@ -0,0 +1,363 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use resvg::usvg;
+use serde::Serialize;
+
+use crate::{
+  convert::{is_svg, render_tree, usvg_options, validate_request, ConvertRequest},
+  encode::{encode_png, PngEncoding},
+  input,
+  preprocess::preprocess_svg,
+  presets,
+};
+
+// Evaluates SMIL animation (<animate>, <set>, <animateTransform>, <animateColor>) at a
+// fixed time by baking each animated value into its target's attributes and dropping
+// the animation elements, since usvg renders only the static document. CSS
+// @keyframes and <animateMotion> aren't evaluated; motion elements are just removed.
+
+const MAX_FRAMES: u32 = 1000;
+
+const ANIMATION_TAGS: [&str; 5] = ["animate", "set", "animateTransform", "animateColor", "animateMotion"];
+
+/// Parses a SMIL clock value ("2s", "150ms", "0:01.5", "1.2") into seconds.
+fn clock_value(s: &str) -> Option<f64> {
+  let s = s.trim();
+  if let Some(v) = s.strip_suffix("ms") {
+    return v.trim().parse::<f64>().ok().map(|v| v / 1000.0);
+  }
+  if let Some(v) = s.strip_suffix("min") {
+    return v.trim().parse::<f64>().ok().map(|v| v * 60.0);
+  }
+  if let Some(v) = s.strip_suffix('h') {
+    return v.trim().parse::<f64>().ok().map(|v| v * 3600.0);
+  }
+  if let Some(v) = s.strip_suffix('s') {
+    return v.trim().parse().ok();
+  }
+  if s.contains(':') {
+    return s
+      .split(':')
+      .try_fold(0.0, |acc, part| part.parse::<f64>().ok().map(|v| acc * 60.0 + v));
+  }
+  s.parse().ok()
+}
+
+// Number tokens with their unit suffix, e.g. "10px 5" → [(10, "px"), (5, "")].
+fn numbers(s: &str) -> Option<Vec<(f64, &str)>> {
+  s.split(|c: char| c.is_whitespace() || c == ',')
+    .filter(|t| !t.is_empty())
+    .map(|t| {
+      let split = t
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || *c == '.' || ((*c == '-' || *c == '+') && *i == 0)))
+        .map(|(i, _)| i)
+        .unwrap_or(t.len());
+      t[..split].parse().ok().map(|v| (v, &t[split..]))
+    })
+    .collect()
+}
+
+fn hex_color(s: &str) -> Option<[f64; 3]> {
+  let h = s.trim().strip_prefix('#')?;
+  let expanded: String = match h.len() {
+    3 => h.chars().flat_map(|c| [c, c]).collect(),
+    6 => h.to_string(),
+    _ => return None,
+  };
+  let v = u32::from_str_radix(&expanded, 16).ok()?;
+  Some([(v >> 16 & 0xff) as f64, (v >> 8 & 0xff) as f64, (v & 0xff) as f64])
+}
+
+/// Interpolates between two attribute values, or picks one when they aren't numeric.
+fn interpolate(a: &str, b: &str, t: f64) -> String {
+  if let (Some(ca), Some(cb)) = (hex_color(a), hex_color(b)) {
+    let c: Vec<u8> = (0..3).map(|i| (ca[i] + (cb[i] - ca[i]) * t).round() as u8).collect();
+    return format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2]);
+  }
+  match (numbers(a), numbers(b)) {
+    (Some(na), Some(nb)) if na.len() == nb.len() && !na.is_empty() => na
+      .iter()
+      .zip(&nb)
+      .map(|((va, unit), (vb, _))| format!("{}{unit}", va + (vb - va) * t))
+      .collect::<Vec<_>>()
+      .join(" "),
+    _ if t < 0.5 => a.to_string(),
+    _ => b.to_string(),
+  }
+}
+
+fn value_at(values: &[String], key_times: Option<&[f64]>, discrete: bool, p: f64) -> String {
+  let n = values.len();
+  if n == 1 {
+    return values[0].clone();
+  }
+  if discrete {
+    let i = match key_times {
+      Some(kt) => kt.iter().rposition(|k| *k <= p).unwrap_or(0),
+      None => ((p * n as f64).floor() as usize).min(n - 1),
+    };
+    return values[i.min(n - 1)].clone();
+  }
+  if p >= 1.0 {
+    return values[n - 1].clone();
+  }
+  let (i, local) = match key_times {
+    Some(kt) => {
+      let i = kt.iter().rposition(|k| *k <= p).unwrap_or(0).min(n - 2);
+      let span = kt[i + 1] - kt[i];
+      (i, if span > 0.0 { (p - kt[i]) / span } else { 0.0 })
+    }
+    None => {
+      let pos = p * (n - 1) as f64;
+      ((pos.floor() as usize).min(n - 2), pos.fract())
+    }
+  };
+  interpolate(&values[i], &values[i + 1], local)
+}
+
+/// The value `anim` contributes at `time`, or None when it has no effect then.
+fn evaluate(anim: roxmltree::Node, base: Option<&str>, time: f64) -> Option<String> {
+  let tag = anim.tag_name().name();
+  let begin = match anim.attribute("begin") {
+    // Only the first offset of a begin list counts; event-based begins never fire here.
+    Some(b) => clock_value(b.split(';').next()?)?,
+    None => 0.0,
+  };
+  if time < begin {
+    return None;
+  }
+  let elapsed = time - begin;
+  let dur = anim.attribute("dur").and_then(clock_value).filter(|d| *d > 0.0);
+  let freeze = anim.attribute("fill") == Some("freeze");
+
+  if tag == "set" {
+    let to = anim.attribute("to")?;
+    let end = dur.unwrap_or(f64::INFINITY);
+    return (elapsed < end || freeze).then(|| to.to_string());
+  }
+
+  let dur = dur?;
+  let repeats = match anim.attribute("repeatCount") {
+    Some("indefinite") => f64::INFINITY,
+    Some(r) => r.trim().parse().ok()?,
+    None => 1.0,
+  };
+  let active = match anim.attribute("repeatDur") {
+    Some("indefinite") => f64::INFINITY,
+    Some(r) => clock_value(r)?,
+    None => dur * repeats,
+  };
+  let progress = if elapsed >= active {
+    if !freeze {
+      return None;
+    }
+    // Frozen at wherever the active duration ended, usually the last value.
+    let rem = active % dur;
+    if rem == 0.0 {
+      1.0
+    } else {
+      rem / dur
+    }
+  } else {
+    (elapsed % dur) / dur
+  };
+
+  let values: Vec<String> = if let Some(v) = anim.attribute("values") {
+    v.split(';')
+      .map(|s| s.trim().to_string())
+      .filter(|s| !s.is_empty())
+      .collect()
+  } else {
+    let from = anim.attribute("from").or(base);
+    match (from, anim.attribute("to"), anim.attribute("by")) {
+      (Some(f), Some(t), _) => vec![f.to_string(), t.to_string()],
+      (Some(f), None, Some(by)) => {
+        // "by" animates from the start value to start + by.
+        let (a, b) = (numbers(f)?, numbers(by)?);
+        if a.len() != b.len() {
+          return None;
+        }
+        let to = a
+          .iter()
+          .zip(&b)
+          .map(|((x, u), (y, _))| format!("{}{u}", x + y))
+          .collect::<Vec<_>>();
+        vec![f.to_string(), to.join(" ")]
+      }
+      (None, Some(t), _) => vec![t.to_string()],
+      _ => return None,
+    }
+  };
+  if values.is_empty() {
+    return None;
+  }
+  let key_times: Option<Vec<f64>> = anim
+    .attribute("keyTimes")
+    .and_then(|k| k.split(';').map(|s| s.trim().parse().ok()).collect())
+    .filter(|k: &Vec<f64>| k.len() == values.len());
+  let discrete = anim.attribute("calcMode") == Some("discrete")
+    || values.iter().any(|v| hex_color(v).is_none() && numbers(v).is_none());
+  Some(value_at(&values, key_times.as_deref(), discrete, progress))
+}
+
+// Byte offset just after an element's qualified name in its start tag.
+fn after_tag_name(text: &str, node: roxmltree::Node) -> usize {
+  let start = node.range().start + 1;
+  let len = text[start..]
+    .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+    .unwrap_or(0);
+  start + len
+}
+
+/// Returns `text` with every SMIL animation evaluated at `time` seconds.
+pub(crate) fn evaluate_at(text: &str, time: f64) -> Result<String, String> {
+  let doc = roxmltree::Document::parse_with_options(
+    text,
+    roxmltree::ParsingOptions {
+      allow_dtd: true,
+      ..Default::default()
+    },
+  )
+  .map_err(|e| e.to_string())?;
+  let anims: Vec<roxmltree::Node> = doc
+    .descendants()
+    .filter(|n| n.is_element() && ANIMATION_TAGS.contains(&n.tag_name().name()))
+    .collect();
+  if anims.is_empty() {
+    return Ok(text.to_string());
+  }
+
+  // Final value per (target element, attribute), applied in document order.
+  let mut baked: HashMap<(roxmltree::NodeId, String), String> = HashMap::new();
+  let mut order: Vec<(roxmltree::NodeId, String)> = Vec::new();
+  for anim in &anims {
+    let href = anim
+      .attribute("href")
+      .or_else(|| anim.attribute(("http://www.w3.org/1999/xlink", "href")));
+    let target = match href.and_then(|h| h.strip_prefix('#')) {
+      Some(id) => doc.descendants().find(|n| n.attribute("id") == Some(id)),
+      None => anim.parent_element(),
+    };
+    let (Some(target), Some(name)) = (target, anim.attribute("attributeName")) else {
+      continue;
+    };
+    if anim.tag_name().name() == "animateMotion" {
+      continue;
+    }
+    let key = (target.id(), name.to_string());
+    let base = baked.get(&key).map(String::as_str).or_else(|| target.attribute(name));
+    let Some(mut value) = evaluate(*anim, base, time) else {
+      continue;
+    };
+    if anim.tag_name().name() == "animateTransform" {
+      let kind = anim.attribute("type").unwrap_or("translate");
+      value = format!("{kind}({value})");
+      if anim.attribute("additive") == Some("sum") {
+        if let Some(prev) = base {
+          value = format!("{prev} {value}");
+        }
+      }
+    }
+    if !baked.contains_key(&key) {
+      order.push(key.clone());
+    }
+    baked.insert(key, value);
+  }
+
+  // Text edits as (start, end, replacement); ranges never overlap.
+  let mut edits: Vec<(usize, usize, String)> = anims
+    .iter()
+    .map(|a| (a.range().start, a.range().end, String::new()))
+    .collect();
+  for key in order {
+    let node = doc
+      .get_node(key.0)
+      .ok_or_else(|| "Animation target vanished.".to_string())?;
+    let value = baked[&key]
+      .replace('&', "&amp;")
+      .replace('"', "&quot;")
+      .replace('<', "&lt;");
+    match node.attributes().find(|a| a.name() == key.1) {
+      Some(attr) => edits.push((attr.range_value().start, attr.range_value().end, value)),
+      None => {
+        let at = after_tag_name(text, node);
+        edits.push((at, at, format!(" {}=\"{value}\"", key.1)));
+      }
+    }
+  }
+  edits.sort_by_key(|e| std::cmp::Reverse(e.0));
+  let mut out = text.to_string();
+  for (start, end, replacement) in edits {
+    out.replace_range(start..end, &replacement);
+  }
+  Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameExport {
+  pub dir: String,
+  pub files: Vec<String>,
+  pub fps: f64,
+}
+
+/// Samples an animated SVG every 1/`fps` seconds over `duration` and writes the frames
+/// as a numbered PNG sequence (`frame_0001.png`, ...) into `<stem>_frames/`, using the
+/// size and post-processing settings from `req`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_animation_frames(
+  mut req: ConvertRequest,
+  svg_path: String,
+  fps: f64,
+  duration: f64,
+  output_dir: Option<String>,
+) -> Result<FrameExport, String> {
+  if !(fps.is_finite() && fps > 0.0 && duration.is_finite() && duration > 0.0) {
+    return Err("Frame rate and duration must be positive.".into());
+  }
+  let count = (duration * fps).round().max(1.0);
+  if count > MAX_FRAMES as f64 {
+    return Err(format!("Too many frames (max {MAX_FRAMES})."));
+  }
+  // Checked once here rather than per frame; every frame clones the settled request.
+  presets::resolve(&mut req)?;
+  validate_request(&mut req)?;
+  tauri::async_runtime::spawn_blocking(move || {
+    let svg = PathBuf::from(&svg_path);
+    if !svg.is_file() || !is_svg(&svg) {
+      return Err("Invalid SVG file path.".into());
+    }
+    let data = input::read_source(&svg)?;
+    let stem = svg.file_stem().and_then(|s| s.to_str()).unwrap_or("animation");
+    let base = match output_dir {
+      Some(d) => PathBuf::from(d),
+      None => svg.parent().map(|p| p.to_path_buf()).unwrap_or_default(),
+    };
+    let dir = base.join(format!("{stem}_frames"));
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let encoding = PngEncoding::from_request(&req)?;
+    let opt = usvg_options(&req);
+
+    let mut files = Vec::with_capacity(count as usize);
+    for i in 0..count as u32 {
+      let frame_req = ConvertRequest {
+        time: Some(i as f64 / fps),
+        ..req.clone()
+      };
+      let frame = preprocess_svg(&data, &frame_req)?;
+      let tree = usvg::Tree::from_data(&frame, &opt).map_err(|e| e.to_string())?;
+      let pixmap = render_tree(&tree, &frame_req)?;
+      let path = dir.join(format!("frame_{:04}.png", i + 1));
+      fs::write(&path, encode_png(&pixmap, encoding)?).map_err(|e| e.to_string())?;
+      files.push(path.to_string_lossy().to_string());
+    }
+    Ok(FrameExport {
+      dir: dir.to_string_lossy().to_string(),
+      files,
+      fps,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,402 @@
+use std::{
+  fs::{self, File},
+  io::{self, BufRead, BufReader, Read},
+  path::{Component, Path, PathBuf},
+  sync::mpsc::{self, Receiver, SyncSender},
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use flate2::bufread::GzDecoder;
+
+use tauri::{Manager, Runtime};
+
+use crate::{
+  convert::{is_svg, ConvertRequest},
+  input::{self, MAX_SOURCE_BYTES},
+};
+
+// Archive input (`inputMode: "zip"` or `"tar"`): icon packs arrive zipped and asset bundles
+// as tarballs. A tar (optionally gzipped) is never unpacked: the batch renders each SVG as it
+// is read out of the archive, so only the outputs are written. A zip is unpacked to a scratch
+// folder in the app cache and the batch runs in folder mode over it, unless `archiveInMemory`
+// asks for it to be read the way a tar is. Outputs keep their paths inside the archive, and go
+// to `<archive name>/` beside the archive unless the request names an output folder. The
+// scratch folder is removed when the batch ends.
+
+const SCRATCH_DIR: &str = "archives";
+// Archives can hold far more than they take to download; these bound what one batch takes
+// on, so a zip bomb fails up front instead of filling the disk.
+const MAX_ENTRIES: usize = 100_000;
+const MAX_UNPACKED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+pub(crate) fn is_archive_mode(mode: &str) -> bool {
+  matches!(mode, "zip" | "tar")
+}
+
+/// Where an archive batch reads its SVGs from.
+pub(crate) enum Prepared {
+  Unpacked(Unpacked),
+  Streamed(Streamed),
+}
+
+/// The unpacked copy of an archive; deleted on drop.
+pub(crate) struct Unpacked {
+  dir: PathBuf,
+}
+
+impl Drop for Unpacked {
+  fn drop(&mut self) {
+    if let Err(err) = fs::remove_dir_all(&self.dir) {
+      log::warn!("failed to remove {}: {err}", self.dir.display());
+    }
+  }
+}
+
+/// An archive read entry by entry while the batch runs. Each SVG is known by the virtual path
+/// `<archive>/<path inside it>`, which the batch treats as a file under the folder `<archive>`.
+pub(crate) struct Streamed {
+  archive: PathBuf,
+  kind: Kind,
+  listed: Vec<Listed>,
+  rx: Option<Receiver<Result<Entry, String>>>,
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+  Zip,
+  Tar,
+}
+
+struct Listed {
+  rel: PathBuf,
+  size: u64,
+  index: usize, // position in the archive
+}
+
+/// One SVG read out of an archive.
+pub(crate) struct Entry {
+  pub(crate) path: PathBuf, // virtual path
+  pub(crate) data: Vec<u8>,
+  pub(crate) mtime: Option<SystemTime>,
+}
+
+impl Streamed {
+  /// The SVGs' virtual paths and sizes, in the order `next` returns them.
+  pub(crate) fn files(&self) -> Vec<(PathBuf, u64)> {
+    self
+      .listed
+      .iter()
+      .map(|l| (self.archive.join(&l.rel), l.size))
+      .collect()
+  }
+
+  /// Reads the next SVG, which must be `path`. The archive is opened on the first call and read
+  /// on a thread of its own, at most one entry ahead of the batch.
+  pub(crate) fn next(&mut self, path: &Path) -> Result<Entry, String> {
+    let rx = self.rx.get_or_insert_with(|| {
+      let (tx, rx) = mpsc::sync_channel(1);
+      let archive = self.archive.clone();
+      match self.kind {
+        Kind::Zip => {
+          let listed: Vec<(usize, PathBuf)> = self.listed.iter().map(|l| (l.index, l.rel.clone())).collect();
+          std::thread::spawn(move || stream_zip(&archive, &listed, tx));
+        }
+        Kind::Tar => {
+          std::thread::spawn(move || stream_tar(&archive, tx));
+        }
+      }
+      rx
+    });
+    let entry = rx.recv().map_err(|_| "The archive ended early.".to_string())??;
+    if entry.path != path {
+      return Err("The archive changed during the batch.".into());
+    }
+    Ok(entry)
+  }
+}
+
+/// For archive input, gets the SVGs in `req`'s archive ready to convert and points `req` at
+/// them in folder mode. Blocks while listing or unpacking the archive.
+pub(crate) fn prepare<R: Runtime>(app: &impl Manager<R>, req: &mut ConvertRequest) -> Result<Option<Prepared>, String> {
+  if !is_archive_mode(&req.input_mode) {
+    return Ok(None);
+  }
+  let archive = PathBuf::from(&req.input_path);
+  if !archive.is_file() {
+    return Err("Invalid archive path.".into());
+  }
+  let streamed = match req.input_mode.as_str() {
+    "zip" if req.archive_in_memory.unwrap_or(false) => Some((Kind::Zip, list_zip(&mut open_zip(&archive)?)?)),
+    "zip" => None,
+    _ => Some((Kind::Tar, list_tar(&archive)?)),
+  };
+  let prepared = if let Some((kind, listed)) = streamed {
+    if listed.is_empty() {
+      return Err("The archive contains no SVG files.".into());
+    }
+    Prepared::Streamed(Streamed {
+      archive: archive.clone(),
+      kind,
+      listed,
+      rx: None,
+    })
+  } else {
+    let dir = app
+      .path()
+      .app_cache_dir()
+      .map_err(|e| e.to_string())?
+      .join(SCRATCH_DIR)
+      .join(uuid::Uuid::new_v4().simple().to_string());
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let unpacked = Unpacked { dir };
+    if unzip_svgs(&archive, &unpacked.dir)? == 0 {
+      return Err("The archive contains no SVG files.".into());
+    }
+    Prepared::Unpacked(unpacked)
+  };
+
+  if req
+    .output_dir
+    .as_deref()
+    .map(str::trim)
+    .filter(|d| !d.is_empty())
+    .is_none()
+  {
+    let stem = archive.file_stem().and_then(|s| s.to_str()).unwrap_or("archive");
+    // bundle.tar.gz -> bundle
+    let stem = stem.strip_suffix(".tar").unwrap_or(stem);
+    let parent = archive.parent().unwrap_or(Path::new("."));
+    req.output_dir = Some(parent.join(stem).to_string_lossy().to_string());
+  }
+  req.input_mode = "folder".into();
+  req.input_path = match &prepared {
+    Prepared::Unpacked(unpacked) => unpacked.dir.to_string_lossy().to_string(),
+    Prepared::Streamed(_) => archive.to_string_lossy().to_string(),
+  };
+  req.input_paths = None;
+  Ok(Some(prepared))
+}
+
+// Running totals checked against the archive limits as entries are taken on.
+#[derive(Default)]
+struct Budget {
+  entries: usize,
+  bytes: u64,
+}
+
+impl Budget {
+  fn take(&mut self, bytes: u64) -> Result<(), String> {
+    self.entries += 1;
+    if self.entries > MAX_ENTRIES {
+      return Err(format!("The archive holds more than {MAX_ENTRIES} SVG files."));
+    }
+    self.add(bytes)
+  }
+
+  fn add(&mut self, bytes: u64) -> Result<(), String> {
+    self.bytes += bytes;
+    if self.bytes > MAX_UNPACKED_BYTES {
+      return Err(format!(
+        "The archive's SVGs come to more than {} GB unpacked.",
+        MAX_UNPACKED_BYTES / (1024 * 1024 * 1024)
+      ));
+    }
+    Ok(())
+  }
+}
+
+fn open_zip(archive: &Path) -> Result<zip::ZipArchive<File>, String> {
+  let file = File::open(archive).map_err(|e| e.to_string())?;
+  zip::ZipArchive::new(file).map_err(|e| format!("Failed to open archive: {e}"))
+}
+
+// The SVG entries in a zip's directory, checked against the limits before any is read.
+fn list_zip(zip: &mut zip::ZipArchive<File>) -> Result<Vec<Listed>, String> {
+  let mut listed = Vec::new();
+  let mut budget = Budget::default();
+  for index in 0..zip.len() {
+    let entry = zip
+      .by_index_raw(index)
+      .map_err(|e| format!("Failed to read archive: {e}"))?;
+    // `enclosed_name` drops entries that would land outside the folder they're unpacked to
+    // (absolute or `..` paths).
+    let Some(rel) = entry.enclosed_name() else { continue };
+    if entry.is_dir() || !is_svg(&rel) || is_metadata(&rel) {
+      continue;
+    }
+    input::check_size(&rel, entry.size())?;
+    budget.take(entry.size())?;
+    listed.push(Listed {
+      rel,
+      size: entry.size(),
+      index,
+    });
+  }
+  Ok(listed)
+}
+
+// Extracts the SVGs in a zip under `dest`, keeping their paths. Returns how many there were.
+fn unzip_svgs(archive: &Path, dest: &Path) -> Result<usize, String> {
+  let mut zip = open_zip(archive)?;
+  let listed = list_zip(&mut zip)?;
+  // What was actually written, in case the declared sizes were lower.
+  let mut written = Budget::default();
+  for Listed { rel, index, .. } in &listed {
+    let mut entry = zip
+      .by_index(*index)
+      .map_err(|e| format!("Failed to read archive: {e}"))?;
+    let path = dest.join(rel);
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut out = File::create(&path).map_err(|e| e.to_string())?;
+    // The declared sizes can lie, so the copy is capped too.
+    let copied = io::copy(&mut (&mut entry).take(MAX_SOURCE_BYTES + 1), &mut out).map_err(|e| e.to_string())?;
+    input::check_size(rel, copied)?;
+    written.add(copied)?;
+  }
+  Ok(listed.len())
+}
+
+// Reads the listed zip entries in order, handing each to the batch through `tx`.
+fn stream_zip(archive: &Path, listed: &[(usize, PathBuf)], tx: SyncSender<Result<Entry, String>>) {
+  let mut zip = match open_zip(archive) {
+    Ok(zip) => zip,
+    Err(err) => {
+      let _ = tx.send(Err(err));
+      return;
+    }
+  };
+  for (index, rel) in listed {
+    let entry = zip
+      .by_index(*index)
+      .map_err(|e| format!("Failed to read archive: {e}"))
+      .and_then(|entry| read_capped(entry, rel))
+      .map(|data| Entry {
+        path: archive.join(rel),
+        data,
+        mtime: None,
+      });
+    // The batch stopped early and dropped the receiver.
+    if tx.send(entry).is_err() {
+      return;
+    }
+  }
+}
+
+// Reads one entry into memory, up to the source size limit.
+fn read_capped(entry: impl Read, rel: &Path) -> Result<Vec<u8>, String> {
+  let mut data = Vec::new();
+  entry
+    .take(MAX_SOURCE_BYTES + 1)
+    .read_to_end(&mut data)
+    .map_err(|e| format!("Failed to read archive: {e}"))?;
+  input::check_size(rel, data.len() as u64)?;
+  Ok(data)
+}
+
+fn open_tar(archive: &Path) -> Result<(BufReader<File>, bool), String> {
+  let mut reader = BufReader::new(File::open(archive).map_err(|e| e.to_string())?);
+  let gzipped = reader.fill_buf().map_err(|e| e.to_string())?.starts_with(&[0x1f, 0x8b]);
+  Ok((reader, gzipped))
+}
+
+// The SVG entries in a tar, checked against the limits. A tar has no index, so this walks its
+// headers: a plain tar is skipped through by seeking, but a gzipped one has to be inflated to
+// find them (and is inflated again as the batch reads it, rather than kept anywhere).
+fn list_tar(archive: &Path) -> Result<Vec<Listed>, String> {
+  let (reader, gzipped) = open_tar(archive)?;
+  if gzipped {
+    list_tar_entries(tar::Archive::new(GzDecoder::new(reader)).entries())
+  } else {
+    list_tar_entries(tar::Archive::new(reader).entries_with_seek())
+  }
+}
+
+fn list_tar_entries<R: Read>(entries: io::Result<tar::Entries<'_, R>>) -> Result<Vec<Listed>, String> {
+  let mut listed = Vec::new();
+  let mut budget = Budget::default();
+  let entries = entries.map_err(|e| format!("Failed to open archive: {e}"))?;
+  for (index, entry) in entries.enumerate() {
+    let entry = entry.map_err(|e| format!("Failed to read archive: {e}"))?;
+    let Some(rel) = tar_svg(&entry)? else { continue };
+    // A tar entry is exactly as long as its header says, so the declared size can be trusted.
+    input::check_size(&rel, entry.size())?;
+    budget.take(entry.size())?;
+    listed.push(Listed {
+      rel,
+      size: entry.size(),
+      index,
+    });
+  }
+  Ok(listed)
+}
+
+// The path of a tar entry to convert, or `None` to pass over it.
+fn tar_svg<R: Read>(entry: &tar::Entry<'_, R>) -> Result<Option<PathBuf>, String> {
+  if !entry.header().entry_type().is_file() {
+    return Ok(None);
+  }
+  let path = entry.path().map_err(|e| e.to_string())?;
+  Ok(enclosed(&path).filter(|rel| is_svg(rel) && !is_metadata(rel)))
+}
+
+// Reads the tar's SVG entries in order, handing each to the batch through `tx`. The order
+// matches `list_tar`, which `Streamed::next` checks entry by entry.
+fn stream_tar(archive: &Path, tx: SyncSender<Result<Entry, String>>) {
+  let sent = open_tar(archive).and_then(|(reader, gzipped)| {
+    if gzipped {
+      send_tar_entries(tar::Archive::new(GzDecoder::new(reader)), archive, &tx)
+    } else {
+      send_tar_entries(tar::Archive::new(reader), archive, &tx)
+    }
+  });
+  if let Err(err) = sent {
+    let _ = tx.send(Err(err));
+  }
+}
+
+fn send_tar_entries<R: Read>(
+  mut tar: tar::Archive<R>,
+  archive: &Path,
+  tx: &SyncSender<Result<Entry, String>>,
+) -> Result<(), String> {
+  let entries = tar.entries().map_err(|e| format!("Failed to open archive: {e}"))?;
+  for entry in entries {
+    let entry = entry.map_err(|e| format!("Failed to read archive: {e}"))?;
+    let Some(rel) = tar_svg(&entry)? else { continue };
+    let mtime = entry
+      .header()
+      .mtime()
+      .ok()
+      .map(|secs| UNIX_EPOCH + Duration::from_secs(secs));
+    let entry = read_capped(entry, &rel).map(|data| Entry {
+      path: archive.join(&rel),
+      data,
+      mtime,
+    });
+    // The batch stopped early and dropped the receiver.
+    if tx.send(entry).is_err() {
+      return Ok(());
+    }
+  }
+  Ok(())
+}
+
+// `path` if it stays inside the folder the archive stands for: no root, drive or `..` parts.
+fn enclosed(path: &Path) -> Option<PathBuf> {
+  let mut rel = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::Normal(part) => rel.push(part),
+      Component::CurDir => {}
+      _ => return None,
+    }
+  }
+  (!rel.as_os_str().is_empty()).then_some(rel)
+}
+
+// macOS adds `__MACOSX/._name.svg` resource forks to zips it creates; they aren't SVGs.
+fn is_metadata(rel: &Path) -> bool {
+  rel.components().any(|c| c.as_os_str() == "__MACOSX")
+}
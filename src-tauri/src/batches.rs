@@ -0,0 +1,101 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::convert::{BatchMessage, ConvertProgressEvent, ConvertRequest};
+
+// Batches that are running right now. Any number can run side by side (each convert call is its
+// own batch with its own id, settings and event stream); this is what lets a window that didn't
+// start one, or one reopened mid-run, find out what is going on.
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveBatch {
+  pub batch_id: String,
+  pub input_path: String,
+  pub output_dir: Option<String>,
+  pub started_at_ms: u64,
+  pub progress: Option<ConvertProgressEvent>, // latest progress message
+  #[serde(skip)]
+  progress_path: Option<PathBuf>,
+}
+
+#[derive(Default, Clone)]
+pub struct ActiveBatches(Arc<Mutex<HashMap<String, ActiveBatch>>>);
+
+impl ActiveBatches {
+  fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ActiveBatch>> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Lists the batch as running until the returned guard is dropped, however the batch ends.
+  pub(crate) fn start(&self, batch_id: &str, req: &ConvertRequest) -> ActiveGuard {
+    let started_at_ms = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_millis() as u64)
+      .unwrap_or(0);
+    self.lock().insert(
+      batch_id.to_string(),
+      ActiveBatch {
+        batch_id: batch_id.to_string(),
+        input_path: req.input_path.clone(),
+        output_dir: req.output_dir.clone(),
+        started_at_ms,
+        progress: None,
+        progress_path: None,
+      },
+    );
+    ActiveGuard {
+      batches: self.clone(),
+      batch_id: batch_id.to_string(),
+    }
+  }
+
+  pub(crate) fn update(&self, msg: &BatchMessage) {
+    if let BatchMessage::Progress(p) = msg {
+      if let Some(batch) = self.lock().get_mut(&p.batch_id) {
+        batch.progress = Some(p.clone());
+      }
+    }
+  }
+
+  /// Records that `batch_id` writes its progress file to `path`, unless another running batch
+  /// already does. Returns whether the path was free.
+  pub(crate) fn claim_progress_path(&self, batch_id: &str, path: &Path) -> bool {
+    let mut batches = self.lock();
+    let taken = batches
+      .values()
+      .any(|b| b.batch_id != batch_id && b.progress_path.as_deref() == Some(path));
+    if !taken {
+      if let Some(batch) = batches.get_mut(batch_id) {
+        batch.progress_path = Some(path.to_path_buf());
+      }
+    }
+    !taken
+  }
+}
+
+pub(crate) struct ActiveGuard {
+  batches: ActiveBatches,
+  batch_id: String,
+}
+
+impl Drop for ActiveGuard {
+  fn drop(&mut self) {
+    self.batches.lock().remove(&self.batch_id);
+  }
+}
+
+/// The batches running right now, oldest first.
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_active_batches(batches: State<'_, ActiveBatches>) -> Vec<ActiveBatch> {
+  let mut list: Vec<ActiveBatch> = batches.lock().values().cloned().collect();
+  list.sort_by_key(|b| b.started_at_ms);
+  list
+}
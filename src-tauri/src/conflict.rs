@@ -0,0 +1,187 @@
+use std::{
+  collections::{HashMap, HashSet},
+  path::{Path, PathBuf},
+  sync::{mpsc, Arc, Mutex},
+  time::Duration,
+};
+
+use serde::Serialize;
+use tauri::{Emitter, State};
+use ts_rs::TS;
+
+use crate::{convert::EVENT_VERSION, history, layout};
+
+// An unanswered "ask" keeps the existing file after this long, so a batch left alone (or
+// started from a window nobody is watching) still finishes.
+const ASK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConflictAction {
+  Overwrite,
+  Skip,
+  Rename,
+}
+
+impl ConflictAction {
+  pub(crate) fn parse(s: &str) -> Option<Self> {
+    match s {
+      "overwrite" => Some(Self::Overwrite),
+      "skip" => Some(Self::Skip),
+      "rename" => Some(Self::Rename),
+      _ => None,
+    }
+  }
+}
+
+/// Validates an `on_conflict` policy: one of the actions, or "ask".
+pub(crate) fn validate_policy(policy: &str) -> Result<(), String> {
+  if policy == "ask" || ConflictAction::parse(policy).is_some() {
+    Ok(())
+  } else {
+    Err("Invalid conflict policy (expected overwrite, skip, rename or ask).".into())
+  }
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConvertConflictEvent {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
+  pub index: u32,
+  pub svg: String,
+  pub png: String,
+}
+
+#[derive(Default)]
+struct Registry {
+  // (batch id, item index) -> the blocked item waiting for a decision
+  pending: HashMap<(String, u32), mpsc::Sender<ConflictAction>>,
+  // Decisions the user chose to apply to the rest of a batch.
+  sticky: HashMap<String, ConflictAction>,
+}
+
+/// Pending "ask" conflicts, shared between batch workers and `resolve_conflict`.
+#[derive(Default, Clone)]
+pub struct ConflictRegistry(Arc<Mutex<Registry>>);
+
+impl ConflictRegistry {
+  fn lock(&self) -> std::sync::MutexGuard<'_, Registry> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Emits `convert-conflict` and blocks the calling worker until the user answers, or keeps
+  /// the existing file if nobody does within `ASK_TIMEOUT`.
+  pub(crate) fn ask(&self, window: &tauri::Window, batch_id: &str, index: u32, svg: &str, png: &Path) -> ConflictAction {
+    let rx = {
+      let mut reg = self.lock();
+      if let Some(action) = reg.sticky.get(batch_id) {
+        return *action;
+      }
+      let (tx, rx) = mpsc::channel();
+      reg.pending.insert((batch_id.to_string(), index), tx);
+      rx
+    };
+    let _ = window.emit(
+      "convert-conflict",
+      ConvertConflictEvent {
+        version: EVENT_VERSION,
+        batch_id: batch_id.to_string(),
+        index,
+        svg: svg.to_string(),
+        png: png.to_string_lossy().to_string(),
+      },
+    );
+    // If the registry is dropped without an answer, keep the existing file.
+    rx.recv_timeout(ASK_TIMEOUT).unwrap_or_else(|_| {
+      self.lock().pending.remove(&(batch_id.to_string(), index));
+      ConflictAction::Skip
+    })
+  }
+
+  /// Forgets everything recorded for a finished batch.
+  pub(crate) fn finish_batch(&self, batch_id: &str) {
+    let mut reg = self.lock();
+    reg.sticky.remove(batch_id);
+    reg.pending.retain(|(b, _), _| b != batch_id);
+  }
+}
+
+/// Outputs a batch has claimed so far. Two sources can map to one output path (e.g. `a.svg`
+/// and `a.svgz`, or rules flattening folders); the later one is renamed rather than replacing
+/// the earlier, which may not even be on disk yet while it encodes.
+#[derive(Default, Clone)]
+pub(crate) struct Claims(Arc<Mutex<HashSet<PathBuf>>>);
+
+impl Claims {
+  fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<PathBuf>> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Where the output planned for `path` goes: `path` itself or a free name beside it, or
+  /// `None` to keep the file already there. `decide` is only consulted for a file that was
+  /// there before the batch.
+  pub(crate) fn settle(
+    &self,
+    path: &Path,
+    trash_replaced: bool,
+    decide: impl FnOnce() -> ConflictAction,
+  ) -> Result<Option<PathBuf>, String> {
+    let action = if self.lock().contains(path) {
+      ConflictAction::Rename
+    } else if layout::io_path(path)?.exists() {
+      match decide() {
+        // Hand-edited exports stay recoverable from the trash.
+        ConflictAction::Overwrite if trash_replaced => {
+          history::move_to_trash(&layout::io_path(path)?)
+            .map_err(|e| format!("Failed to move the existing file to the trash: {e}"))?;
+          ConflictAction::Overwrite
+        }
+        action => action,
+      }
+    } else {
+      ConflictAction::Overwrite
+    };
+    let path = match action {
+      ConflictAction::Overwrite => path.to_path_buf(),
+      ConflictAction::Rename => unique_path(path, |p| self.lock().contains(p)),
+      ConflictAction::Skip => return Ok(None),
+    };
+    self.lock().insert(path.clone());
+    Ok(Some(path))
+  }
+}
+
+/// First free `<stem>-<n>.png` next to `path`, also passing over names `taken` reports.
+pub(crate) fn unique_path(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+  let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("output");
+  let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("png");
+  let mut n = 2;
+  loop {
+    let candidate = path.with_file_name(format!("{stem}-{n}.{ext}"));
+    if !taken(&candidate) && !layout::io_path(&candidate).is_ok_and(|p| p.exists()) {
+      return candidate;
+    }
+    n += 1;
+  }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn resolve_conflict(
+  registry: State<'_, ConflictRegistry>,
+  batch_id: String,
+  index: u32,
+  action: String,
+  apply_to_all: Option<bool>,
+) -> Result<(), String> {
+  let action = ConflictAction::parse(&action).ok_or_else(|| "Invalid conflict action.".to_string())?;
+  let mut reg = registry.lock();
+  if apply_to_all.unwrap_or(false) {
+    reg.sticky.insert(batch_id.clone(), action);
+  }
+  let tx = reg
+    .pending
+    .remove(&(batch_id, index))
+    .ok_or_else(|| "No pending conflict for this item.".to_string())?;
+  tx.send(action).map_err(|_| "The conversion is no longer waiting.".to_string())
+}
@@ -1,49 +1,261 @@
 use std::{
+  borrow::Cow,
+  collections::VecDeque,
   fs,
   path::{Path, PathBuf},
+  sync::{
+    mpsc::{self, SyncSender},
+    Arc, Mutex,
+  },
+  thread::JoinHandle,
+  time::{Duration, Instant, SystemTime},
 };
 
 use resvg::{tiny_skia, usvg};
 use serde::{Deserialize, Serialize};
-use tauri::Emitter;
-use walkdir::WalkDir;
+use sha2::{Digest, Sha256};
+use tauri::{ipc::Channel, Emitter, Manager};
+use ts_rs::TS;
+
+use crate::{
+  archive,
+  batches::ActiveBatches,
+  conflict::{self, ConflictAction, ConflictRegistry},
+  dupes::{DuplicateIndex, DuplicateOutput},
+  encode::{encode_png, PngEncoding},
+  eps,
+  filters::{self, DuotoneOptions, HslAdjust},
+  finder::{self, FinderTags},
+  gpu,
+  input,
+  hdr::HdrOptions,
+  history::BatchHistory,
+  inspect::{inspect_svg, SvgFeatures},
+  layout, linear, lottie, metadata,
+  monitor::BatchMonitors,
+  passthrough, pdf,
+  postfx::{self, OutlineOptions, ShadowOptions, TileOptions},
+  preprocess::{self, preprocess_svg, FontAxes},
+  presets,
+  progress::ProgressFile,
+  queue::{self, JobQueue, Slot},
+  report::{self, DiskUsage, OutputRecord},
+  resample::{self, Filter, Transfer},
+  rules::{ConversionRule, RuleSet},
+  settings::{MetadataSettings, SettingsStore},
+  sftp::{self, SftpTarget},
+  sprite::{self, SpriteOptions},
+  svgout,
+  upload::{self, UploadProgressEvent, UploadReport},
+  walk::{self, WalkOptions},
+  webhook,
+  watermark::{self, WatermarkOptions},
+};
 use std::sync::mpsc::Sender;
 
-const MAX_PIXELS: u64 = 80_000_000;
+pub(crate) const MAX_PIXELS: u64 = 80_000_000;
+// Outputs above this size are rendered in horizontal strips so the UI gets per-item progress.
+const STRIP_RENDER_PIXELS: u64 = 8_000_000;
+const STRIP_ROWS: u32 = 256;
+// Number of recent completions used for the rolling files/second rate.
+const RATE_WINDOW: usize = 20;
+const SLOWEST_FILES: usize = 5;
+/// Version of the batch event and summary payloads. Bump it when a field is renamed,
+/// removed or changes meaning, so the frontend can tell it's talking to a different build.
+pub const EVENT_VERSION: u32 = 1;
 
-#[derive(Debug, Clone, Deserialize)]
+// Sample renders for output-size estimates are capped to keep the estimate fast.
+const ESTIMATE_SAMPLE_PIXELS: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct ConvertRequest {
-  pub input_mode: String, // "file" | "folder"
+  pub input_mode: String, // "file" | "folder" | "zip" | "tar"
   pub input_path: String,
+  pub input_paths: Option<Vec<String>>, // File mode: convert these files instead of just inputPath
+  pub archive_in_memory: Option<bool>, // zip mode: read each SVG into memory instead of unpacking the archive
   pub output_dir: Option<String>,
-  pub size_mode: String, // "scale" | "exact"
+  pub size_mode: String, // "scale" | "exact" | "longEdge" | "shortEdge" | "megapixels" | "physical"
   pub scale: Option<f64>,
   pub width: Option<u32>,
   pub height: Option<u32>,
+  pub edge: Option<u32>, // longEdge/shortEdge modes: that edge's length in px
+  pub megapixels: Option<f64>, // megapixels mode: target pixel count in millions
+  pub physical: Option<PhysicalSize>, // physical mode: print size and resolution (also sets pHYs)
   pub crop: Option<bool>, // Exact mode only: center-crop (cover) instead of stretch
+  pub size_preset: Option<String>, // Named size from list_size_presets; overrides the size fields
   pub background: Option<String>, // "#RRGGBB" (optional)
+  pub verify: Option<bool>, // Re-decode written PNGs and check their size
+  pub contact_sheet: Option<bool>, // Write an index.html preview of the batch
+  pub sprite: Option<SpriteOptions>, // Combine outputs into a sprite PNG + stylesheet
+  pub manifest: Option<bool>, // Write a manifest.json describing every output
+  pub density_set: Option<bool>, // Also render @2x/@3x variants of the computed size
+  pub target: Option<String>, // Platform preset: "android" | "ios"
+  pub on_conflict: Option<String>, // "overwrite" (default) | "skip" | "rename" | "ask"
+  pub exclude: Option<Vec<String>>, // Folder mode: paths to leave out (e.g. from list_svg_files)
+  pub walk: Option<WalkOptions>, // Folder mode: hidden-file / ignore-file handling
+  pub sort_by: Option<String>, // Folder mode: "name" | "path" (default) | "size" | "mtime" | "natural"
+  pub sort_desc: Option<bool>,
+  pub languages: Option<Vec<String>>, // systemLanguage preference order, e.g. ["de", "en"]
+  pub color_scheme: Option<String>, // "light" | "dark": emulate prefers-color-scheme
+  pub font_axes: Option<FontAxes>,
+  pub shape_rendering: Option<String>, // "crispEdges" | "geometricPrecision" | "optimizeSpeed"
+  pub text_rendering: Option<String>,  // "optimizeLegibility" | "geometricPrecision" | "optimizeSpeed"
+  pub force_rendering_hints: Option<bool>, // Override hints set in the SVG itself
+  pub linear_light: Option<bool>, // Scale and composite in linear RGB instead of sRGB
+  pub alpha_mode: Option<String>, // "straight" (default) | "premultiplied"
+  pub matte: Option<String>,      // "#RRGGBB" bled into semi-transparent edges
+  pub resample_filter: Option<String>, // Supersample and filter down: "nearest" | "bilinear" | "catmullRom" | "lanczos3"
+  pub shadow: Option<ShadowOptions>,
+  pub outline: Option<OutlineOptions>,
+  pub corner_radius: Option<f32>, // px; rounds the corners of the final image
+  pub mask: Option<String>,       // "circle": cover-scale into a centered circle
+  pub watermark: Option<WatermarkOptions>,
+  pub tile: Option<TileOptions>, // Repeat the render across a larger canvas
+  pub invert: Option<bool>,      // Invert RGB, keep alpha
+  pub hsl: Option<HslAdjust>,
+  pub tint: Option<String>, // "#RRGGBB": recolor everything, keeping alpha
+  pub duotone: Option<DuotoneOptions>,
+  pub posterize: Option<u8>, // Color levels per channel (2..=255)
+  pub threshold: Option<u8>, // Luma cutoff: black below, white at or above
+  pub optimize_png: Option<bool>, // Smallest lossless PNG color type (default true)
+  pub output_format: Option<String>, // "png" (default) | "svg" (cleaned-up SVG) | "eps"
+  pub text_to_paths: Option<bool>,   // SVG output: convert text to outlines
+  pub svg_normalize: Option<bool>,   // SVG output: write usvg's cleaned tree (default) or the source
+  pub raster_passthrough: Option<bool>, // Scale a lone full-canvas PNG/JPEG directly instead of re-rendering
+  pub time: Option<f64>, // Seconds: evaluate SMIL animation at this point instead of t=0
+  pub color_space: Option<String>, // "srgb" (default) | "display-p3": convert and tag the PNG
+  pub hdr: Option<HdrOptions>, // Experimental 16-bit PQ/BT.2020 PNG with a cICP chunk
+  pub color_vision: Option<Vec<String>>, // Extra outputs simulating "protanopia" | "deuteranopia" | "tritanopia"
+  pub review_pdf: Option<bool>, // Collect the PNG outputs into a multi-page review.pdf
+  pub contact_sheet_pdf: Option<bool>, // Also write the contact sheet as a printable grid PDF
+  pub rules: Option<Vec<ConversionRule>>, // Per-file overrides by glob; first match wins
+  pub size_from_file_name: Option<bool>, // Take sizes from names like `x_1200x630.svg` / `x@2x.svg`
+  pub group_by: Option<String>, // Output subfolders: "none" (default) | "size" | "format"
+  pub run_folder: Option<bool>, // Write into a new dated subfolder of the output dir per batch
+  pub file_name_form: Option<String>, // Output name Unicode form: "keep" (default) | "nfc" | "ascii"
+  pub preserve_mtime: Option<bool>, // Give outputs the source file's modified time
+  pub file_mode: Option<String>, // Unix permission bits for outputs, octal (e.g. "644"); ignored on Windows
+  pub read_only: Option<bool>, // Mark outputs read-only
+  pub finder_tags: Option<FinderTags>, // macOS: tag outputs (and failed sources) in Finder
+  pub trash_replaced: Option<bool>, // Move files about to be overwritten to the system trash first
+  pub detect_duplicates: Option<bool>, // Flag outputs byte-identical to another PNG in the output tree
+  pub progress_file: Option<bool>, // Mirror progress into a JSON file for external monitoring
+  pub progress_path: Option<String>, // Where to write it (default: progress.json in the app cache)
+  pub priority: Option<i32>, // Queue priority against other running batches; higher goes first (default 0)
+  pub low_power: Option<bool>, // Background mode: one item at a time, low thread priority, paced
+  pub engine: Option<String>, // "cpu" (default) | "gpu": GPU where built in and available; filtered SVGs use the CPU
+  pub upload: Option<bool>, // After the batch, upload everything it wrote to the bucket in settings
+  pub sftp: Option<SftpTarget>, // Send outputs to this server instead of an output folder
+  #[serde(skip)]
+  pub metadata: Option<MetadataSettings>, // Attribution from settings, filled in by run_batch
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum PhysicalUnit {
+  Mm,
+  Cm,
+  In,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct PhysicalSize {
+  pub width: f64,
+  pub height: Option<f64>, // omitted: follows the SVG's aspect ratio
+  pub unit: PhysicalUnit,
+  pub dpi: f64,
+}
+
+impl PhysicalSize {
+  fn to_pixels(self, length: f64) -> u32 {
+    let inches = match self.unit {
+      PhysicalUnit::Mm => length / 25.4,
+      PhysicalUnit::Cm => length / 2.54,
+      PhysicalUnit::In => length,
+    };
+    (inches * self.dpi).round().max(1.0) as u32
+  }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct SvgSize {
   pub width: u32,
   pub height: u32,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct FolderSizeInfo {
   pub total: u32,
   pub all_same: bool,
   pub base_size: Option<SvgSize>,
   pub unique_sizes: Vec<SvgSize>,
+  pub histogram: Option<Vec<SizeCount>>, // Every unique size with its file count (opt-in)
+  pub flagged: Vec<FlaggedSvg>, // Files with animation, scripts, embedded rasters or external refs
+  pub invalid: Vec<InvalidSvg>, // Files that couldn't be read or parsed
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct InvalidSvg {
+  pub path: String,
+  pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FlaggedSvg {
+  pub path: String,
+  #[serde(flatten)]
+  pub features: SvgFeatures,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SizeCount {
+  pub width: u32,
+  pub height: u32,
+  pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SvgFileEntry {
+  pub path: String,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  #[ts(type = "number")]
+  pub bytes: u64,
+  pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SvgFileList {
+  pub total: u32,
+  pub offset: u32,
+  pub items: Vec<SvgFileEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct ConvertProgressEvent {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
   pub phase: String,
   pub current: u32,
   pub active: Option<u32>,
@@ -51,11 +263,20 @@ pub struct ConvertProgressEvent {
   pub ok: u32,
   pub failed: u32,
   pub last_svg: Option<String>,
+  pub item_percent: Option<u8>, // 0–100 within the active item's render phase
+  #[ts(type = "number")]
+  pub elapsed_ms: u64,
+  pub files_per_sec: Option<f64>, // rolling rate over the last few items
+  #[ts(type = "number | null")]
+  pub eta_ms: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct ConvertItemEvent {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
   pub index: u32,
   pub total: u32,
   pub svg: String,
@@ -64,26 +285,194 @@ pub struct ConvertItemEvent {
   pub out_height: Option<u32>,
   pub ok: bool,
   pub engine: Option<String>,
+  pub checksum: Option<String>, // SHA-256 of the written PNG (verify mode only)
+  pub extra_pngs: Vec<String>, // Additional variants written for the same SVG
+  pub skipped: bool, // Output already existed and was kept (conflict policy)
+  pub warnings: Vec<String>, // e.g. "animated", "scripted", "embeddedRaster", "externalRefs"
+  pub renamed_from: Option<String>, // Output stem before unsafe filename characters were replaced
+  pub duplicates: Vec<DuplicateOutput>, // Outputs identical to a file already in the output tree
   pub error: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+struct ItemResult {
+  outputs: Vec<RenderOutput>,
+  warnings: Vec<String>,
+  engine: &'static str, // "vello" if any output was drawn on the GPU, else "resvg"
+  encode_ms: u64,       // time spent encoding and writing, possibly overlapping the next render
+}
+
+// An item whose renders are done but whose PNGs may still be encoding on its encoder thread,
+// so the batch can render the next file meanwhile. `wait` collects the outputs in order.
+struct PendingItem {
+  outputs: Vec<PendingOutput>,
+  encoded: Option<JoinHandle<Vec<Encoded>>>,
+  warnings: Vec<String>,
+  engine: &'static str,
+}
+
+// A batch item between its render and its report.
+struct InFlight<'a> {
+  index: u32,
+  svg: &'a PathBuf,
+  svg_str: String,
+  finder_tags: Option<FinderTags>,
+  renamed_from: Option<String>,
+  render_ms: u64,
+  res: Result<PendingItem, String>,
+}
+
+enum PendingOutput {
+  Ready(RenderOutput),
+  Encoding, // the next result from the item's encoder
+}
+
+type Encoded = (Result<RenderOutput, String>, Duration);
+
+// Writes an item's PNGs one after another on a thread of its own. The thread holds the item's
+// render slot until the last PNG is written, so encoding counts against the queue like
+// rendering does and a batch never has more encoders running than slots.
+struct Encoder {
+  tx: SyncSender<(tiny_skia::Pixmap, PngEncoding, PathBuf)>,
+  handle: JoinHandle<Vec<Encoded>>,
+}
+
+impl Encoder {
+  fn spawn(req: Arc<ConvertRequest>, mtime: Option<SystemTime>, slot: Slot) -> Self {
+    // One render ahead at most, so finished pixmaps don't pile up behind a slow encode.
+    let (tx, rx) = mpsc::sync_channel::<(tiny_skia::Pixmap, PngEncoding, PathBuf)>(1);
+    let handle = std::thread::spawn(move || {
+      let _slot = slot;
+      rx.into_iter()
+        .map(|(pixmap, encoding, path)| {
+          let started = Instant::now();
+          (write_png(&pixmap, encoding, path, &req, mtime), started.elapsed())
+        })
+        .collect()
+    });
+    Self { tx, handle }
+  }
+}
+
+impl PendingItem {
+  fn wait(self) -> Result<ItemResult, String> {
+    let mut encode_ms = 0;
+    let mut outputs = Vec::with_capacity(self.outputs.len());
+    let mut first_err = None;
+    // Join the encoder even after a failure, so nothing is still writing once the item is reported.
+    let mut encoded = match self.encoded {
+      Some(handle) => handle
+        .join()
+        .unwrap_or_else(|_| vec![(Err("PNG encoder panicked.".into()), Duration::ZERO)]),
+      None => Vec::new(),
+    }
+    .into_iter();
+    for out in self.outputs {
+      match out {
+        PendingOutput::Ready(out) => outputs.push(out),
+        PendingOutput::Encoding => {
+          let (res, took) = encoded
+            .next()
+            .unwrap_or_else(|| (Err("PNG encoder stopped early.".into()), Duration::ZERO));
+          encode_ms += took.as_millis() as u64;
+          match res {
+            Ok(out) => outputs.push(out),
+            Err(err) => {
+              first_err.get_or_insert(err);
+            }
+          }
+        }
+      }
+    }
+    if let Some(err) = first_err {
+      return Err(err);
+    }
+    Ok(ItemResult {
+      outputs,
+      warnings: self.warnings,
+      engine: self.engine,
+      encode_ms,
+    })
+  }
+}
+
+struct RenderOutput {
+  path: PathBuf,
+  width: u32,
+  height: u32,
+  bytes: u64,
+  checksum: Option<String>,
+  skipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export)]
 pub struct ConvertSummary {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
   pub total: u32,
   pub ok: u32,
   pub failed: u32,
+  pub contact_sheet: Option<String>,
+  pub sprite: Option<String>,
+  pub manifest: Option<String>,
+  pub review_pdf: Option<String>,
+  pub contact_sheet_pdf: Option<String>,
+  pub run_dir: Option<String>,
+  pub disk_usage: DiskUsage,
+  pub duplicates: Vec<DuplicateOutput>,
+  pub upload: Option<UploadReport>,
+  #[serde(flatten)]
+  pub stats: ConvertStats,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConvertStatsEvent {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
+  #[serde(flatten)]
+  pub stats: ConvertStats,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConvertStats {
+  #[ts(type = "number")]
+  pub bytes_written: u64,
+  #[ts(type = "number")]
+  pub bytes_read: u64,
+  pub avg_item_ms: Option<f64>,
+  pub slowest: Vec<SlowFile>,
+  #[ts(type = "number")]
+  pub duration_ms: u64,
 }
 
-fn is_svg(path: &Path) -> bool {
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SlowFile {
+  pub svg: String,
+  #[ts(type = "number")]
+  pub ms: u64,
+}
+
+pub(crate) fn is_svg(path: &Path) -> bool {
   path
     .extension()
     .and_then(|s| s.to_str())
-    .map(|s| s.eq_ignore_ascii_case("svg"))
+    .map(|s| s.eq_ignore_ascii_case("svg") || s.eq_ignore_ascii_case("svgz"))
     .unwrap_or(false)
 }
 
-fn parse_bg_color(bg: &str) -> Option<tiny_skia::Color> {
+// Batch inputs: SVGs, plus Lottie animations in builds with Lottie support.
+pub(crate) fn is_input(path: &Path) -> bool {
+  is_svg(path) || lottie::is_lottie(path)
+}
+
+pub(crate) fn parse_bg_color(bg: &str) -> Option<tiny_skia::Color> {
   let s = bg.trim().trim_start_matches('#');
   if s.len() != 6 {
     return None;
@@ -107,18 +496,77 @@ fn enforce_pixel_cap(w: u32, h: u32) -> Result<(), String> {
   Ok(())
 }
 
-fn read_svg_size(svg_path: &Path) -> Result<SvgSize, String> {
-  let data = fs::read(svg_path).map_err(|e| e.to_string())?;
-  let opt = usvg::Options::default();
-  let tree = usvg::Tree::from_data(&data, &opt).map_err(|e| e.to_string())?;
+pub(crate) fn tree_size(tree: &usvg::Tree) -> SvgSize {
   let sz = tree.size();
-  Ok(SvgSize {
+  SvgSize {
     width: sz.width().ceil().max(1.0) as u32,
     height: sz.height().ceil().max(1.0) as u32,
-  })
+  }
+}
+
+/// Parser options for rendering with `req` (size scans only need the defaults).
+pub(crate) fn usvg_options(req: &ConvertRequest) -> usvg::Options<'static> {
+  let mut opt = usvg::Options::default();
+  if let Some(langs) = req.languages.as_ref().filter(|l| !l.is_empty()) {
+    opt.languages = langs.iter().map(|l| l.trim().to_string()).collect();
+  }
+  // Validated in run_batch; these only set the defaults for elements without a hint.
+  if let Some(Ok(v)) = req.shape_rendering.as_deref().map(parse_shape_rendering) {
+    opt.shape_rendering = v;
+  }
+  if let Some(Ok(v)) = req.text_rendering.as_deref().map(parse_text_rendering) {
+    opt.text_rendering = v;
+  }
+  opt
+}
+
+pub(crate) fn parse_shape_rendering(s: &str) -> Result<usvg::ShapeRendering, String> {
+  match s {
+    "crispEdges" => Ok(usvg::ShapeRendering::CrispEdges),
+    "geometricPrecision" => Ok(usvg::ShapeRendering::GeometricPrecision),
+    "optimizeSpeed" => Ok(usvg::ShapeRendering::OptimizeSpeed),
+    _ => Err(format!("Invalid shape rendering: {s}")),
+  }
+}
+
+pub(crate) fn parse_text_rendering(s: &str) -> Result<usvg::TextRendering, String> {
+  match s {
+    "optimizeLegibility" => Ok(usvg::TextRendering::OptimizeLegibility),
+    "geometricPrecision" => Ok(usvg::TextRendering::GeometricPrecision),
+    "optimizeSpeed" => Ok(usvg::TextRendering::OptimizeSpeed),
+    _ => Err(format!("Invalid text rendering: {s}")),
+  }
+}
+
+fn read_svg_size(svg_path: &Path) -> Result<SvgSize, String> {
+  let data = input::read_source(svg_path)?;
+  svg_size_from_data(&data)
+}
+
+fn svg_size_from_data(data: &[u8]) -> Result<SvgSize, String> {
+  let opt = usvg::Options::default();
+  let tree = usvg::Tree::from_data(data, &opt).map_err(|e| e.to_string())?;
+  Ok(tree_size(&tree))
 }
 
-fn compute_output_size(req: &ConvertRequest, src: &SvgSize) -> Result<(u32, u32), String> {
+// Reads the PNG back from disk so truncated writes (flaky disks, network shares)
+// surface as item failures instead of silently broken files.
+fn verify_written_png(path: &Path, expect_w: u32, expect_h: u32) -> Result<String, String> {
+  let data = fs::read(path).map_err(|e| format!("Verify failed: {e}"))?;
+  let decoded = tiny_skia::Pixmap::decode_png(&data).map_err(|e| format!("Verify failed: {e}"))?;
+  if decoded.width() != expect_w || decoded.height() != expect_h {
+    return Err(format!(
+      "Verify failed: expected {}×{}, got {}×{}.",
+      expect_w,
+      expect_h,
+      decoded.width(),
+      decoded.height()
+    ));
+  }
+  Ok(format!("{:x}", Sha256::digest(&data)))
+}
+
+pub(crate) fn compute_output_size(req: &ConvertRequest, src: &SvgSize) -> Result<(u32, u32), String> {
   match req.size_mode.as_str() {
     "scale" => {
       let s = req.scale.unwrap_or(1.0);
@@ -137,90 +585,81 @@ fn compute_output_size(req: &ConvertRequest, src: &SvgSize) -> Result<(u32, u32)
       }
       Ok((w, h))
     }
-    _ => Err("Invalid size mode.".into()),
-  }
-}
-
-fn make_output_path(svg_path: &Path, root: Option<&Path>, out_dir: Option<&Path>, out_w: u32, out_h: u32) -> PathBuf {
-  let base = svg_path
-    .file_stem()
-    .and_then(|s| s.to_str())
-    .unwrap_or("output")
-    .to_string();
-  let file_name = format!("{base}_{out_w}x{out_h}.png");
-
-  let mut rel_prefix = String::new();
-  if let Some(root) = root {
-    if let Ok(rel) = svg_path.strip_prefix(root) {
-      if let Some(parent) = rel.parent() {
-        let p = parent.to_string_lossy();
-        if !p.is_empty() && p != "." {
-          rel_prefix = p.replace(['/', '\\'], "_");
-        }
+    // One edge is given; the other follows the SVG's aspect ratio.
+    "longEdge" | "shortEdge" => {
+      let edge = req.edge.filter(|e| *e > 0).ok_or_else(|| "Edge length must be a positive number.".to_string())?;
+      let long = src.width.max(src.height).max(1) as f64;
+      let short = src.width.min(src.height).max(1) as f64;
+      let s = edge as f64 / if req.size_mode == "longEdge" { long } else { short };
+      let w = (src.width as f64 * s).round().max(1.0) as u32;
+      let h = (src.height as f64 * s).round().max(1.0) as u32;
+      Ok((w, h))
+    }
+    // Same aspect ratio, scaled so width × height lands on the target area.
+    "megapixels" => {
+      let mp = req.megapixels.unwrap_or(0.0);
+      if !mp.is_finite() || mp <= 0.0 {
+        return Err("Megapixels must be a positive number.".into());
       }
+      let area = (src.width.max(1) as f64) * (src.height.max(1) as f64);
+      let s = (mp * 1_000_000.0 / area).sqrt();
+      let w = (src.width as f64 * s).round().max(1.0) as u32;
+      let h = (src.height as f64 * s).round().max(1.0) as u32;
+      Ok((w, h))
     }
-  }
-  // When exporting multiple files to a single output directory (file mode),
-  // prefix with the parent folder name to reduce collisions.
-  if rel_prefix.is_empty() && root.is_none() && out_dir.is_some() {
-    if let Some(parent_name) = svg_path
-      .parent()
-      .and_then(|p| p.file_name())
-      .and_then(|s| s.to_str())
-      .filter(|s| !s.is_empty())
-    {
-      rel_prefix = parent_name.to_string();
+    "physical" => {
+      let p = req.physical.ok_or_else(|| "Physical size is required in Physical mode.".to_string())?;
+      let positive = |v: f64| v.is_finite() && v > 0.0;
+      if !positive(p.width) || !positive(p.dpi) || p.height.is_some_and(|h| !positive(h)) {
+        return Err("Physical size and DPI must be positive numbers.".into());
+      }
+      let w = p.to_pixels(p.width);
+      let h = match p.height {
+        Some(h) => p.to_pixels(h),
+        None => ((w as f64) * src.height as f64 / src.width.max(1) as f64).round().max(1.0) as u32,
+      };
+      Ok((w, h))
     }
+    _ => Err("Invalid size mode.".into()),
   }
+}
 
-  let final_name = if rel_prefix.is_empty() {
-    file_name
-  } else {
-    format!("{rel_prefix}_{file_name}")
-  };
-
-  if let Some(out_dir) = out_dir {
-    out_dir.join(final_name)
-  } else {
-    svg_path.with_file_name(final_name)
+/// Whether both output dimensions are given explicitly, so the aspect ratio may differ
+/// from the SVG's and `crop` decides between stretching and covering.
+pub(crate) fn is_fixed_frame(req: &ConvertRequest) -> bool {
+  match req.size_mode.as_str() {
+    "exact" => true,
+    "physical" => req.physical.is_some_and(|p| p.height.is_some()),
+    _ => false,
   }
 }
 
-fn render_one_with_stage(
-  svg_path: &Path,
-  req: &ConvertRequest,
-  root: Option<&Path>,
-  out_dir: Option<&Path>,
-  stage_tx: Sender<String>,
-) -> Result<(PathBuf, u32, u32), String> {
-  let _ = stage_tx.send("read".into());
-  let data = fs::read(svg_path).map_err(|e| e.to_string())?;
-
-  let _ = stage_tx.send("parse".into());
-  let opt = usvg::Options::default();
-  let tree = usvg::Tree::from_data(&data, &opt).map_err(|e| e.to_string())?;
+/// Renders a parsed tree at the size requested by `req` (background, crop, pixel cap included).
+pub(crate) fn render_tree(tree: &usvg::Tree, req: &ConvertRequest) -> Result<tiny_skia::Pixmap, String> {
+  let (out_w, out_h) = compute_output_size(req, &tree_size(tree))?;
+  render_tree_at(tree, req, out_w, out_h)
+}
 
-  let src_sz = {
-    let sz = tree.size();
-    SvgSize {
-      width: sz.width().ceil().max(1.0) as u32,
-      height: sz.height().ceil().max(1.0) as u32,
-    }
-  };
+/// Like [`render_tree`], but at an explicit output size.
+pub(crate) fn render_tree_at(
+  tree: &usvg::Tree,
+  req: &ConvertRequest,
+  out_w: u32,
+  out_h: u32,
+) -> Result<tiny_skia::Pixmap, String> {
+  render_tree_with_progress(tree, req, out_w, out_h, &mut |_| {})
+}
 
-  let (out_w, out_h) = compute_output_size(req, &src_sz)?;
+/// Like [`render_tree_at`], reporting 0–100 render progress for large outputs.
+fn render_tree_with_progress(
+  tree: &usvg::Tree,
+  req: &ConvertRequest,
+  out_w: u32,
+  out_h: u32,
+  on_progress: &mut dyn FnMut(u8),
+) -> Result<tiny_skia::Pixmap, String> {
   enforce_pixel_cap(out_w, out_h)?;
-
-  let _ = stage_tx.send("render".into());
-  let mut pixmap = tiny_skia::Pixmap::new(out_w, out_h)
-    .ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
-
-  if let Some(bg) = req.background.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
-    let c = parse_bg_color(bg).ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string())?;
-    pixmap.fill(c);
-  } else {
-    pixmap.fill(tiny_skia::Color::from_rgba8(0, 0, 0, 0));
-  }
+  let background = request_background(req)?;
 
   let size = tree.size();
   let src_w = size.width() as f32;
@@ -231,7 +670,14 @@ fn render_one_with_stage(
   // Default behavior:
   // - Scale mode: scale to exact output size.
   // - Exact mode + crop=true: scale to cover and center-crop (no stretching).
-  let transform = if req.size_mode == "exact" && req.crop.unwrap_or(false) {
+  let transform = if req.mask.as_deref() == Some("circle") {
+    // Cover the inscribed circle; the corners get masked off afterwards.
+    let d = out_w_f.min(out_h_f);
+    let scale = (d / src_w).max(d / src_h);
+    let tx = (out_w_f - (src_w * scale)) * 0.5;
+    let ty = (out_h_f - (src_h * scale)) * 0.5;
+    usvg::Transform::from_row(scale, 0.0, 0.0, scale, tx, ty)
+  } else if is_fixed_frame(req) && req.crop.unwrap_or(false) {
     let scale = (out_w_f / src_w).max(out_h_f / src_h);
     // Translate so the scaled SVG is centered, cropping equally from both sides.
     let tx = (out_w_f - (src_w * scale)) * 0.5;
@@ -243,32 +689,453 @@ fn render_one_with_stage(
     let sy = out_h_f / src_h;
     usvg::Transform::from_scale(sx, sy)
   };
-  let mut pm = pixmap.as_mut();
-  resvg::render(&tree, transform, &mut pm);
 
-  let _ = stage_tx.send("write".into());
-  let out_path = make_output_path(svg_path, root, out_dir, out_w, out_h);
-  if let Some(parent) = out_path.parent() {
+  // Post effects need the artwork's own alpha, so the background goes in afterwards.
+  if postfx::active(req) {
+    let pixmap = rasterize(tree, req, transform, out_w, out_h, None, on_progress)?;
+    finish_pixmap(pixmap, req, background)
+  } else {
+    let pixmap = rasterize(tree, req, transform, out_w, out_h, background, on_progress)?;
+    finish_pixmap(pixmap, req, None)
+  }
+}
+
+/// Renders one frame of a Lottie animation with the same effects an SVG render gets.
+fn render_lottie(
+  anim: &mut lottie::Animation,
+  req: &ConvertRequest,
+  out_w: u32,
+  out_h: u32,
+) -> Result<tiny_skia::Pixmap, String> {
+  enforce_pixel_cap(out_w, out_h)?;
+  let background = request_background(req)?;
+  let pixmap = anim.render(req, out_w, out_h)?;
+  finish_pixmap(pixmap, req, background)
+}
+
+fn request_background(req: &ConvertRequest) -> Result<Option<tiny_skia::Color>, String> {
+  match req.background.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    Some(bg) => Ok(Some(
+      parse_bg_color(bg).ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string())?,
+    )),
+    None => Ok(None),
+  }
+}
+
+// Everything layered on the rendered artwork: post effects, then the background (if not
+// already drawn), watermark, color filters and finally the mask.
+fn finish_pixmap(
+  pixmap: tiny_skia::Pixmap,
+  req: &ConvertRequest,
+  background: Option<tiny_skia::Color>,
+) -> Result<tiny_skia::Pixmap, String> {
+  let pixmap = if postfx::active(req) { postfx::apply(pixmap, req)? } else { pixmap };
+  let mut pixmap = match background {
+    Some(bg) => postfx::flatten(pixmap, bg)?,
+    None => pixmap,
+  };
+  if let Some(wm) = &req.watermark {
+    watermark::apply(&mut pixmap, wm)?;
+  }
+  filters::apply(&mut pixmap, req)?;
+  postfx::apply_mask(pixmap, req)
+}
+
+fn rasterize(
+  tree: &usvg::Tree,
+  req: &ConvertRequest,
+  transform: usvg::Transform,
+  out_w: u32,
+  out_h: u32,
+  background: Option<tiny_skia::Color>,
+  on_progress: &mut dyn FnMut(u8),
+) -> Result<tiny_skia::Pixmap, String> {
+  if req.raster_passthrough.unwrap_or(false) {
+    if let Some(raster) = passthrough::full_canvas_raster(tree) {
+      let pixmap = passthrough::draw(&raster, tree, transform, out_w, out_h, background)?;
+      on_progress(100);
+      return Ok(pixmap);
+    }
+  }
+  let filter = req.resample_filter.as_deref().map(Filter::parse).transpose()?;
+  if filter.is_none() && !req.linear_light.unwrap_or(false) {
+    if let Some(pixmap) = gpu::render(tree, req, transform, out_w, out_h, background) {
+      on_progress(100);
+      return Ok(pixmap);
+    }
+  }
+  if req.linear_light.unwrap_or(false) {
+    let pixmap = linear::render_linear(tree, transform, out_w, out_h, background, filter.unwrap_or(Filter::Box))?;
+    on_progress(100);
+    return Ok(pixmap);
+  }
+  if let Some(filter) = filter {
+    let pixmap = resample::render_resampled(tree, transform, out_w, out_h, background, filter, Transfer::IDENTITY)?;
+    on_progress(100);
+    return Ok(pixmap);
+  }
+
+  let mut pixmap = tiny_skia::Pixmap::new(out_w, out_h)
+    .ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  pixmap.fill(background.unwrap_or(tiny_skia::Color::from_rgba8(0, 0, 0, 0)));
+
+  if (out_w as u64) * (out_h as u64) < STRIP_RENDER_PIXELS {
+    let mut pm = pixmap.as_mut();
+    resvg::render(tree, transform, &mut pm);
+    on_progress(100);
+    return Ok(pixmap);
+  }
+
+  // Render band by band straight into the row slices of the final pixmap.
+  let stride = out_w as usize * 4;
+  let mut y = 0u32;
+  while y < out_h {
+    let rows = STRIP_ROWS.min(out_h - y);
+    let start = y as usize * stride;
+    let end = start + rows as usize * stride;
+    let mut strip = tiny_skia::PixmapMut::from_bytes(&mut pixmap.data_mut()[start..end], out_w, rows)
+      .ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+    resvg::render(tree, transform.post_translate(0.0, -(y as f32)), &mut strip);
+    y += rows;
+    on_progress(((y as u64 * 100) / out_h as u64) as u8);
+  }
+  Ok(pixmap)
+}
+
+// Batch timing: wall-clock elapsed plus a rolling completion rate for the ETA.
+struct RateTracker {
+  started: Instant,
+  recent: VecDeque<Instant>,
+}
+
+impl RateTracker {
+  fn new() -> Self {
+    Self {
+      started: Instant::now(),
+      recent: VecDeque::with_capacity(RATE_WINDOW + 1),
+    }
+  }
+
+  fn record(&mut self) {
+    self.recent.push_back(Instant::now());
+    if self.recent.len() > RATE_WINDOW {
+      self.recent.pop_front();
+    }
+  }
+
+  fn elapsed_ms(&self) -> u64 {
+    self.started.elapsed().as_millis() as u64
+  }
+
+  fn files_per_sec(&self) -> Option<f64> {
+    let last = *self.recent.back()?;
+    // With a single completion, measure from the batch start instead.
+    let (from, n) = if self.recent.len() >= 2 {
+      (*self.recent.front()?, self.recent.len() - 1)
+    } else {
+      (self.started, 1)
+    };
+    let secs = last.duration_since(from).as_secs_f64();
+    (secs > 0.0).then(|| n as f64 / secs)
+  }
+
+  fn eta_ms(&self, remaining: u32) -> Option<u64> {
+    let rate = self.files_per_sec()?;
+    Some(((remaining as f64 / rate) * 1000.0).round() as u64)
+  }
+}
+
+// What a batch item renders from.
+enum Source {
+  Svg(usvg::Tree),
+  Lottie(lottie::Animation),
+}
+
+enum StageUpdate {
+  Phase(&'static str),
+  Percent(u8),
+}
+
+// Encodes `pixmap` and writes it to `path`, then applies the request's file attributes.
+fn write_png(
+  pixmap: &tiny_skia::Pixmap,
+  encoding: PngEncoding,
+  path: PathBuf,
+  req: &ConvertRequest,
+  mtime: Option<SystemTime>,
+) -> Result<RenderOutput, String> {
+  let io_path = layout::io_path(&path)?;
+  let (out_w, out_h) = (pixmap.width(), pixmap.height());
+  if let Some(parent) = io_path.parent() {
     fs::create_dir_all(parent).map_err(|e| e.to_string())?;
   }
-  let png = pixmap.encode_png().map_err(|e| e.to_string())?;
-  fs::write(&out_path, png).map_err(|e| e.to_string())?;
-  Ok((out_path, out_w, out_h))
+  let mut png = encode_png(pixmap, encoding)?;
+  if let Some(meta) = &req.metadata {
+    png = metadata::embed(&png, meta);
+  }
+  let bytes = png.len() as u64;
+  make_writable(&io_path)?;
+  fs::write(&io_path, png).map_err(|e| e.to_string())?;
+  finish_file(&io_path, req, mtime)?;
+
+  let checksum = if req.verify.unwrap_or(false) {
+    Some(verify_written_png(&io_path, out_w, out_h)?)
+  } else {
+    None
+  };
+  Ok(RenderOutput {
+    path,
+    width: out_w,
+    height: out_h,
+    bytes,
+    checksum,
+    skipped: false,
+  })
+}
+
+/// Reads, renders and writes one source. Unless `slot` is low-power, PNG encoding runs on a
+/// separate thread that keeps the slot until it's done, and the result is only complete once
+/// [`PendingItem::wait`] returns. `on_conflict` settles where each output goes (`None` keeps
+/// the existing file), and may hand the slot back while it waits on the user.
+fn render_one_with_stage(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+  stage_tx: Sender<StageUpdate>,
+  on_conflict: &mut dyn FnMut(&Path, &mut Slot) -> Result<Option<PathBuf>, String>,
+  mut slot: Slot,
+  entry: Option<archive::Entry>,
+) -> Result<PendingItem, String> {
+  let _ = stage_tx.send(StageUpdate::Phase("read"));
+  gpu::take_used();
+  // Archive entries streamed into the batch arrive already read, with their own mtime.
+  let (raw, entry_mtime) = match entry {
+    Some(entry) => (input::from_bytes(svg_path, entry.data)?, Some(entry.mtime)),
+    None => (input::read_source(svg_path)?, None),
+  };
+  // Build tools and asset managers compare mtimes to spot changed sources.
+  let source_mtime = if req.preserve_mtime.unwrap_or(false) {
+    match entry_mtime {
+      Some(mtime) => mtime,
+      None => Some(fs::metadata(svg_path).and_then(|m| m.modified()).map_err(|e| e.to_string())?),
+    }
+  } else {
+    None
+  };
+
+  let _ = stage_tx.send(StageUpdate::Phase("parse"));
+  let (data, mut source, warnings) = if lottie::is_lottie(svg_path) {
+    let anim = lottie::Animation::load(&raw, svg_path)?;
+    (Cow::Borrowed(&raw[..]), Source::Lottie(anim), Vec::new())
+  } else {
+    let warnings = inspect_svg(&raw).warnings();
+    let data = preprocess_svg(&raw, req)?;
+    let opt = usvg_options(req);
+    let tree = usvg::Tree::from_data(&data, &opt).map_err(|e| e.to_string())?;
+    (data, Source::Svg(tree), warnings)
+  };
+  let src_size = match &source {
+    Source::Svg(tree) => tree_size(tree),
+    Source::Lottie(anim) => anim.size(),
+  };
+
+  let (base_w, base_h) = compute_output_size(req, &src_size)?;
+  let plan = layout::plan_outputs(svg_path, req, root, out_dir, base_w, base_h)?;
+  let encoding = PngEncoding::from_request(req)?;
+
+  let vector_format = svgout::vector_format(req);
+  // Every destination is settled before anything renders, so an item waiting on the user
+  // isn't holding a rendered pixmap or an encoder.
+  let mut targets = Vec::with_capacity(plan.targets.len());
+  for mut target in plan.targets {
+    if let Some(ext) = vector_format {
+      if matches!(source, Source::Lottie(_)) {
+        return Err("Lottie files can only be exported as PNG.".into());
+      }
+      target.path.set_extension(ext);
+      if target.path == svg_path {
+        return Err("SVG output would overwrite the source file.".into());
+      }
+    }
+    let settled = on_conflict(&target.path, &mut slot)?;
+    targets.push((target, settled));
+  }
+
+  let mut outputs = Vec::with_capacity(targets.len());
+  // Started with the first PNG to encode; it needs its own copy of the request.
+  let mut encoder: Option<Encoder> = None;
+  // Low-power items encode in line, keeping the slot until they return.
+  let pipelined = !slot.low_power();
+  let mut slot = Some(slot);
+  // Color-vision variants reuse the render of the output they follow.
+  let keep_render = targets.iter().any(|(t, _)| t.color_vision.is_some());
+  let mut last_render: Option<(u32, u32, tiny_skia::Pixmap)> = None;
+  for (mut target, settled) in targets {
+    match settled {
+      Some(path) => target.path = path,
+      None => {
+        outputs.push(PendingOutput::Ready(RenderOutput {
+          bytes: layout::io_path(&target.path)
+            .and_then(|p| fs::metadata(p).map_err(|e| e.to_string()))
+            .map_or(0, |m| m.len()),
+          path: target.path,
+          width: target.width,
+          height: target.height,
+          checksum: None,
+          skipped: true,
+        }));
+        continue;
+      }
+    }
+
+    let io_path = layout::io_path(&target.path)?;
+    if let Some(ext) = vector_format {
+      let _ = stage_tx.send(StageUpdate::Phase("write"));
+      if let Some(parent) = io_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+      }
+      let Source::Svg(tree) = &source else { unreachable!() };
+      let text = match ext {
+        "eps" => eps::write_eps(tree, req, target.width, target.height)?,
+        _ => svgout::write_svg(tree, &data, req, target.width, target.height)?,
+      };
+      make_writable(&io_path)?;
+      fs::write(&io_path, &text).map_err(|e| e.to_string())?;
+      finish_file(&io_path, req, source_mtime)?;
+      outputs.push(PendingOutput::Ready(RenderOutput {
+        path: target.path,
+        width: target.width,
+        height: target.height,
+        bytes: text.len() as u64,
+        checksum: None,
+        skipped: false,
+      }));
+      continue;
+    }
+
+    let cached = last_render
+      .as_ref()
+      .filter(|(w, h, _)| target.color_vision.is_some() && (*w, *h) == (target.width, target.height))
+      .map(|(_, _, p)| p.clone());
+    let mut pixmap = match cached {
+      Some(pixmap) => pixmap,
+      None => {
+        let _ = stage_tx.send(StageUpdate::Phase("render"));
+        let mut last_percent = 0u8;
+        let pixmap = match &mut source {
+          Source::Svg(tree) => render_tree_with_progress(tree, req, target.width, target.height, &mut |p| {
+            if p != last_percent {
+              last_percent = p;
+              let _ = stage_tx.send(StageUpdate::Percent(p));
+            }
+          })?,
+          Source::Lottie(anim) => render_lottie(anim, req, target.width, target.height)?,
+        };
+        let pixmap = match &req.tile {
+          Some(t) => postfx::tile(&pixmap, t, target.width as f32 / base_w as f32)?,
+          None => pixmap,
+        };
+        if keep_render {
+          last_render = Some((target.width, target.height, pixmap.clone()));
+        }
+        pixmap
+      }
+    };
+    if let Some(kind) = target.color_vision {
+      filters::simulate_color_vision(&mut pixmap, kind);
+    }
+
+    let _ = stage_tx.send(StageUpdate::Phase("write"));
+    if pipelined {
+      let encoder = encoder.get_or_insert_with(|| {
+        let slot = slot.take().unwrap_or_else(Slot::unqueued);
+        Encoder::spawn(Arc::new(req.clone()), source_mtime, slot)
+      });
+      encoder
+        .tx
+        .send((pixmap, encoding, target.path))
+        .map_err(|_| "PNG encoder stopped early.".to_string())?;
+      outputs.push(PendingOutput::Encoding);
+    } else {
+      outputs.push(PendingOutput::Ready(write_png(&pixmap, encoding, target.path, req, source_mtime)?));
+    }
+  }
+
+  for (path, contents) in plan.extra_files {
+    let io_path = layout::io_path(&path)?;
+    // The PNGs beside it may not have been written yet to create the folder.
+    if let Some(parent) = io_path.parent() {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(io_path, contents).map_err(|e| e.to_string())?;
+  }
+  let engine = if gpu::take_used() { "vello" } else { "resvg" };
+  Ok(PendingItem {
+    outputs,
+    // Closing the channel lets the encoder finish once it has written what was sent.
+    encoded: encoder.map(|e| e.handle),
+    warnings,
+    engine,
+  })
+}
+
+// Outputs from an earlier read-only export would otherwise fail to overwrite.
+fn make_writable(path: &Path) -> Result<(), String> {
+  let Ok(meta) = fs::metadata(path) else { return Ok(()) };
+  let mut perms = meta.permissions();
+  if perms.readonly() {
+    #[allow(clippy::permissions_set_readonly_false)]
+    perms.set_readonly(false);
+    fs::set_permissions(path, perms).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+// Applies the requested mtime and permissions to a written output; permissions go last
+// since a read-only file can't have its time set.
+fn finish_file(path: &Path, req: &ConvertRequest, mtime: Option<SystemTime>) -> Result<(), String> {
+  if let Some(mtime) = mtime {
+    fs::File::options()
+      .write(true)
+      .open(path)
+      .and_then(|f| f.set_modified(mtime))
+      .map_err(|e| e.to_string())?;
+  }
+  let mode = parse_file_mode(req)?;
+  if mode.is_none() && !req.read_only.unwrap_or(false) {
+    return Ok(());
+  }
+  let mut perms = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+  #[cfg(unix)]
+  if let Some(mode) = mode {
+    use std::os::unix::fs::PermissionsExt;
+    perms.set_mode(mode);
+  }
+  if req.read_only.unwrap_or(false) {
+    perms.set_readonly(true);
+  }
+  fs::set_permissions(path, perms).map_err(|e| e.to_string())
+}
+
+fn parse_file_mode(req: &ConvertRequest) -> Result<Option<u32>, String> {
+  let Some(mode) = req.file_mode.as_deref().map(str::trim).filter(|s| !s.is_empty()) else {
+    return Ok(None);
+  };
+  let mode = mode.strip_prefix("0o").unwrap_or(mode);
+  match u32::from_str_radix(mode, 8) {
+    Ok(m) if m <= 0o7777 => Ok(Some(m)),
+    _ => Err("Invalid file mode (expected octal permission bits like 644).".into()),
+  }
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub fn count_svg_files(dir_path: String) -> Result<u32, String> {
+pub fn count_svg_files(dir_path: String, walk: Option<WalkOptions>) -> Result<u32, String> {
   let p = PathBuf::from(dir_path);
   if !p.is_dir() {
     return Err("Invalid folder path.".into());
   }
-  let mut count = 0u32;
-  for e in WalkDir::new(&p).into_iter().filter_map(Result::ok) {
-    if e.file_type().is_file() && is_svg(e.path()) {
-      count += 1;
-    }
-  }
-  Ok(count)
+  Ok(walk::svg_files(&p, walk.unwrap_or_default()).count() as u32)
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -281,7 +1148,11 @@ pub fn get_svg_size(svg_path: String) -> Result<SvgSize, String> {
 }
 
 #[tauri::command(rename_all = "camelCase")]
-pub fn scan_svg_folder_sizes(dir_path: String) -> Result<FolderSizeInfo, String> {
+pub fn scan_svg_folder_sizes(
+  dir_path: String,
+  histogram: Option<bool>,
+  walk: Option<WalkOptions>,
+) -> Result<FolderSizeInfo, String> {
   let p = PathBuf::from(dir_path);
   if !p.is_dir() {
     return Err("Invalid folder path.".into());
@@ -298,17 +1169,55 @@ pub fn scan_svg_folder_sizes(dir_path: String) -> Result<FolderSizeInfo, String>
   // but keep counting total SVG files.
   let mut keep_parsing = true;
 
-  for e in WalkDir::new(&p).into_iter().filter_map(Result::ok) {
-    if !e.file_type().is_file() || !is_svg(e.path()) {
-      continue;
-    }
+  // The histogram needs every size, so it keeps parsing past the first mismatch.
+  let want_histogram = histogram.unwrap_or(false);
+  let mut counts: std::collections::HashMap<(u32, u32), u32> = std::collections::HashMap::new();
+  let mut flagged: Vec<FlaggedSvg> = Vec::new();
+  let mut invalid: Vec<InvalidSvg> = Vec::new();
+
+  for path in walk::svg_files(&p, walk.unwrap_or_default()) {
     total += 1;
 
-    if !keep_parsing {
+    // Every file is read for the content flags; parsing is what the early exit saves.
+    let data = match input::read_source(&path) {
+      Ok(d) => d,
+      Err(err) => {
+        invalid.push(InvalidSvg {
+          path: path.to_string_lossy().to_string(),
+          error: err,
+        });
+        continue;
+      }
+    };
+    let features = inspect_svg(&data);
+    if features.any() {
+      flagged.push(FlaggedSvg {
+        path: path.to_string_lossy().to_string(),
+        features,
+      });
+    }
+
+    if !keep_parsing && !want_histogram {
       continue;
     }
 
-    let sz = read_svg_size(e.path())?;
+    // A bad file is listed rather than failing the whole scan.
+    let sz = match svg_size_from_data(&data) {
+      Ok(sz) => sz,
+      Err(error) => {
+        invalid.push(InvalidSvg {
+          path: path.to_string_lossy().to_string(),
+          error,
+        });
+        continue;
+      }
+    };
+    if want_histogram {
+      *counts.entry((sz.width, sz.height)).or_insert(0) += 1;
+      if !keep_parsing {
+        continue;
+      }
+    }
     if base_size.is_none() {
       base_size = Some(sz.clone());
     } else if let Some(bs) = base_size.as_ref() {
@@ -345,193 +1254,925 @@ pub fn scan_svg_folder_sizes(dir_path: String) -> Result<FolderSizeInfo, String>
     }
   }
 
+  let histogram = want_histogram.then(|| {
+    let mut h: Vec<SizeCount> = counts
+      .into_iter()
+      .map(|((width, height), count)| SizeCount { width, height, count })
+      .collect();
+    h.sort_by(|a, b| b.count.cmp(&a.count).then(a.width.cmp(&b.width)).then(a.height.cmp(&b.height)));
+    h
+  });
+
   Ok(FolderSizeInfo {
     total,
     all_same,
     base_size,
     unique_sizes,
+    histogram,
+    flagged,
+    invalid,
+  })
+}
+
+// One page of a folder listing, in the order a batch with the same sorting would convert it.
+fn list_svgs(
+  dir: &Path,
+  offset: Option<u32>,
+  limit: Option<u32>,
+  walk: WalkOptions,
+  sort_by: Option<&str>,
+  sort_desc: bool,
+) -> Result<SvgFileList, String> {
+  let mut paths: Vec<PathBuf> = walk::svg_files(dir, walk).collect();
+  walk::sort_paths(&mut paths, sort_by, sort_desc)?;
+
+  let total = paths.len() as u32;
+  let offset = offset.unwrap_or(0).min(total);
+  let limit = limit.unwrap_or(200).max(1);
+  let items = paths
+    .iter()
+    .skip(offset as usize)
+    .take(limit as usize)
+    .map(|path| {
+      let bytes = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+      let (size, error) = match read_svg_size(path) {
+        Ok(sz) => (Some(sz), None),
+        Err(err) => (None, Some(err)),
+      };
+      SvgFileEntry {
+        path: path.to_string_lossy().to_string(),
+        width: size.as_ref().map(|s| s.width),
+        height: size.as_ref().map(|s| s.height),
+        bytes,
+        error,
+      }
+    })
+    .collect();
+
+  Ok(SvgFileList { total, offset, items })
+}
+
+/// Lists every SVG in a folder with its size, one page at a time (sizes are only parsed
+/// for the requested page). Unparsable files are listed with an error instead of failing.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_svg_files(
+  dir_path: String,
+  offset: Option<u32>,
+  limit: Option<u32>,
+  walk: Option<WalkOptions>,
+  sort_by: Option<String>,
+  sort_desc: Option<bool>,
+) -> Result<SvgFileList, String> {
+  let dir = PathBuf::from(dir_path);
+  if !dir.is_dir() {
+    return Err("Invalid folder path.".into());
+  }
+  tauri::async_runtime::spawn_blocking(move || {
+    list_svgs(
+      &dir,
+      offset,
+      limit,
+      walk.unwrap_or_default(),
+      sort_by.as_deref(),
+      sort_desc.unwrap_or(false),
+    )
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct BatchEstimate {
+  pub files: u32,
+  pub outputs: u32,
+  pub unreadable: u32,
+  #[ts(type = "number")]
+  pub total_pixels: u64,
+  pub sampled: u32,
+  pub bytes_per_pixel: Option<f64>,
+  #[ts(type = "number | null")]
+  pub estimated_bytes: Option<u64>,
+}
+
+fn estimate_batch(req: &ConvertRequest, samples: u32) -> Result<BatchEstimate, String> {
+  let svgs = collect_svgs(req)?;
+  let input_path = PathBuf::from(&req.input_path);
+  let root = (req.input_mode == "folder").then_some(input_path.as_path());
+  let mut rules = RuleSet::build(req)?;
+  for rule_req in rules.requests_mut() {
+    presets::resolve(rule_req)?;
+  }
+
+  let mut total_pixels = 0u64;
+  let mut outputs = 0u32;
+  let mut unreadable = 0u32;
+  for svg in &svgs {
+    let Ok(src) = read_svg_size(svg) else {
+      unreadable += 1;
+      continue;
+    };
+    let req = &item_request(&rules, svg, req);
+    let out_dir = req.output_dir.as_ref().map(PathBuf::from);
+    let (w, h) = compute_output_size(req, &src)?;
+    for t in layout::plan_outputs(svg, req, root, out_dir.as_deref(), w, h)?.targets {
+      total_pixels += t.width as u64 * t.height as u64;
+      outputs += 1;
+    }
+  }
+
+  // Encode a few evenly spaced files (scaled down if large) to learn bytes per pixel.
+  let step = (svgs.len() / samples.max(1) as usize).max(1);
+  let (mut sample_bytes, mut sample_pixels, mut sampled) = (0u64, 0u64, 0u32);
+  for svg in svgs.iter().step_by(step).take(samples as usize) {
+    let Ok(data) = input::read_source(svg) else { continue };
+    let Ok(data) = preprocess_svg(&data, req) else { continue };
+    let Ok(tree) = usvg::Tree::from_data(&data, &usvg_options(req)) else {
+      continue;
+    };
+    let Ok((w, h)) = compute_output_size(req, &tree_size(&tree)) else {
+      continue;
+    };
+    let pixels = w as u64 * h as u64;
+    let shrink = if pixels > ESTIMATE_SAMPLE_PIXELS {
+      (ESTIMATE_SAMPLE_PIXELS as f64 / pixels as f64).sqrt()
+    } else {
+      1.0
+    };
+    let sw = ((w as f64 * shrink).round() as u32).max(1);
+    let sh = ((h as f64 * shrink).round() as u32).max(1);
+    let Ok(pixmap) = render_tree_at(&tree, req, sw, sh) else {
+      continue;
+    };
+    let Ok(png) = encode_png(&pixmap, PngEncoding::from_request(req).unwrap_or_default()) else {
+      continue;
+    };
+    sample_bytes += png.len() as u64;
+    sample_pixels += sw as u64 * sh as u64;
+    sampled += 1;
+  }
+  let bytes_per_pixel = (sample_pixels > 0).then(|| sample_bytes as f64 / sample_pixels as f64);
+
+  Ok(BatchEstimate {
+    files: svgs.len() as u32,
+    outputs,
+    unreadable,
+    total_pixels,
+    sampled,
+    bytes_per_pixel,
+    estimated_bytes: bytes_per_pixel.map(|bpp| (bpp * total_pixels as f64).round() as u64),
   })
 }
 
+/// Estimates total pixels and output bytes for a planned batch so the UI can warn
+/// before a huge export. Takes the same settings object the converter uses.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn estimate_batch_output(mut req: ConvertRequest, samples: Option<u32>) -> Result<BatchEstimate, String> {
+  presets::resolve(&mut req)?;
+  tauri::async_runtime::spawn_blocking(move || estimate_batch(&req, samples.unwrap_or(5)))
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Converts the batch `req` describes; every option lives on `ConvertRequest`. Progress is
+/// broadcast as `convert-progress` / `convert-item` / `convert-stats` window events.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn convert_svg_to_png(window: tauri::Window, req: ConvertRequest) -> Result<ConvertSummary, String> {
+  run_batch(window.clone(), req, BatchEvents::Window(window)).await
+}
+
+/// Like [`convert_svg_to_png`], but streams progress, items, stats and the final summary
+/// through the caller's channel, so only this invocation sees them.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn convert_svg_to_png(
+pub async fn convert_svg_to_png_streamed(
   window: tauri::Window,
-  input_mode: String,
-  input_path: String,
-  input_paths: Option<Vec<String>>,
-  output_dir: Option<String>,
-  size_mode: String,
-  scale: Option<f64>,
-  width: Option<u32>,
-  height: Option<u32>,
-  crop: Option<bool>,
-  background: Option<String>,
+  req: ConvertRequest,
+  on_event: Channel<BatchMessage>,
 ) -> Result<ConvertSummary, String> {
-  let req = ConvertRequest {
-    input_mode,
-    input_path,
-    output_dir,
-    size_mode,
-    scale,
-    width,
-    height,
-    crop,
-    background,
-  };
-  let input_path = PathBuf::from(&req.input_path);
-  if req.input_mode == "folder" {
-    if !input_path.is_dir() {
-      return Err("Invalid folder path.".into());
+  let app = window.app_handle().clone();
+  run_batch(window, req, BatchEvents::Channel(app, on_event)).await
+}
+
+/// One message on a batch's channel, mirroring the window events.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+#[serde(tag = "event", content = "data", rename_all = "camelCase")]
+pub enum BatchMessage {
+  Progress(ConvertProgressEvent),
+  Item(ConvertItemEvent),
+  Stats(ConvertStatsEvent),
+  Upload(UploadProgressEvent),
+  Summary(ConvertSummary),
+}
+
+impl BatchMessage {
+  pub(crate) fn batch_id(&self) -> &str {
+    match self {
+      Self::Progress(p) => &p.batch_id,
+      Self::Item(item) => &item.batch_id,
+      Self::Stats(stats) => &stats.batch_id,
+      Self::Upload(upload) => &upload.batch_id,
+      Self::Summary(summary) => &summary.batch_id,
+    }
+  }
+}
+
+// Where a batch reports to: window events for the original command, or the caller's channel.
+// Either way, detached progress windows watching the batch get a copy.
+#[derive(Clone)]
+pub(crate) enum BatchEvents {
+  Window(tauri::Window),
+  Channel(tauri::AppHandle, Channel<BatchMessage>),
+}
+
+impl BatchEvents {
+  fn send(&self, msg: BatchMessage) {
+    let app = match self {
+      Self::Window(window) => window.app_handle(),
+      Self::Channel(app, _) => app,
+    };
+    if let Some(active) = app.try_state::<ActiveBatches>() {
+      active.update(&msg);
+    }
+    if let Some(monitors) = app.try_state::<BatchMonitors>() {
+      monitors.forward(app, &msg);
+    }
+    let sent = match (self, msg) {
+      (Self::Channel(_, channel), msg) => channel.send(msg),
+      (Self::Window(window), BatchMessage::Progress(p)) => window.emit("convert-progress", p),
+      (Self::Window(window), BatchMessage::Item(item)) => window.emit("convert-item", item),
+      (Self::Window(window), BatchMessage::Stats(stats)) => window.emit("convert-stats", stats),
+      (Self::Window(window), BatchMessage::Upload(upload)) => window.emit("upload-progress", upload),
+      // The command's return value already carries the summary.
+      (Self::Window(_), BatchMessage::Summary(_)) => Ok(()),
+    };
+    if let Err(err) = sent {
+      log::warn!("failed to send batch event: {err}");
     }
   }
+}
+
+// The settings one file is converted with: its matching rule, then any size its name encodes.
+pub(crate) fn item_request(rules: &RuleSet, svg: &Path, req: &ConvertRequest) -> ConvertRequest {
+  let mut item = rules.request_for(svg, req).clone();
+  presets::apply_file_name(svg, &mut item);
+  item
+}
 
+// Option checks shared by the batch request and every rule's merged request.
+// A watermark overlay is decoded here, once per request.
+pub(crate) fn validate_request(req: &mut ConvertRequest) -> Result<(), String> {
   if let Some(bg) = req.background.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
     if parse_bg_color(bg).is_none() {
       return Err("Invalid background color (expected #RRGGBB).".into());
     }
   }
 
-  let out_dir = req.output_dir.as_ref().map(PathBuf::from);
+  if let Some(t) = req.target.as_deref() {
+    layout::validate_target(t)?;
+  }
+  if let Some(g) = req.group_by.as_deref() {
+    layout::validate_group_by(g)?;
+  }
+  if let Some(f) = req.file_name_form.as_deref() {
+    layout::validate_file_name_form(f)?;
+  }
+  parse_file_mode(req)?;
+  if let Some(tags) = &req.finder_tags {
+    finder::validate(tags)?;
+  }
+  preprocess::validate_options(req)?;
+  PngEncoding::from_request(req)?;
+  if let Some(f) = req.resample_filter.as_deref() {
+    Filter::parse(f)?;
+  }
+  if let Some(e) = req.engine.as_deref() {
+    gpu::validate_engine(e)?;
+  }
+  postfx::validate(req)?;
+  filters::validate(req)?;
+  svgout::validate_output_format(req)?;
+  if let Some(wm) = &mut req.watermark {
+    watermark::validate(wm)?;
+  }
+  Ok(())
+}
 
+// The SVGs a request selects: the folder's files (minus exclusions, sorted) or the given files.
+pub(crate) fn collect_svgs(req: &ConvertRequest) -> Result<Vec<PathBuf>, String> {
+  let input_path = PathBuf::from(&req.input_path);
   let mut svgs: Vec<PathBuf> = Vec::new();
   if req.input_mode == "folder" {
-    for e in WalkDir::new(&input_path).into_iter().filter_map(Result::ok) {
-      if e.file_type().is_file() && is_svg(e.path()) {
-        svgs.push(e.path().to_path_buf());
-      }
+    if !input_path.is_dir() {
+      return Err("Invalid folder path.".into());
     }
-    svgs.sort();
+    let excluded: std::collections::HashSet<PathBuf> =
+      req.exclude.iter().flatten().map(PathBuf::from).collect();
+    svgs = walk::input_files(&input_path, req.walk.unwrap_or_default())
+      .filter(|p| !excluded.contains(p))
+      .collect();
+    walk::sort_paths(&mut svgs, req.sort_by.as_deref(), req.sort_desc.unwrap_or(false))?;
   } else {
-    let provided = input_paths.unwrap_or_default();
+    let provided = req.input_paths.as_deref().unwrap_or_default();
     if !provided.is_empty() {
       for p in provided {
         let pb = PathBuf::from(p);
-        if !pb.is_file() || !is_svg(&pb) {
+        if !pb.is_file() || !is_input(&pb) {
           return Err("Invalid SVG file path.".into());
         }
         svgs.push(pb);
       }
     } else {
-      if !input_path.is_file() || !is_svg(&input_path) {
+      if !input_path.is_file() || !is_input(&input_path) {
         return Err("Invalid SVG file path.".into());
       }
       svgs.push(input_path.clone());
     }
   }
+  Ok(svgs)
+}
+
+/// Converts every SVG selected by `req`, reporting progress through `events`, then calls the
+/// webhook in settings, if any. Batches share no state beyond the registries keyed by batch
+/// id, so several can run at once.
+pub(crate) async fn run_batch(
+  window: tauri::Window,
+  req: ConvertRequest,
+  events: BatchEvents,
+) -> Result<ConvertSummary, String> {
+  let started = Instant::now();
+  let (input_path, output_dir) = (req.input_path.clone(), req.output_dir.clone());
+  let result = convert_batch(window.clone(), req, events).await;
+  // Told about failures too, including requests refused before anything ran.
+  if let Some(url) = window.try_state::<SettingsStore>().and_then(|s| s.get().webhook.url) {
+    webhook::notify(&url, &input_path, output_dir.as_deref(), started.elapsed(), &result);
+  }
+  result
+}
+
+async fn convert_batch(
+  window: tauri::Window,
+  mut req: ConvertRequest,
+  events: BatchEvents,
+) -> Result<ConvertSummary, String> {
+  presets::resolve(&mut req)?;
+  validate_request(&mut req)?;
+  let conflict_policy = req.on_conflict.clone().unwrap_or_else(|| "overwrite".into());
+  conflict::validate_policy(&conflict_policy)?;
+  sftp::validate(&req)?;
+  // Archive input runs over an unpacked copy (held until the batch ends) or straight out of
+  // the archive. Unpacking is slow disk work, so it stays off the async runtime.
+  let window_for_archive = window.clone();
+  let (prepared, mut req) = tauri::async_runtime::spawn_blocking(move || {
+    archive::prepare(&window_for_archive, &mut req).map(|prepared| (prepared, req))
+  })
+  .await
+  .map_err(|e| e.to_string())??;
+  let (_unpacked, streamed) = match prepared {
+    Some(archive::Prepared::Unpacked(unpacked)) => (Some(unpacked), None),
+    Some(archive::Prepared::Streamed(streamed)) => (None, Some(streamed)),
+    None => (None, None),
+  };
+  // Likewise, a remote destination is written to a local staging folder first, once the
+  // server has answered.
+  let window_for_sftp = window.clone();
+  let (staging, mut req) = tauri::async_runtime::spawn_blocking(move || {
+    sftp::stage_output(&window_for_sftp, &mut req).map(|staging| (staging, req))
+  })
+  .await
+  .map_err(|e| e.to_string())??;
+  let input_path = PathBuf::from(&req.input_path);
+  // Attribution is a setting rather than a request option, so it applies to every batch.
+  let settings = window.try_state::<SettingsStore>().map(|s| s.get()).unwrap_or_default();
+  req.metadata = Some(settings.metadata).filter(|m| !m.is_empty());
+  // Connected up front, so a missing bucket or key fails before anything is converted.
+  let upload_target = if req.upload.unwrap_or(false) {
+    Some(upload::Target::connect(&settings.upload).await?)
+  } else {
+    None
+  };
+  // Duplicates are looked for across the whole output tree, earlier run folders included.
+  let tree_root = req.output_dir.clone().filter(|s| !s.trim().is_empty()).map(PathBuf::from);
+  // Rules inherit the run folder unless they name their own output dir.
+  let mut run_dir = None;
+  if req.run_folder.unwrap_or(false) {
+    let base = req.output_dir.as_deref().map(str::trim).filter(|s| !s.is_empty());
+    let base = base.ok_or_else(|| "A run folder needs an output folder.".to_string())?;
+    let dir = layout::create_run_dir(Path::new(base))?;
+    req.output_dir = Some(dir.to_string_lossy().to_string());
+    run_dir = req.output_dir.clone();
+  }
+  let mut rules = RuleSet::build(&req)?;
+  for rule_req in rules.requests_mut() {
+    presets::resolve(rule_req)?;
+    validate_request(rule_req)?;
+  }
+  let conflicts = window.try_state::<ConflictRegistry>().map(|s| s.inner().clone());
+  let claims = conflict::Claims::default();
+
+  let out_dir = req.output_dir.as_ref().map(PathBuf::from);
+
+  // Streamed archive entries are listed up front; their bytes are read as the loop reaches them.
+  let (svgs, source_sizes): (Vec<PathBuf>, Vec<u64>) = match &streamed {
+    Some(streamed) => streamed.files().into_iter().unzip(),
+    None => (collect_svgs(&req)?, Vec::new()),
+  };
+  let streamed = streamed.map(|s| Arc::new(Mutex::new(s)));
+  let mut duplicate_index = if req.detect_duplicates.unwrap_or(false) {
+    let root = tree_root.or_else(|| batch_report_dir(&req, None, &input_path, &svgs));
+    root.map(|root| DuplicateIndex::scan(&root, req.walk.unwrap_or_default()))
+  } else {
+    None
+  };
+  let mut duplicates: Vec<DuplicateOutput> = Vec::new();
 
   let total = svgs.len() as u32;
   let mut ok = 0u32;
   let mut failed = 0u32;
+  let mut records: Vec<OutputRecord> = Vec::new();
+  let mut written: Vec<PathBuf> = Vec::new();
+  let batch_id = uuid::Uuid::new_v4().to_string();
+  let mut rate = RateTracker::new();
+  let mut stats = ConvertStats::default();
+  let mut item_times: Vec<(u64, String)> = Vec::with_capacity(svgs.len());
+  let _active = window.try_state::<ActiveBatches>().map(|a| a.start(&batch_id, &req));
+  let queue = window.try_state::<JobQueue>().map(|q| q.inner().clone());
+  let _job = queue
+    .as_ref()
+    .map(|q| q.add(&batch_id, req.priority.unwrap_or(0), req.low_power.unwrap_or(false)));
+  let mut pace = Duration::ZERO;
+  let progress_file = ProgressFile::for_request(&window, &req, &batch_id)?;
 
-  let _ = window.emit(
-    "convert-progress",
-    ConvertProgressEvent {
-      phase: "start".into(),
-      current: 0,
-      active: None,
-      total,
-      ok,
-      failed,
-      last_svg: None,
-    },
-  );
-
-  for (i, svg) in svgs.iter().enumerate() {
-    let index = (i as u32) + 1;
-    let svg_str = svg.to_string_lossy().to_string();
-
-    let req_cloned = req.clone();
-    let svg_cloned = svg.clone();
-    let root = if req.input_mode == "folder" { Some(input_path.clone()) } else { None };
-    let out_dir_for_task = out_dir.clone();
-
-    let (stage_tx, stage_rx) = std::sync::mpsc::channel::<String>();
-    let win_for_stage = window.clone();
-    let svg_for_stage = svg_str.clone();
-    let stage_handle = tauri::async_runtime::spawn_blocking(move || {
-      while let Ok(stage) = stage_rx.recv() {
-        let _ = win_for_stage.emit(
-          "convert-progress",
-          ConvertProgressEvent {
-            phase: stage,
+  let start = ConvertProgressEvent {
+    version: EVENT_VERSION,
+    batch_id: batch_id.clone(),
+    phase: "start".into(),
+    current: 0,
+    active: None,
+    total,
+    ok,
+    failed,
+    last_svg: None,
+    item_percent: None,
+    elapsed_ms: 0,
+    files_per_sec: None,
+    eta_ms: None,
+  };
+  if let Some(file) = &progress_file {
+    file.write(&start);
+  }
+  events.send(BatchMessage::Progress(start));
+
+  // Items go through a two-stage pipeline: each iteration renders the next file, then reports
+  // the one before it, whose PNGs were encoding on other threads meanwhile. The extra
+  // iteration at the end drains the last item.
+  let mut in_flight: Option<InFlight> = None;
+  for i in 0..=svgs.len() {
+    let next = if let Some(svg) = svgs.get(i) {
+      let index = (i as u32) + 1;
+      let svg_str = svg.to_string_lossy().to_string();
+
+      let req_cloned = item_request(&rules, svg, &req);
+      let svg_cloned = svg.clone();
+      let root = if req.input_mode == "folder" { Some(input_path.clone()) } else { None };
+      let out_dir_for_task = req_cloned.output_dir.as_ref().map(PathBuf::from);
+      let finder_tags = req_cloned.finder_tags.clone();
+      let renamed_from = layout::renamed_stem(svg, &req_cloned, root.as_deref(), out_dir_for_task.as_deref());
+
+      let (stage_tx, stage_rx) = std::sync::mpsc::channel::<StageUpdate>();
+      let events_for_stage = events.clone();
+      let svg_for_stage = svg_str.clone();
+      let batch_for_stage = batch_id.clone();
+      let started = rate.started;
+      let files_per_sec = rate.files_per_sec();
+      let eta_ms = rate.eta_ms(total - index + 1);
+      let stage_handle = tauri::async_runtime::spawn_blocking(move || {
+        let mut phase = "read";
+        while let Ok(update) = stage_rx.recv() {
+          let item_percent = match update {
+            StageUpdate::Phase(p) => {
+              phase = p;
+              None
+            }
+            StageUpdate::Percent(p) => Some(p),
+          };
+          events_for_stage.send(BatchMessage::Progress(ConvertProgressEvent {
+            version: EVENT_VERSION,
+            batch_id: batch_for_stage.clone(),
+            phase: phase.into(),
             current: index,
             active: Some(index),
             total,
             ok: ok,       // last known from main loop; updated after item finishes
             failed: failed,
             last_svg: Some(svg_for_stage.clone()),
-          },
-        );
-      }
-    });
+            item_percent,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            files_per_sec,
+            eta_ms,
+          }));
+        }
+      });
 
-    let res = tauri::async_runtime::spawn_blocking(move || {
-      render_one_with_stage(
-        &svg_cloned,
-        &req_cloned,
-        root.as_ref().map(|p| p.as_path()),
-        out_dir_for_task.as_ref().map(|p| p.as_path()),
-        stage_tx,
-      )
-    })
-    .await
-    .map_err(|e| e.to_string())?;
+      // Wait for this batch's turn at a render slot, resting first if it runs in low-power mode.
+      let slot = match queue.clone() {
+        Some(queue) => {
+          let batch = batch_id.clone();
+          let acquire = tauri::async_runtime::spawn_blocking(move || {
+            if queue.is_low_power(&batch) {
+              std::thread::sleep(pace);
+            }
+            queue.acquire(&batch)
+          });
+          Some(acquire.await.map_err(|e| e.to_string())?)
+        }
+        None => None,
+      };
+      let low_power = slot.as_ref().is_some_and(Slot::low_power);
 
-    // Ensure stage emitter ends
-    let _ = stage_handle.await;
+      stats.bytes_read += match source_sizes.get(i) {
+        Some(size) => *size,
+        None => fs::metadata(svg).map(|m| m.len()).unwrap_or(0),
+      };
+      let item_started = Instant::now();
+      let policy = conflict_policy.clone();
+      let conflicts_for_task = conflicts.clone();
+      let win_for_conflict = window.clone();
+      let batch_for_conflict = batch_id.clone();
+      let svg_for_conflict = svg_str.clone();
+      let streamed_for_task = streamed.clone();
+      let claims = claims.clone();
+      // The slot goes with the item's encoder when its PNGs are written in the background.
+      let slot = slot.unwrap_or_else(Slot::unqueued);
+      let res = tauri::async_runtime::spawn_blocking(move || {
+        let entry = match &streamed_for_task {
+          Some(streamed) => Some(streamed.lock().unwrap_or_else(|e| e.into_inner()).next(&svg_cloned)?),
+          None => None,
+        };
+        let trash_replaced = req_cloned.trash_replaced.unwrap_or(false);
+        let mut on_conflict = |png: &Path, slot: &mut Slot| {
+          claims.settle(png, trash_replaced, || {
+            match (ConflictAction::parse(&policy), conflicts_for_task.as_ref()) {
+              (Some(action), _) => action,
+              // "ask": block this item until the frontend calls `resolve_conflict`, letting
+              // other items have its slot meanwhile.
+              (None, Some(reg)) => {
+                slot.released(|| reg.ask(&win_for_conflict, &batch_for_conflict, index, &svg_for_conflict, png))
+              }
+              (None, None) => ConflictAction::Skip,
+            }
+          })
+        };
+        let render = |slot: Slot| {
+          render_one_with_stage(
+            &svg_cloned,
+            &req_cloned,
+            root.as_ref().map(|p| p.as_path()),
+            out_dir_for_task.as_ref().map(|p| p.as_path()),
+            stage_tx,
+            &mut on_conflict,
+            slot,
+            entry,
+          )
+        };
+        // Low-power items encode in line, on the same low-priority thread.
+        if low_power {
+          queue::at_low_priority(|| render(slot))
+        } else {
+          render(slot)
+        }
+      })
+      .await
+      .map_err(|e| e.to_string())?;
+      pace = if low_power { queue::pacing(item_started.elapsed()) } else { Duration::ZERO };
+
+      // Ensure stage emitter ends
+      let _ = stage_handle.await;
+      Some(InFlight {
+        index,
+        svg,
+        svg_str,
+        finder_tags,
+        renamed_from,
+        render_ms: item_started.elapsed().as_millis() as u64,
+        res,
+      })
+    } else {
+      None
+    };
+    // The previous item's PNGs were encoding while this one rendered; report it now.
+    let Some(InFlight {
+      index,
+      svg,
+      svg_str,
+      finder_tags,
+      renamed_from,
+      render_ms,
+      res,
+    }) = std::mem::replace(&mut in_flight, next)
+    else {
+      continue;
+    };
+    let res = match res {
+      Ok(pending) => tauri::async_runtime::spawn_blocking(move || pending.wait())
+        .await
+        .map_err(|e| e.to_string())?,
+      Err(err) => Err(err),
+    };
+    let encode_ms = res.as_ref().map_or(0, |r| r.encode_ms);
+    item_times.push((render_ms + encode_ms, svg_str.clone()));
 
     match res {
-      Ok((png_path, out_w, out_h)) => {
+      Ok(ItemResult {
+        outputs,
+        mut warnings,
+        engine,
+        ..
+      }) => {
         ok += 1;
-        let _ = window.emit(
-          "convert-item",
-          ConvertItemEvent {
-            index,
-            total,
-            svg: svg_str.clone(),
-            png: png_path.to_string_lossy().to_string(),
-            out_width: Some(out_w),
-            out_height: Some(out_h),
-            ok: true,
-            engine: Some("resvg".into()),
-            error: None,
-          },
-        );
+        let mut item_duplicates = Vec::new();
+        for out in &outputs {
+          if !out.skipped {
+            stats.bytes_written += out.bytes;
+            written.push(out.path.clone());
+            if let Some(existing) = duplicate_index.as_mut().and_then(|index| index.check(&out.path)) {
+              item_duplicates.push(DuplicateOutput {
+                output: out.path.to_string_lossy().to_string(),
+                existing: existing.to_string_lossy().to_string(),
+              });
+            }
+            if let Some(tags) = &finder_tags {
+              if let Err(err) = finder::tag_output(&out.path, tags) {
+                log::warn!("failed to tag {}: {err}", out.path.display());
+              }
+            }
+          }
+          records.push(OutputRecord {
+            svg: svg.clone(),
+            png: out.path.clone(),
+            width: out.width,
+            height: out.height,
+            bytes: out.bytes,
+            checksum: out.checksum.clone(),
+          });
+        }
+        if !item_duplicates.is_empty() {
+          warnings.push("duplicateOutput".into());
+          duplicates.extend(item_duplicates.iter().cloned());
+        }
+        // The first output is the primary (1x) one; the rest are extra variants.
+        let primary = outputs.first();
+        events.send(BatchMessage::Item(ConvertItemEvent {
+          version: EVENT_VERSION,
+          batch_id: batch_id.clone(),
+          index,
+          total,
+          svg: svg_str.clone(),
+          png: primary.map(|o| o.path.to_string_lossy().to_string()).unwrap_or_default(),
+          out_width: primary.map(|o| o.width),
+          out_height: primary.map(|o| o.height),
+          ok: true,
+          engine: Some(engine.into()),
+          checksum: primary.and_then(|o| o.checksum.clone()),
+          extra_pngs: outputs.iter().skip(1).map(|o| o.path.to_string_lossy().to_string()).collect(),
+          skipped: !outputs.is_empty() && outputs.iter().all(|o| o.skipped),
+          warnings,
+          renamed_from,
+          duplicates: item_duplicates,
+          error: None,
+        }));
       }
       Err(err) => {
         failed += 1;
-        let _ = window.emit(
-          "convert-item",
-          ConvertItemEvent {
-            index,
-            total,
-            svg: svg_str.clone(),
-            png: "".into(),
-            out_width: None,
-            out_height: None,
-            ok: false,
-            engine: Some("resvg".into()),
-            error: Some(err),
-          },
-        );
+        // Sources streamed out of an archive aren't files to tag.
+        if let Some(tags) = finder_tags.as_ref().filter(|_| streamed.is_none()) {
+          if let Err(err) = finder::tag_failed_source(svg, tags) {
+            log::warn!("failed to tag {svg_str}: {err}");
+          }
+        }
+        events.send(BatchMessage::Item(ConvertItemEvent {
+          version: EVENT_VERSION,
+          batch_id: batch_id.clone(),
+          index,
+          total,
+          svg: svg_str.clone(),
+          png: "".into(),
+          out_width: None,
+          out_height: None,
+          ok: false,
+          engine: Some("resvg".into()),
+          checksum: None,
+          extra_pngs: Vec::new(),
+          skipped: false,
+          warnings: Vec::new(),
+          renamed_from,
+          duplicates: Vec::new(),
+          error: Some(err),
+        }));
+      }
+    }
+
+    rate.record();
+    let done = ConvertProgressEvent {
+      version: EVENT_VERSION,
+      batch_id: batch_id.clone(),
+      phase: "done".into(),
+      current: index,
+      active: None,
+      total,
+      ok,
+      failed,
+      last_svg: Some(svg_str.clone()),
+      item_percent: None,
+      elapsed_ms: rate.elapsed_ms(),
+      files_per_sec: rate.files_per_sec(),
+      eta_ms: rate.eta_ms(total - index),
+    };
+    if let Some(file) = &progress_file {
+      file.write(&done);
+    }
+    events.send(BatchMessage::Progress(done));
+  }
+  // A final snapshot so watchers can tell a finished batch from a stalled one.
+  if let Some(file) = &progress_file {
+    file.write(&ConvertProgressEvent {
+      version: EVENT_VERSION,
+      batch_id: batch_id.clone(),
+      phase: "finished".into(),
+      current: total,
+      active: None,
+      total,
+      ok,
+      failed,
+      last_svg: None,
+      item_percent: None,
+      elapsed_ms: rate.elapsed_ms(),
+      files_per_sec: rate.files_per_sec(),
+      eta_ms: Some(0),
+    });
+  }
+
+  if let Some(reg) = conflicts.as_ref() {
+    reg.finish_batch(&batch_id);
+  }
+
+  stats.duration_ms = rate.elapsed_ms();
+  if !item_times.is_empty() {
+    stats.avg_item_ms = Some(item_times.iter().map(|(ms, _)| *ms as f64).sum::<f64>() / item_times.len() as f64);
+  }
+  item_times.sort_by(|a, b| b.0.cmp(&a.0));
+  stats.slowest = item_times
+    .into_iter()
+    .take(SLOWEST_FILES)
+    .map(|(ms, svg)| SlowFile { svg, ms })
+    .collect();
+  events.send(BatchMessage::Stats(ConvertStatsEvent {
+    version: EVENT_VERSION,
+    batch_id: batch_id.clone(),
+    stats: stats.clone(),
+  }));
+
+  let report_dir = batch_report_dir(&req, out_dir.as_deref(), &input_path, &svgs);
+  let mut contact_sheet = None;
+  if req.contact_sheet.unwrap_or(false) && !records.is_empty() {
+    if let Some(dir) = report_dir.as_deref() {
+      match report::write_contact_sheet(dir, &records) {
+        Ok(p) => {
+          contact_sheet = Some(p.to_string_lossy().to_string());
+          written.push(p);
+        }
+        Err(err) => log::warn!("failed to write contact sheet: {err}"),
+      }
+    }
+  }
+  let mut sprite = None;
+  if let (Some(opts), Some(dir)) = (req.sprite.as_ref(), report_dir.as_deref()) {
+    if !records.is_empty() {
+      match sprite::write_sprite(dir, &records, opts) {
+        Ok(p) => {
+          sprite = Some(p.to_string_lossy().to_string());
+          written.push(p.with_extension("png"));
+          written.push(p);
+        }
+        Err(err) => log::warn!("failed to write sprite: {err}"),
       }
     }
+  }
+  let mut manifest = None;
+  if req.manifest.unwrap_or(false) {
+    if let Some(dir) = report_dir.as_deref() {
+      match report::write_manifest(dir, &req, &records) {
+        Ok(p) => {
+          manifest = Some(p.to_string_lossy().to_string());
+          written.push(p);
+        }
+        Err(err) => log::warn!("failed to write manifest: {err}"),
+      }
+    }
+  }
 
-    let _ = window.emit(
-      "convert-progress",
-      ConvertProgressEvent {
-        phase: "done".into(),
-        current: index,
-        active: None,
-        total,
-        ok,
-        failed,
-        last_svg: Some(svg_str.clone()),
-      },
-    );
+  let mut contact_sheet_pdf = None;
+  if req.contact_sheet_pdf.unwrap_or(false) && !records.is_empty() {
+    if let Some(dir) = report_dir.as_deref() {
+      match pdf::write_contact_sheet_pdf(dir, &records) {
+        Ok(p) => {
+          contact_sheet_pdf = Some(p.to_string_lossy().to_string());
+          written.push(p);
+        }
+        Err(err) => log::warn!("failed to write contact sheet PDF: {err}"),
+      }
+    }
+  }
+  let mut review_pdf = None;
+  if req.review_pdf.unwrap_or(false) && !records.is_empty() {
+    if let Some(dir) = report_dir.as_deref() {
+      match pdf::write_review_pdf(dir, &records) {
+        Ok(p) => {
+          review_pdf = Some(p.to_string_lossy().to_string());
+          written.push(p);
+        }
+        Err(err) => log::warn!("failed to write review PDF: {err}"),
+      }
+    }
+  }
+  let disk_usage = report::disk_usage(&written);
+  let mut upload = None;
+  if let Some(target) = upload_target.as_ref() {
+    let root = out_dir.as_deref().or(report_dir.as_deref());
+    let report = target
+      .upload_all(&batch_id, root, &written, |p| events.send(BatchMessage::Upload(p)))
+      .await;
+    upload = Some(report);
+  }
+  let mut history_run_dir = run_dir.as_ref().map(PathBuf::from);
+  if let Some(staging) = staging {
+    let (files, events_for_sftp, batch) = (written.clone(), events.clone(), batch_id.clone());
+    let (staging, report) = tauri::async_runtime::spawn_blocking(move || {
+      let report = staging.transfer_all(&batch, &files, |p| events_for_sftp.send(BatchMessage::Upload(p)));
+      (staging, report)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    let prev = upload.unwrap_or_default();
+    upload = Some(UploadReport {
+      uploaded: prev.uploaded + report.uploaded,
+      failed: prev.failed + report.failed,
+    });
+    // The staging folder goes with the batch: report the copies on the server instead, and
+    // leave what's there out of the undo history.
+    for path in [
+      &mut contact_sheet,
+      &mut sprite,
+      &mut manifest,
+      &mut review_pdf,
+      &mut contact_sheet_pdf,
+      &mut run_dir,
+    ] {
+      *path = path.take().map(|p| staging.remote_url(&p));
+    }
+    for dup in &mut duplicates {
+      dup.output = staging.remote_url(&dup.output);
+      dup.existing = staging.remote_url(&dup.existing);
+    }
+    written.retain(|f| !staging.holds(f));
+    history_run_dir = history_run_dir.filter(|d| !staging.holds(d));
+  }
+  if let Some(history) = window.try_state::<BatchHistory>() {
+    history.record(&batch_id, written, history_run_dir);
   }
 
-  Ok(ConvertSummary { total, ok, failed })
+  let summary = ConvertSummary {
+    version: EVENT_VERSION,
+    batch_id,
+    total,
+    ok,
+    failed,
+    contact_sheet,
+    sprite,
+    manifest,
+    review_pdf,
+    contact_sheet_pdf,
+    run_dir,
+    disk_usage,
+    duplicates,
+    upload,
+    stats,
+  };
+  events.send(BatchMessage::Summary(summary.clone()));
+  Ok(summary)
+}
+
+// Batch-level reports go next to the outputs: the output dir if set, else the input folder
+// (folder mode) or the first file's folder (file mode).
+fn batch_report_dir(req: &ConvertRequest, out_dir: Option<&Path>, input_path: &Path, svgs: &[PathBuf]) -> Option<PathBuf> {
+  if let Some(d) = out_dir {
+    return Some(d.to_path_buf());
+  }
+  if req.input_mode == "folder" {
+    return Some(input_path.to_path_buf());
+  }
+  svgs.first().and_then(|p| p.parent()).map(Path::to_path_buf)
 }
 
 
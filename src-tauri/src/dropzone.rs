@@ -0,0 +1,137 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use tauri::{Emitter, Manager, State};
+
+use crate::convert::{is_svg, run_batch, BatchEvents, ConvertRequest};
+
+const DROP_ZONE_SIZE: f64 = 220.0;
+
+#[derive(Default)]
+pub struct DropZoneState {
+  inner: Mutex<DropZoneInner>,
+}
+
+#[derive(Default)]
+struct DropZoneInner {
+  active: bool,
+  // Logical geometry to restore when leaving drop-zone mode.
+  saved_size: Option<tauri::LogicalSize<f64>>,
+  saved_position: Option<tauri::LogicalPosition<f64>>,
+  preset: Option<ConvertRequest>,
+}
+
+impl DropZoneState {
+  fn lock(&self) -> std::sync::MutexGuard<'_, DropZoneInner> {
+    self.inner.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  pub fn is_active(&self) -> bool {
+    self.lock().active
+  }
+}
+
+/// Converts files dropped on the compact window with the active preset.
+pub fn on_drop(window: &tauri::Window, paths: &[PathBuf]) {
+  let Some(state) = window.try_state::<DropZoneState>() else {
+    return;
+  };
+  let preset = {
+    let inner = state.lock();
+    if !inner.active {
+      return;
+    }
+    inner.preset.clone()
+  };
+
+  let svgs: Vec<String> = paths
+    .iter()
+    .filter(|p| p.is_file() && is_svg(p))
+    .map(|p| p.to_string_lossy().to_string())
+    .collect();
+  if svgs.is_empty() {
+    return;
+  }
+
+  // Without a preset the frontend decides what to do with the files.
+  let Some(mut req) = preset else {
+    let _ = window.emit("drop-zone-files", svgs);
+    return;
+  };
+  req.input_mode = "file".into();
+  req.input_path = svgs[0].clone();
+  req.input_paths = Some(svgs);
+
+  let win = window.clone();
+  tauri::async_runtime::spawn(async move {
+    if let Err(err) = run_batch(win.clone(), req, BatchEvents::Window(win.clone())).await {
+      let _ = win.emit("drop-zone-error", err);
+    }
+  });
+}
+
+/// Stores the settings used for files dropped while in drop-zone mode.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_active_preset(state: State<'_, DropZoneState>, preset: Option<ConvertRequest>) {
+  state.lock().preset = preset;
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn enter_drop_zone_mode(window: tauri::WebviewWindow, state: State<'_, DropZoneState>) -> Result<(), String> {
+  let scale = window.scale_factor().map_err(|e| e.to_string())?;
+  {
+    // Release the lock before touching the window: resizing fires window events synchronously.
+    let mut inner = state.lock();
+    if inner.active {
+      return Ok(());
+    }
+    inner.saved_size = window.inner_size().ok().map(|s| s.to_logical::<f64>(scale));
+    inner.saved_position = window.outer_position().ok().map(|p| p.to_logical::<f64>(scale));
+    inner.active = true;
+  }
+
+  window.set_min_size(None::<tauri::Size>).map_err(|e| e.to_string())?;
+  window
+    .set_size(tauri::Size::Logical(tauri::LogicalSize::<f64> {
+      width: DROP_ZONE_SIZE,
+      height: DROP_ZONE_SIZE,
+    }))
+    .map_err(|e| e.to_string())?;
+  window.set_resizable(false).map_err(|e| e.to_string())?;
+  window.set_always_on_top(true).map_err(|e| e.to_string())?;
+  let _ = window.emit("drop-zone-mode", true);
+  Ok(())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn exit_drop_zone_mode(
+  window: tauri::WebviewWindow,
+  state: State<'_, DropZoneState>,
+  store: State<'_, crate::settings::SettingsStore>,
+) -> Result<(), String> {
+  let (saved_size, saved_position) = {
+    let mut inner = state.lock();
+    if !inner.active {
+      return Ok(());
+    }
+    inner.active = false;
+    (inner.saved_size.take(), inner.saved_position.take())
+  };
+
+  let ws = store.get().window;
+  window.set_always_on_top(false).map_err(|e| e.to_string())?;
+  window.set_resizable(ws.resizable).map_err(|e| e.to_string())?;
+  window
+    .set_min_size(Some(tauri::Size::Logical(tauri::LogicalSize::<f64> {
+      width: ws.min_width,
+      height: ws.min_height,
+    })))
+    .map_err(|e| e.to_string())?;
+  if let Some(size) = saved_size {
+    window.set_size(tauri::Size::Logical(size)).map_err(|e| e.to_string())?;
+  }
+  if let Some(pos) = saved_position {
+    window.set_position(tauri::Position::Logical(pos)).map_err(|e| e.to_string())?;
+  }
+  let _ = window.emit("drop-zone-mode", false);
+  Ok(())
+}
@@ -0,0 +1,85 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::walk::{self, WalkOptions};
+
+// Finds new outputs that are byte-identical to a PNG already in the output tree, from an
+// earlier batch or earlier in this one. Existing files are only listed up front; a file
+// is hashed the first time a new output of the same length turns up.
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DuplicateOutput {
+  pub output: String,
+  pub existing: String,
+}
+
+struct Entry {
+  path: PathBuf,
+  hash: Option<[u8; 32]>,
+}
+
+#[derive(Default)]
+pub(crate) struct DuplicateIndex {
+  by_len: HashMap<u64, Vec<Entry>>,
+}
+
+fn hash_file(path: &Path) -> Option<[u8; 32]> {
+  fs::read(path).ok().map(|data| Sha256::digest(&data).into())
+}
+
+impl DuplicateIndex {
+  pub(crate) fn scan(root: &Path, opts: WalkOptions) -> Self {
+    let mut index = Self::default();
+    for path in walk::png_files(root, opts) {
+      if let Ok(meta) = fs::metadata(&path) {
+        index
+          .by_len
+          .entry(meta.len())
+          .or_default()
+          .push(Entry { path, hash: None });
+      }
+    }
+    index
+  }
+
+  /// The first indexed file with the same bytes as `path`, if any. `path` joins the index
+  /// either way so later outputs are compared against it too.
+  pub(crate) fn check(&mut self, path: &Path) -> Option<PathBuf> {
+    let len = fs::metadata(path).ok()?.len();
+    let entries = self.by_len.entry(len).or_default();
+    entries.retain(|e| e.path != path);
+    // Nothing else this long, so no need to read it yet.
+    if entries.is_empty() {
+      entries.push(Entry {
+        path: path.to_path_buf(),
+        hash: None,
+      });
+      return None;
+    }
+    let hash = hash_file(path)?;
+    let mut found = None;
+    for entry in entries.iter_mut() {
+      if entry.hash.is_none() {
+        entry.hash = hash_file(&entry.path);
+      }
+      if entry.hash == Some(hash) {
+        found = Some(entry.path.clone());
+        break;
+      }
+    }
+    entries.push(Entry {
+      path: path.to_path_buf(),
+      hash: Some(hash),
+    });
+    found
+  }
+}
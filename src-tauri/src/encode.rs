@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+
+use resvg::tiny_skia;
+
+use crate::{
+  convert::{parse_bg_color, ConvertRequest},
+  gamut,
+  hdr::{self, HdrOptions},
+};
+
+/// Output-side alpha handling, applied while converting the premultiplied pixmap into
+/// PNG rows.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct PngEncoding {
+  // Store premultiplied color (for engines that load textures without converting).
+  premultiplied: bool,
+  // Color bled into partially transparent pixels, so consumers that ignore or filter
+  // alpha badly show this color at edges instead of dark fringes.
+  matte: Option<[u8; 3]>,
+  // Pick the smallest lossless color type instead of always writing RGBA.
+  optimize: bool,
+  // Convert to Display P3 and tag the file as such.
+  display_p3: bool,
+  // Write a 16-bit PQ/BT.2020 PNG instead; excludes the options above.
+  hdr: Option<HdrOptions>,
+  // Print resolution for the pHYs chunk, in pixels per metre.
+  pixels_per_meter: Option<u32>,
+}
+
+impl PngEncoding {
+  pub(crate) fn from_request(req: &ConvertRequest) -> Result<Self, String> {
+    let premultiplied = match req.alpha_mode.as_deref().unwrap_or("straight") {
+      "straight" => false,
+      "premultiplied" => true,
+      _ => return Err("Invalid alpha mode (expected straight or premultiplied).".into()),
+    };
+    let display_p3 = match req.color_space.as_deref().unwrap_or("srgb") {
+      "srgb" => false,
+      "display-p3" => true,
+      _ => return Err("Invalid color space (expected srgb or display-p3).".into()),
+    };
+    let matte = match req.matte.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      Some(m) => {
+        let c = parse_bg_color(m).ok_or_else(|| "Invalid matte color (expected #RRGGBB).".to_string())?;
+        let c = c.to_color_u8();
+        let rgb = [c.red(), c.green(), c.blue()];
+        Some(if display_p3 { gamut::srgb_to_p3(rgb) } else { rgb })
+      }
+      None => None,
+    };
+    if let Some(h) = &req.hdr {
+      hdr::validate(h)?;
+      if premultiplied || matte.is_some() || display_p3 {
+        return Err("HDR output can't be combined with premultiplied alpha, a matte or Display P3.".into());
+      }
+    }
+    Ok(Self {
+      premultiplied,
+      matte,
+      optimize: req.optimize_png.unwrap_or(true),
+      display_p3,
+      hdr: req.hdr,
+      pixels_per_meter: req
+        .physical
+        .filter(|p| req.size_mode == "physical" && p.dpi.is_finite() && p.dpi > 0.0)
+        .map(|p| (p.dpi / 0.0254).round() as u32),
+    })
+  }
+
+  fn is_default(&self) -> bool {
+    !self.premultiplied && self.matte.is_none() && !self.optimize && !self.display_p3
+  }
+}
+
+fn rgba_rows(pixmap: &tiny_skia::Pixmap, enc: PngEncoding) -> Vec<u8> {
+  let mut out = Vec::with_capacity(pixmap.data().len());
+  for px in pixmap.pixels() {
+    let a = px.alpha();
+    let mut rgb = if enc.display_p3 {
+      // The conversion needs straight color; re-premultiply afterwards if asked to.
+      let c = px.demultiply();
+      let p3 = gamut::srgb_to_p3([c.red(), c.green(), c.blue()]);
+      if enc.premultiplied {
+        p3.map(|v| ((v as u16 * a as u16 + 127) / 255) as u8)
+      } else {
+        p3
+      }
+    } else if enc.premultiplied {
+      [px.red(), px.green(), px.blue()]
+    } else {
+      let c = px.demultiply();
+      [c.red(), c.green(), c.blue()]
+    };
+    if let Some(m) = enc.matte {
+      let inv = 255 - a as u16;
+      for (v, m) in rgb.iter_mut().zip(m) {
+        // Straight color is weighted by alpha; premultiplied already is.
+        let own = if enc.premultiplied { *v as u16 * 255 } else { *v as u16 * a as u16 };
+        *v = ((own + m as u16 * inv + 127) / 255) as u8;
+      }
+    }
+    out.extend_from_slice(&[rgb[0], rgb[1], rgb[2], a]);
+  }
+  out
+}
+
+/// Cheapest PNG layout that represents a set of RGBA pixels exactly.
+enum Layout {
+  Gray,
+  GrayAlpha,
+  // Palette entries plus the bit depth the indices need.
+  Indexed(Vec<[u8; 4]>, png::BitDepth),
+  Rgb,
+  Rgba,
+}
+
+fn analyze(rows: &[u8]) -> Layout {
+  let (mut opaque, mut gray) = (true, true);
+  let mut colors: Vec<[u8; 4]> = Vec::new();
+  let mut seen: HashSet<[u8; 4]> = HashSet::new();
+  for px in rows.chunks_exact(4) {
+    let c = [px[0], px[1], px[2], px[3]];
+    opaque &= c[3] == 255;
+    gray &= c[0] == c[1] && c[1] == c[2];
+    if colors.len() <= 256 && seen.insert(c) {
+      colors.push(c);
+    }
+  }
+  let depth = match colors.len() {
+    0..=2 => Some(png::BitDepth::One),
+    3..=4 => Some(png::BitDepth::Two),
+    5..=16 => Some(png::BitDepth::Four),
+    17..=256 => Some(png::BitDepth::Eight),
+    _ => None,
+  };
+  match (gray, opaque, depth) {
+    // Sub-byte indices beat one gray byte per pixel.
+    (true, true, Some(d)) if d != png::BitDepth::Eight => Layout::Indexed(colors, d),
+    (true, true, _) => Layout::Gray,
+    (_, _, Some(d)) => Layout::Indexed(colors, d),
+    (true, false, None) => Layout::GrayAlpha,
+    (false, true, None) => Layout::Rgb,
+    (false, false, None) => Layout::Rgba,
+  }
+}
+
+// Packs palette indices MSB-first, each row padded to a whole byte.
+fn pack_indices(rows: &[u8], width: usize, palette: &[[u8; 4]], bits: usize) -> Vec<u8> {
+  let lookup: HashMap<[u8; 4], u8> = palette.iter().enumerate().map(|(i, c)| (*c, i as u8)).collect();
+  let row_bytes = (width * bits).div_ceil(8);
+  let mut out = Vec::with_capacity(row_bytes * rows.len() / (width * 4));
+  for row in rows.chunks_exact(width * 4) {
+    let mut packed = vec![0u8; row_bytes];
+    for (x, px) in row.chunks_exact(4).enumerate() {
+      let idx = lookup[&[px[0], px[1], px[2], px[3]]];
+      let bit = x * bits;
+      packed[bit / 8] |= idx << (8 - bits - bit % 8);
+    }
+    out.extend_from_slice(&packed);
+  }
+  out
+}
+
+fn write_png(
+  width: u32,
+  height: u32,
+  color: png::ColorType,
+  depth: png::BitDepth,
+  palette: Option<(Vec<u8>, Option<Vec<u8>>)>,
+  data: &[u8],
+) -> Result<Vec<u8>, String> {
+  let mut out = Vec::new();
+  let mut encoder = png::Encoder::new(&mut out, width, height);
+  encoder.set_color(color);
+  encoder.set_depth(depth);
+  if let Some((plte, trns)) = palette {
+    encoder.set_palette(plte);
+    if let Some(trns) = trns {
+      encoder.set_trns(trns);
+    }
+  }
+  let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+  writer.write_image_data(data).map_err(|e| e.to_string())?;
+  writer.finish().map_err(|e| e.to_string())?;
+  Ok(out)
+}
+
+/// Encodes `pixmap` as PNG, as 8-bit RGBA or, when optimizing, the smallest color
+/// type that holds the pixels losslessly.
+pub(crate) fn encode_png(pixmap: &tiny_skia::Pixmap, enc: PngEncoding) -> Result<Vec<u8>, String> {
+  let png = match enc.hdr {
+    Some(h) => hdr::encode(pixmap, h)?,
+    None if enc.is_default() => pixmap.encode_png().map_err(|e| e.to_string())?,
+    None => encode_pixels(pixmap, enc)?,
+  };
+  Ok(match enc.pixels_per_meter {
+    Some(ppm) => {
+      // Same density on both axes; unit 1 is the metre.
+      let phys = [&ppm.to_be_bytes()[..], &ppm.to_be_bytes(), &[1]].concat();
+      gamut::insert_after_ihdr(&png, &[(b"pHYs", phys.as_slice())])
+    }
+    None => png,
+  })
+}
+
+fn encode_pixels(pixmap: &tiny_skia::Pixmap, enc: PngEncoding) -> Result<Vec<u8>, String> {
+  let (w, h) = (pixmap.width(), pixmap.height());
+  let rows = rgba_rows(pixmap, enc);
+  let layout = match (enc.optimize, enc.display_p3) {
+    (false, _) => Layout::Rgba,
+    // An RGB profile can't be attached to a grayscale PNG.
+    (true, true) => match analyze(&rows) {
+      Layout::Gray => Layout::Rgb,
+      Layout::GrayAlpha => Layout::Rgba,
+      other => other,
+    },
+    (true, false) => analyze(&rows),
+  };
+  let eight = png::BitDepth::Eight;
+  let png = match layout {
+    Layout::Rgba => write_png(w, h, png::ColorType::Rgba, eight, None, &rows),
+    Layout::Rgb => {
+      let data: Vec<u8> = rows.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect();
+      write_png(w, h, png::ColorType::Rgb, eight, None, &data)
+    }
+    Layout::GrayAlpha => {
+      let data: Vec<u8> = rows.chunks_exact(4).flat_map(|p| [p[0], p[3]]).collect();
+      write_png(w, h, png::ColorType::GrayscaleAlpha, eight, None, &data)
+    }
+    Layout::Gray => {
+      let data: Vec<u8> = rows.chunks_exact(4).map(|p| p[0]).collect();
+      write_png(w, h, png::ColorType::Grayscale, eight, None, &data)
+    }
+    Layout::Indexed(palette, depth) => {
+      let data = pack_indices(&rows, w as usize, &palette, depth as usize);
+      let plte: Vec<u8> = palette.iter().flat_map(|c| [c[0], c[1], c[2]]).collect();
+      let trns = palette
+        .iter()
+        .any(|c| c[3] != 255)
+        .then(|| palette.iter().map(|c| c[3]).collect());
+      write_png(w, h, png::ColorType::Indexed, depth, Some((plte, trns)), &data)
+    }
+  }?;
+  Ok(if enc.display_p3 { gamut::tag_display_p3(png) } else { png })
+}
@@ -0,0 +1,284 @@
+use std::fmt::Write;
+
+use resvg::{tiny_skia, usvg};
+
+use crate::convert::{is_fixed_frame, parse_bg_color, ConvertRequest};
+
+// "eps" output format: the usvg tree written as Level 2 PostScript for print RIPs that
+// take EPS but not SVG or PDF. EPS has no transparency, so group opacity, masks and
+// filters are dropped; gradients become their average stop color and patterns are
+// skipped. Text is written as its outlines; embedded PNGs and nested SVGs are kept.
+
+/// EPS document for one output, `width`×`height` points, laid out like the PNG render.
+pub(crate) fn write_eps(tree: &usvg::Tree, req: &ConvertRequest, width: u32, height: u32) -> Result<String, String> {
+  let (src_w, src_h) = (tree.size().width(), tree.size().height());
+  let (out_w, out_h) = (width as f32, height as f32);
+  let transform = if is_fixed_frame(req) && req.crop.unwrap_or(false) {
+    let scale = (out_w / src_w).max(out_h / src_h);
+    usvg::Transform::from_row(
+      scale,
+      0.0,
+      0.0,
+      scale,
+      (out_w - src_w * scale) * 0.5,
+      (out_h - src_h * scale) * 0.5,
+    )
+  } else {
+    usvg::Transform::from_scale(out_w / src_w, out_h / src_h)
+  };
+
+  let mut ps = String::new();
+  ps.push_str("%!PS-Adobe-3.0 EPSF-3.0\n");
+  let _ = writeln!(ps, "%%BoundingBox: 0 0 {width} {height}");
+  let _ = writeln!(ps, "%%HiResBoundingBox: 0 0 {width} {height}");
+  ps.push_str("%%Creator: SVG to PNG\n%%LanguageLevel: 2\n%%Pages: 1\n%%EndComments\n");
+  ps.push_str("%%Page: 1 1\ngsave\n");
+  // SVG user space is y-down.
+  let _ = writeln!(ps, "0 {height} translate 1 -1 scale");
+  let _ = writeln!(
+    ps,
+    "0 0 moveto {w} 0 lineto {w} {h} lineto 0 {h} lineto closepath clip newpath",
+    w = width,
+    h = height
+  );
+
+  if let Some(bg) = req.background.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+    let c = parse_bg_color(bg).ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string())?;
+    let _ = writeln!(ps, "{} {} {} setrgbcolor", num(c.red()), num(c.green()), num(c.blue()));
+    let _ = writeln!(ps, "0 0 {width} {height} rectfill");
+  }
+
+  concat(&mut ps, transform);
+  write_group(&mut ps, tree.root());
+  ps.push_str("grestore\nshowpage\n%%EOF\n");
+  Ok(ps)
+}
+
+fn write_group(ps: &mut String, group: &usvg::Group) {
+  for node in group.children() {
+    match node {
+      usvg::Node::Group(g) => {
+        ps.push_str("gsave\n");
+        concat(ps, g.transform());
+        if let Some(clip) = g.clip_path() {
+          write_clip(ps, clip);
+        }
+        write_group(ps, g);
+        ps.push_str("grestore\n");
+      }
+      usvg::Node::Path(p) => write_path(ps, p),
+      usvg::Node::Image(img) => write_image(ps, img),
+      usvg::Node::Text(text) => write_group(ps, text.flattened()),
+    }
+  }
+}
+
+fn write_path(ps: &mut String, path: &usvg::Path) {
+  if !path.is_visible() {
+    return;
+  }
+  if let Some(color) = path.fill().and_then(|f| solid_color(f.paint())) {
+    path_ops(ps, path.data(), usvg::Transform::identity());
+    set_color(ps, color);
+    let even_odd = path.fill().is_some_and(|f| f.rule() == usvg::FillRule::EvenOdd);
+    ps.push_str(if even_odd { "eofill\n" } else { "fill\n" });
+  }
+  if let Some(stroke) = path.stroke() {
+    let Some(color) = solid_color(stroke.paint()) else {
+      return;
+    };
+    path_ops(ps, path.data(), usvg::Transform::identity());
+    set_color(ps, color);
+    let cap = match stroke.linecap() {
+      usvg::LineCap::Butt => 0,
+      usvg::LineCap::Round => 1,
+      usvg::LineCap::Square => 2,
+    };
+    let join = match stroke.linejoin() {
+      usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => 0,
+      usvg::LineJoin::Round => 1,
+      usvg::LineJoin::Bevel => 2,
+    };
+    let _ = writeln!(
+      ps,
+      "{} setlinewidth {cap} setlinecap {join} setlinejoin {} setmiterlimit",
+      num(stroke.width().get()),
+      num(stroke.miterlimit().get().max(1.0))
+    );
+    match stroke.dasharray() {
+      Some(dashes) => {
+        let dashes: Vec<String> = dashes.iter().map(|d| num(*d)).collect();
+        let _ = writeln!(ps, "[{}] {} setdash", dashes.join(" "), num(stroke.dashoffset()));
+      }
+      None => ps.push_str("[] 0 setdash\n"),
+    }
+    ps.push_str("stroke\n");
+  }
+}
+
+// Clip paths are written in the current group's space, with their own transforms baked
+// into the coordinates so the CTM is left alone.
+fn write_clip(ps: &mut String, clip: &usvg::ClipPath) {
+  if let Some(inner) = clip.clip_path() {
+    write_clip(ps, inner);
+  }
+  collect_clip(ps, clip.root(), clip.transform());
+  ps.push_str("clip newpath\n");
+}
+
+fn collect_clip(ps: &mut String, group: &usvg::Group, ts: usvg::Transform) {
+  for node in group.children() {
+    match node {
+      usvg::Node::Group(g) => collect_clip(ps, g, ts.pre_concat(g.transform())),
+      usvg::Node::Path(p) => path_ops(ps, p.data(), ts),
+      usvg::Node::Text(text) => collect_clip(ps, text.flattened(), ts),
+      usvg::Node::Image(_) => {}
+    }
+  }
+}
+
+fn path_ops(ps: &mut String, data: &tiny_skia::Path, ts: usvg::Transform) {
+  let map = |p: tiny_skia::Point| {
+    let mut p = p;
+    ts.map_point(&mut p);
+    (num(p.x), num(p.y))
+  };
+  let mut last = tiny_skia::Point::zero();
+  for seg in data.segments() {
+    match seg {
+      tiny_skia::PathSegment::MoveTo(p) => {
+        let (x, y) = map(p);
+        let _ = writeln!(ps, "{x} {y} moveto");
+        last = p;
+      }
+      tiny_skia::PathSegment::LineTo(p) => {
+        let (x, y) = map(p);
+        let _ = writeln!(ps, "{x} {y} lineto");
+        last = p;
+      }
+      tiny_skia::PathSegment::QuadTo(c, p) => {
+        // PostScript only has cubics; raise the quadratic's degree.
+        let c1 = tiny_skia::Point::from_xy(last.x + (c.x - last.x) * 2.0 / 3.0, last.y + (c.y - last.y) * 2.0 / 3.0);
+        let c2 = tiny_skia::Point::from_xy(p.x + (c.x - p.x) * 2.0 / 3.0, p.y + (c.y - p.y) * 2.0 / 3.0);
+        let ((x1, y1), (x2, y2), (x, y)) = (map(c1), map(c2), map(p));
+        let _ = writeln!(ps, "{x1} {y1} {x2} {y2} {x} {y} curveto");
+        last = p;
+      }
+      tiny_skia::PathSegment::CubicTo(c1, c2, p) => {
+        let ((x1, y1), (x2, y2), (x, y)) = (map(c1), map(c2), map(p));
+        let _ = writeln!(ps, "{x1} {y1} {x2} {y2} {x} {y} curveto");
+        last = p;
+      }
+      tiny_skia::PathSegment::Close => ps.push_str("closepath\n"),
+    }
+  }
+}
+
+// Embedded rasters are flattened onto white (no transparency in EPS); nested SVGs are
+// written as vectors.
+fn write_image(ps: &mut String, img: &usvg::Image) {
+  if !img.is_visible() {
+    return;
+  }
+  let size = img.size();
+  let pixmap = match img.kind() {
+    usvg::ImageKind::SVG(tree) => {
+      ps.push_str("gsave\n");
+      concat(
+        ps,
+        usvg::Transform::from_scale(size.width() / tree.size().width(), size.height() / tree.size().height()),
+      );
+      write_group(ps, tree.root());
+      ps.push_str("grestore\n");
+      return;
+    }
+    usvg::ImageKind::PNG(data) => match tiny_skia::Pixmap::decode_png(data) {
+      Ok(p) => p,
+      Err(_) => return,
+    },
+    // JPEG/GIF/WebP would need their own decoders.
+    _ => return,
+  };
+
+  let (pw, ph) = (pixmap.width(), pixmap.height());
+  let _ = writeln!(
+    ps,
+    "gsave\n{} {} scale\n/DeviceRGB setcolorspace",
+    num(size.width()),
+    num(size.height())
+  );
+  let _ = writeln!(
+    ps,
+    "<< /ImageType 1 /Width {pw} /Height {ph} /BitsPerComponent 8 /Decode [0 1 0 1 0 1] \
+     /ImageMatrix [{pw} 0 0 {ph} 0 0] /DataSource currentfile /ASCIIHexDecode filter >> image"
+  );
+  for (i, p) in pixmap.pixels().iter().enumerate() {
+    let c = p.demultiply();
+    let a = c.alpha() as u32;
+    let over_white = |v: u8| ((v as u32 * a + 255 * (255 - a) + 127) / 255) as u8;
+    let _ = write!(
+      ps,
+      "{:02x}{:02x}{:02x}",
+      over_white(c.red()),
+      over_white(c.green()),
+      over_white(c.blue())
+    );
+    if i % 24 == 23 {
+      ps.push('\n');
+    }
+  }
+  ps.push_str(">\ngrestore\n");
+}
+
+// Solid colors pass through; gradients fall back to the average of their stops.
+fn solid_color(paint: &usvg::Paint) -> Option<usvg::Color> {
+  let stops = match paint {
+    usvg::Paint::Color(c) => return Some(*c),
+    usvg::Paint::LinearGradient(g) => g.stops(),
+    usvg::Paint::RadialGradient(g) => g.stops(),
+    usvg::Paint::Pattern(_) => return None,
+  };
+  if stops.is_empty() {
+    return None;
+  }
+  let n = stops.len() as u32;
+  let sum = |f: fn(&usvg::Color) -> u8| (stops.iter().map(|s| f(&s.color()) as u32).sum::<u32>() / n) as u8;
+  Some(usvg::Color::new_rgb(sum(|c| c.red), sum(|c| c.green), sum(|c| c.blue)))
+}
+
+fn set_color(ps: &mut String, c: usvg::Color) {
+  let _ = writeln!(
+    ps,
+    "{} {} {} setrgbcolor",
+    num(c.red as f32 / 255.0),
+    num(c.green as f32 / 255.0),
+    num(c.blue as f32 / 255.0)
+  );
+}
+
+fn concat(ps: &mut String, ts: usvg::Transform) {
+  if ts.is_identity() {
+    return;
+  }
+  let _ = writeln!(
+    ps,
+    "[{} {} {} {} {} {}] concat",
+    num(ts.sx),
+    num(ts.ky),
+    num(ts.kx),
+    num(ts.sy),
+    num(ts.tx),
+    num(ts.ty)
+  );
+}
+
+// Compact decimal: at most four fractional digits, no trailing zeros.
+fn num(v: f32) -> String {
+  let s = format!("{v:.4}");
+  let s = s.trim_end_matches('0').trim_end_matches('.');
+  if s == "-0" || s.is_empty() {
+    "0".into()
+  } else {
+    s.into()
+  }
+}
@@ -0,0 +1,76 @@
+use std::{
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use resvg::usvg;
+use sha2::{Digest, Sha256};
+
+use crate::convert::is_svg;
+
+// usvg has already base64-decoded every `data:` image while parsing, so the tree gives
+// the original file bytes. External references aren't loaded (no resources dir), so
+// only images actually embedded in the SVG are found.
+
+fn collect_images(group: &usvg::Group, out: &mut Vec<(&'static str, Vec<u8>)>) {
+  for node in group.children() {
+    if let usvg::Node::Image(img) = node {
+      let found = match img.kind() {
+        usvg::ImageKind::PNG(d) => Some(("png", d.to_vec())),
+        usvg::ImageKind::JPEG(d) => Some(("jpg", d.to_vec())),
+        usvg::ImageKind::GIF(d) => Some(("gif", d.to_vec())),
+        usvg::ImageKind::WEBP(d) => Some(("webp", d.to_vec())),
+        // Nested SVG images were parsed into trees; look inside them instead.
+        usvg::ImageKind::SVG(tree) => {
+          collect_images(tree.root(), out);
+          None
+        }
+      };
+      out.extend(found);
+    }
+    // Patterns, masks, clip paths and filters keep their content in sub-roots.
+    node.subroots(|g| collect_images(g, out));
+    if let usvg::Node::Group(g) = node {
+      collect_images(g, out);
+    }
+  }
+}
+
+/// Writes every embedded raster image in the SVG to `output_dir` as
+/// `<stem>_image<N>.<ext>`, skipping exact duplicates.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn extract_embedded_images(svg_path: String, output_dir: Option<String>) -> Result<Vec<String>, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let svg = PathBuf::from(&svg_path);
+    if !svg.is_file() || !is_svg(&svg) {
+      return Err("Invalid SVG file path.".into());
+    }
+    let data = fs::read(&svg).map_err(|e| e.to_string())?;
+    let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).map_err(|e| e.to_string())?;
+    let mut images = Vec::new();
+    collect_images(tree.root(), &mut images);
+
+    let dir = match output_dir {
+      Some(d) => PathBuf::from(d),
+      None => svg.parent().map(Path::to_path_buf).unwrap_or_default(),
+    };
+    let stem = svg.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+    let mut seen = HashSet::new();
+    let mut written = Vec::new();
+    for (ext, bytes) in images {
+      if !seen.insert(Sha256::digest(&bytes)) {
+        continue;
+      }
+      if written.is_empty() {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+      }
+      let path = dir.join(format!("{stem}_image{}.{ext}", written.len() + 1));
+      fs::write(&path, bytes).map_err(|e| e.to_string())?;
+      written.push(path.to_string_lossy().to_string());
+    }
+    Ok(written)
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,273 @@
+use resvg::tiny_skia;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+  convert::{parse_bg_color, ConvertRequest},
+  linear::{linear_to_srgb, srgb_to_linear},
+  svgout,
+};
+
+// Per-pixel color filters applied to the finished render. They work on straight
+// (non-premultiplied) color and never touch alpha.
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct HslAdjust {
+  pub hue: Option<f32>,        // degrees to rotate, -180..180
+  pub saturation: Option<f32>, // percent, -100 (gray) .. 100
+  pub lightness: Option<f32>,  // percent, -100 (black) .. 100 (white)
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DuotoneOptions {
+  pub shadow: String,    // "#RRGGBB" for black
+  pub highlight: String, // "#RRGGBB" for white
+}
+
+fn parse_rgb(s: &str, what: &str) -> Result<[u8; 3], String> {
+  let c = parse_bg_color(s.trim())
+    .ok_or_else(|| format!("Invalid {what} color (expected #RRGGBB)."))?
+    .to_color_u8();
+  Ok([c.red(), c.green(), c.blue()])
+}
+
+// Rec. 709 luma, 0..=255.
+fn luma(rgb: [u8; 3]) -> f32 {
+  let [r, g, b] = rgb.map(|v| v as f32);
+  0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// The filters requested by a [`ConvertRequest`], parsed once per render.
+struct Chain {
+  invert: bool,
+  hsl: Option<HslAdjust>,
+  duotone: Option<([u8; 3], [u8; 3])>,
+  posterize: Option<u8>,
+  threshold: Option<u8>,
+  tint: Option<[u8; 3]>,
+}
+
+impl Chain {
+  fn from_request(req: &ConvertRequest) -> Result<Self, String> {
+    let tint = match req.tint.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      Some(t) => Some(parse_rgb(t, "tint")?),
+      None => None,
+    };
+    let duotone = match &req.duotone {
+      Some(d) => Some((parse_rgb(&d.shadow, "shadow")?, parse_rgb(&d.highlight, "highlight")?)),
+      None => None,
+    };
+    if req.posterize.is_some_and(|n| n < 2) {
+      return Err("Posterize needs at least 2 levels.".into());
+    }
+    Ok(Self {
+      invert: req.invert.unwrap_or(false),
+      hsl: req.hsl,
+      duotone,
+      posterize: req.posterize,
+      threshold: req.threshold,
+      tint,
+    })
+  }
+
+  fn is_empty(&self) -> bool {
+    !self.invert
+      && self.hsl.is_none()
+      && self.duotone.is_none()
+      && self.posterize.is_none()
+      && self.threshold.is_none()
+      && self.tint.is_none()
+  }
+
+  fn run(&self, mut rgb: [u8; 3]) -> [u8; 3] {
+    if self.invert {
+      rgb = rgb.map(|v| 255 - v);
+    }
+    if let Some(adj) = &self.hsl {
+      rgb = adjust_hsl(rgb, adj);
+    }
+    if let Some((dark, light)) = self.duotone {
+      // Luma picks the position along the shadow → highlight ramp.
+      let t = luma(rgb) / 255.0;
+      rgb = [0, 1, 2].map(|i| (dark[i] as f32 + (light[i] as f32 - dark[i] as f32) * t).round() as u8);
+    }
+    if let Some(levels) = self.posterize {
+      let steps = (levels - 1) as f32;
+      rgb = rgb.map(|v| ((v as f32 / 255.0 * steps).round() / steps * 255.0).round() as u8);
+    }
+    if let Some(cutoff) = self.threshold {
+      let v = if luma(rgb) >= cutoff as f32 { 255 } else { 0 };
+      rgb = [v; 3];
+    }
+    // Tint uses the render purely as a mask, so it replaces whatever came before.
+    if let Some(t) = self.tint {
+      rgb = t;
+    }
+    rgb
+  }
+}
+
+pub(crate) fn validate(req: &ConvertRequest) -> Result<(), String> {
+  Chain::from_request(req)?;
+  if !ColorVision::from_request(req)?.is_empty() && svgout::vector_format(req).is_some() {
+    return Err("Color-vision variants need PNG output.".into());
+  }
+  if let Some(hsl) = &req.hsl {
+    if hsl.hue.is_some_and(|h| !(-180.0..=180.0).contains(&h)) {
+      return Err("Hue must be between -180 and 180.".into());
+    }
+    for v in [hsl.saturation, hsl.lightness].into_iter().flatten() {
+      if !(-100.0..=100.0).contains(&v) {
+        return Err("Saturation/lightness must be between -100 and 100.".into());
+      }
+    }
+  }
+  Ok(())
+}
+
+fn rgb_to_hsl([r, g, b]: [f32; 3]) -> [f32; 3] {
+  let max = r.max(g).max(b);
+  let min = r.min(g).min(b);
+  let l = (max + min) / 2.0;
+  let d = max - min;
+  if d <= f32::EPSILON {
+    return [0.0, 0.0, l];
+  }
+  let s = if l > 0.5 { d / (2.0 - max - min) } else { d / (max + min) };
+  let h = if max == r {
+    (g - b) / d + if g < b { 6.0 } else { 0.0 }
+  } else if max == g {
+    (b - r) / d + 2.0
+  } else {
+    (r - g) / d + 4.0
+  };
+  [h * 60.0, s, l]
+}
+
+fn hsl_to_rgb([h, s, l]: [f32; 3]) -> [f32; 3] {
+  if s <= f32::EPSILON {
+    return [l, l, l];
+  }
+  let q = if l < 0.5 { l * (1.0 + s) } else { l + s - l * s };
+  let p = 2.0 * l - q;
+  let channel = |t: f32| {
+    let t = t.rem_euclid(1.0);
+    if t < 1.0 / 6.0 {
+      p + (q - p) * 6.0 * t
+    } else if t < 0.5 {
+      q
+    } else if t < 2.0 / 3.0 {
+      p + (q - p) * (2.0 / 3.0 - t) * 6.0
+    } else {
+      p
+    }
+  };
+  let h = h / 360.0;
+  [channel(h + 1.0 / 3.0), channel(h), channel(h - 1.0 / 3.0)]
+}
+
+fn adjust_hsl(rgb: [u8; 3], adj: &HslAdjust) -> [u8; 3] {
+  let [mut h, mut s, mut l] = rgb_to_hsl(rgb.map(|v| v as f32 / 255.0));
+  h = (h + adj.hue.unwrap_or(0.0)).rem_euclid(360.0);
+  let ds = adj.saturation.unwrap_or(0.0) / 100.0;
+  s = (s * (1.0 + ds)).clamp(0.0, 1.0);
+  // Positive lightness moves toward white, negative toward black.
+  let dl = adj.lightness.unwrap_or(0.0) / 100.0;
+  l = if dl >= 0.0 { l + (1.0 - l) * dl } else { l * (1.0 + dl) };
+  hsl_to_rgb([h, s, l.clamp(0.0, 1.0)]).map(|v| (v * 255.0).round().clamp(0.0, 255.0) as u8)
+}
+
+pub(crate) fn apply(pixmap: &mut tiny_skia::Pixmap, req: &ConvertRequest) -> Result<(), String> {
+  let chain = Chain::from_request(req)?;
+  if chain.is_empty() {
+    return Ok(());
+  }
+  for px in pixmap.pixels_mut() {
+    let c = px.demultiply();
+    if c.alpha() == 0 {
+      continue;
+    }
+    let [r, g, b] = chain.run([c.red(), c.green(), c.blue()]);
+    *px = tiny_skia::ColorU8::from_rgba(r, g, b, c.alpha()).premultiply();
+  }
+  Ok(())
+}
+
+/// Color-vision deficiency simulations, written as extra variants of each output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColorVision {
+  Protanopia,
+  Deuteranopia,
+  Tritanopia,
+}
+
+impl ColorVision {
+  pub(crate) fn parse(s: &str) -> Result<Self, String> {
+    match s {
+      "protanopia" => Ok(Self::Protanopia),
+      "deuteranopia" => Ok(Self::Deuteranopia),
+      "tritanopia" => Ok(Self::Tritanopia),
+      _ => Err(format!("Invalid color-vision simulation: {s}")),
+    }
+  }
+
+  /// The requested simulations, deduplicated, in request order.
+  pub(crate) fn from_request(req: &ConvertRequest) -> Result<Vec<Self>, String> {
+    let mut kinds = Vec::new();
+    for s in req.color_vision.iter().flatten() {
+      let kind = Self::parse(s.trim())?;
+      if !kinds.contains(&kind) {
+        kinds.push(kind);
+      }
+    }
+    Ok(kinds)
+  }
+
+  pub(crate) fn name(self) -> &'static str {
+    match self {
+      Self::Protanopia => "protanopia",
+      Self::Deuteranopia => "deuteranopia",
+      Self::Tritanopia => "tritanopia",
+    }
+  }
+
+  // Machado, Oliveira & Fernandes (2009) at full severity, on linear RGB.
+  fn matrix(self) -> [[f32; 3]; 3] {
+    match self {
+      Self::Protanopia => [
+        [0.152_286, 1.052_583, -0.204_868],
+        [0.114_503, 0.786_281, 0.099_216],
+        [-0.003_882, -0.048_116, 1.051_998],
+      ],
+      Self::Deuteranopia => [
+        [0.367_322, 0.860_646, -0.227_968],
+        [0.280_085, 0.672_501, 0.047_413],
+        [-0.011_820, 0.042_940, 0.968_881],
+      ],
+      Self::Tritanopia => [
+        [1.255_528, -0.076_749, -0.178_779],
+        [-0.078_411, 0.930_809, 0.147_602],
+        [0.004_733, 0.691_367, 0.303_900],
+      ],
+    }
+  }
+}
+
+pub(crate) fn simulate_color_vision(pixmap: &mut tiny_skia::Pixmap, kind: ColorVision) {
+  let m = kind.matrix();
+  for px in pixmap.pixels_mut() {
+    let c = px.demultiply();
+    if c.alpha() == 0 {
+      continue;
+    }
+    let lin = [c.red(), c.green(), c.blue()].map(|v| srgb_to_linear(v as f32 / 255.0));
+    let [r, g, b] = m.map(|row| {
+      (linear_to_srgb((row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]).clamp(0.0, 1.0)) * 255.0).round() as u8
+    });
+    *px = tiny_skia::ColorU8::from_rgba(r, g, b, c.alpha()).premultiply();
+  }
+}
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+// Finder tags live in the `com.apple.metadata:_kMDItemUserTags` extended attribute as a
+// binary plist array of "Name\nColorIndex" strings. Outputs get the batch's tags plus
+// the success color; when a file fails, its source SVG gets the failure color instead.
+// Other platforms have no equivalent, so tagging is a no-op there.
+
+#[cfg(target_os = "macos")]
+const TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+// Finder's label colors, by their index in the tag attribute.
+const COLORS: [&str; 8] = ["None", "Gray", "Green", "Purple", "Blue", "Yellow", "Red", "Orange"];
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FinderTags {
+  pub tags: Vec<String>,       // Applied to every output, e.g. a project name
+  pub success: Option<String>, // Color tag for written outputs, e.g. "Green"
+  pub failure: Option<String>, // Color tag for the sources of failed files, e.g. "Red"
+}
+
+fn color_index(name: &str) -> Option<usize> {
+  COLORS.iter().position(|c| c.eq_ignore_ascii_case(name))
+}
+
+pub(crate) fn validate(opts: &FinderTags) -> Result<(), String> {
+  for color in opts.success.iter().chain(&opts.failure) {
+    if color_index(color).is_none() {
+      return Err(format!("Unknown Finder tag color: {color}"));
+    }
+  }
+  if opts.tags.iter().any(|t| t.trim().is_empty() || t.contains('\n')) {
+    return Err("Finder tags must be non-empty single-line names.".into());
+  }
+  Ok(())
+}
+
+// Color names become the matching colored tag; anything else is a plain tag.
+fn tag_entry(name: &str) -> String {
+  match color_index(name) {
+    Some(i) if i > 0 => format!("{}\n{i}", COLORS[i]),
+    _ => name.trim().to_string(),
+  }
+}
+
+pub(crate) fn tag_output(path: &Path, opts: &FinderTags) -> Result<(), String> {
+  let tags: Vec<String> = opts.tags.iter().chain(&opts.success).map(|t| tag_entry(t)).collect();
+  add_tags(path, &tags)
+}
+
+pub(crate) fn tag_failed_source(path: &Path, opts: &FinderTags) -> Result<(), String> {
+  match &opts.failure {
+    Some(color) => add_tags(path, &[tag_entry(color)]),
+    None => Ok(()),
+  }
+}
+
+// Adds to the file's existing tags rather than replacing them, since sources are the user's.
+// If the existing tags can't be read, the file is left untagged.
+#[cfg(target_os = "macos")]
+fn add_tags(path: &Path, tags: &[String]) -> Result<(), String> {
+  use std::process::Command;
+
+  if tags.is_empty() {
+    return Ok(());
+  }
+  let Some(mut all) = read_tags(path) else {
+    log::warn!("skipped tagging {}: its existing tags couldn't be read", path.display());
+    return Ok(());
+  };
+  for tag in tags {
+    let name = tag.split('\n').next().unwrap_or_default();
+    all.retain(|t| t.split('\n').next() != Some(name));
+    all.push(tag.clone());
+  }
+  let hex: String = encode_string_array(&all).iter().map(|b| format!("{b:02x}")).collect();
+  let out = Command::new("xattr")
+    .args(["-wx", TAGS_XATTR, &hex])
+    .arg(path)
+    .output()
+    .map_err(|e| format!("Failed to run xattr: {e}"))?;
+  if !out.status.success() {
+    return Err(format!(
+      "Tagging failed: {}",
+      String::from_utf8_lossy(&out.stderr).trim()
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn add_tags(_path: &Path, _tags: &[String]) -> Result<(), String> {
+  Ok(())
+}
+
+// The file's tags straight from the attribute, colors included (Spotlight's copy has names
+// only, and none at all on volumes it doesn't index). No attribute means no tags; `None`
+// means they couldn't be read.
+#[cfg(target_os = "macos")]
+fn read_tags(path: &Path) -> Option<Vec<String>> {
+  let out = std::process::Command::new("xattr")
+    .args(["-px", TAGS_XATTR])
+    .arg(path)
+    .output()
+    .ok()?;
+  if !out.status.success() {
+    return String::from_utf8_lossy(&out.stderr)
+      .contains("No such xattr")
+      .then(Vec::new);
+  }
+  // `xattr -px` prints the value as spaced hex pairs over several lines.
+  let hex: Vec<u8> = out.stdout.into_iter().filter(u8::is_ascii_hexdigit).collect();
+  let bytes = hex
+    .chunks(2)
+    .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+    .collect::<Option<Vec<u8>>>()?;
+  decode_string_array(&bytes)
+}
+
+// Reads back a binary plist whose top object is an array of strings, as Finder writes tags.
+// Anything else (or anything malformed) gives `None`.
+#[cfg(target_os = "macos")]
+fn decode_string_array(data: &[u8]) -> Option<Vec<String>> {
+  fn uint(data: &[u8], at: usize, size: usize) -> Option<usize> {
+    let bytes = data.get(at..at.checked_add(size)?)?;
+    (size <= 8).then(|| bytes.iter().fold(0usize, |n, b| (n << 8) | *b as usize))
+  }
+  // An object's length and where its contents start; long lengths follow as an int object.
+  fn length(data: &[u8], at: usize) -> Option<(usize, usize)> {
+    let low = (*data.get(at)? & 0x0f) as usize;
+    if low < 15 {
+      return Some((low, at + 1));
+    }
+    let int = *data.get(at + 1)?;
+    let size = 1usize << (int & 0x0f);
+    (int & 0xf0 == 0x10).then_some(())?;
+    Some((uint(data, at + 2, size)?, at + 2 + size))
+  }
+
+  if !data.starts_with(b"bplist00") || data.len() < 8 + 32 {
+    return None;
+  }
+  let trailer = &data[data.len() - 32..];
+  let (offset_size, ref_size) = (trailer[6] as usize, trailer[7] as usize);
+  let objects = uint(trailer, 8, 8)?;
+  let top = uint(trailer, 16, 8)?;
+  let table = uint(trailer, 24, 8)?;
+  let offset =
+    |i: usize| (i < objects).then(|| uint(data, table.checked_add(i.checked_mul(offset_size)?)?, offset_size))?;
+
+  let at = offset(top)?;
+  if data.get(at)? & 0xf0 != 0xa0 {
+    return None;
+  }
+  let (count, refs) = length(data, at)?;
+  (0..count)
+    .map(|i| {
+      let at = offset(uint(data, refs + i * ref_size, ref_size)?)?;
+      let (len, start) = length(data, at)?;
+      match data.get(at)? & 0xf0 {
+        0x50 => data
+          .get(start..start.checked_add(len)?)
+          .map(|b| String::from_utf8_lossy(b).into_owned()),
+        0x60 => {
+          let units: Vec<u16> = data
+            .get(start..start.checked_add(len.checked_mul(2)?)?)?
+            .chunks(2)
+            .map(|u| u16::from_be_bytes([u[0], u[1]]))
+            .collect();
+          String::from_utf16(&units).ok()
+        }
+        _ => None,
+      }
+    })
+    .collect()
+}
+
+// Minimal binary plist ("bplist00") holding one array of strings.
+#[cfg(target_os = "macos")]
+fn encode_string_array(items: &[String]) -> Vec<u8> {
+  fn marker(out: &mut Vec<u8>, kind: u8, len: usize) {
+    if len < 15 {
+      out.push(kind | len as u8);
+    } else {
+      out.push(kind | 0x0f);
+      match len {
+        0..=0xff => out.extend([0x10, len as u8]),
+        0x100..=0xffff => out.extend([0x11].into_iter().chain((len as u16).to_be_bytes())),
+        _ => out.extend([0x12].into_iter().chain((len as u32).to_be_bytes())),
+      }
+    }
+  }
+  fn sized(n: usize) -> u8 {
+    match n {
+      0..=0xff => 1,
+      0x100..=0xffff => 2,
+      _ => 4,
+    }
+  }
+  fn push_sized(out: &mut Vec<u8>, n: usize, size: u8) {
+    out.extend_from_slice(&(n as u32).to_be_bytes()[4 - size as usize..]);
+  }
+
+  let objects = items.len() + 1;
+  let ref_size = sized(objects);
+  let mut out = b"bplist00".to_vec();
+  let mut offsets = Vec::with_capacity(objects);
+
+  offsets.push(out.len());
+  marker(&mut out, 0xa0, items.len());
+  for i in 1..objects {
+    push_sized(&mut out, i, ref_size);
+  }
+  for item in items {
+    offsets.push(out.len());
+    if item.is_ascii() {
+      marker(&mut out, 0x50, item.len());
+      out.extend_from_slice(item.as_bytes());
+    } else {
+      let units: Vec<u16> = item.encode_utf16().collect();
+      marker(&mut out, 0x60, units.len());
+      out.extend(units.iter().flat_map(|u| u.to_be_bytes()));
+    }
+  }
+
+  let table_offset = out.len();
+  let offset_size = sized(table_offset);
+  for offset in &offsets {
+    push_sized(&mut out, *offset, offset_size);
+  }
+  out.extend_from_slice(&[0; 6]);
+  out.extend_from_slice(&[offset_size, ref_size]);
+  out.extend_from_slice(&(objects as u64).to_be_bytes());
+  out.extend_from_slice(&0u64.to_be_bytes());
+  out.extend_from_slice(&(table_offset as u64).to_be_bytes());
+  out
+}
@@ -0,0 +1,161 @@
+use crate::linear::{linear_to_srgb, srgb_to_linear};
+
+// Display P3 output: colors are converted from sRGB (what SVG specifies) into P3
+// primaries, keeping the sRGB transfer curve P3 shares, and the PNG is tagged with
+// both a cICP chunk and an embedded ICC profile so older decoders still manage it.
+
+// Linear sRGB → linear Display P3 (both D65).
+const SRGB_TO_P3: [[f32; 3]; 3] = [
+  [0.822_462, 0.177_538, 0.0],
+  [0.033_194, 0.966_806, 0.0],
+  [0.017_083, 0.072_397, 0.910_520],
+];
+
+pub(crate) fn srgb_to_p3(rgb: [u8; 3]) -> [u8; 3] {
+  let lin = rgb.map(|v| srgb_to_linear(v as f32 / 255.0));
+  SRGB_TO_P3.map(|row| {
+    let v = row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2];
+    (linear_to_srgb(v.clamp(0.0, 1.0)) * 255.0).round() as u8
+  })
+}
+
+fn s15f16(v: f64) -> [u8; 4] {
+  ((v * 65536.0).round() as i32).to_be_bytes()
+}
+
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+  let mut t = b"XYZ \0\0\0\0".to_vec();
+  for v in xyz {
+    t.extend_from_slice(&s15f16(v));
+  }
+  t
+}
+
+fn mluc_tag(text: &str) -> Vec<u8> {
+  let utf16: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+  let mut t = b"mluc\0\0\0\0".to_vec();
+  t.extend_from_slice(&1u32.to_be_bytes());
+  t.extend_from_slice(&12u32.to_be_bytes());
+  t.extend_from_slice(b"enUS");
+  t.extend_from_slice(&(utf16.len() as u32).to_be_bytes());
+  t.extend_from_slice(&28u32.to_be_bytes());
+  t.extend_from_slice(&utf16);
+  t
+}
+
+/// A minimal ICC v4 display profile for Display P3: P3 primaries adapted to D50 and the
+/// sRGB curve as a parametric TRC.
+fn display_p3_icc() -> Vec<u8> {
+  let mut trc = b"para\0\0\0\0".to_vec();
+  trc.extend_from_slice(&3u16.to_be_bytes());
+  trc.extend_from_slice(&[0, 0]);
+  for v in [2.4, 1.0 / 1.055, 0.055 / 1.055, 1.0 / 12.92, 0.04045] {
+    trc.extend_from_slice(&s15f16(v));
+  }
+  let mut chad = b"sf32\0\0\0\0".to_vec();
+  for v in [
+    1.047_882, 0.022_919, -0.050_201, 0.029_587, 0.990_479, -0.017_059, -0.009_232, 0.015_076, 0.751_678,
+  ] {
+    chad.extend_from_slice(&s15f16(v));
+  }
+  let blobs = [
+    mluc_tag("Display P3"),
+    mluc_tag("No copyright, use freely"),
+    xyz_tag([0.964_203, 1.0, 0.824_905]),
+    xyz_tag([0.515_121, 0.241_196, -0.001_053]),
+    xyz_tag([0.291_977, 0.692_245, 0.041_885]),
+    xyz_tag([0.157_104, 0.066_574, 0.784_073]),
+    chad,
+    trc,
+  ];
+  // Signature and blob index; the three TRCs share one curve.
+  let tags: [(&[u8; 4], usize); 10] = [
+    (b"desc", 0),
+    (b"cprt", 1),
+    (b"wtpt", 2),
+    (b"rXYZ", 3),
+    (b"gXYZ", 4),
+    (b"bXYZ", 5),
+    (b"chad", 6),
+    (b"rTRC", 7),
+    (b"gTRC", 7),
+    (b"bTRC", 7),
+  ];
+
+  let data_start = 128 + 4 + tags.len() * 12;
+  let mut body = Vec::new();
+  let mut offsets = Vec::with_capacity(blobs.len());
+  for blob in &blobs {
+    offsets.push(data_start + body.len());
+    body.extend_from_slice(blob);
+    // Tag data starts on 4-byte boundaries.
+    body.resize(body.len().next_multiple_of(4), 0);
+  }
+  let mut table = (tags.len() as u32).to_be_bytes().to_vec();
+  for (sig, i) in tags {
+    table.extend_from_slice(sig);
+    table.extend_from_slice(&(offsets[i] as u32).to_be_bytes());
+    table.extend_from_slice(&(blobs[i].len() as u32).to_be_bytes());
+  }
+
+  let size = 128 + table.len() + body.len();
+  let mut header = vec![0u8; 128];
+  header[0..4].copy_from_slice(&(size as u32).to_be_bytes());
+  header[8..12].copy_from_slice(&[4, 0x30, 0, 0]);
+  header[12..16].copy_from_slice(b"mntr");
+  header[16..20].copy_from_slice(b"RGB ");
+  header[20..24].copy_from_slice(b"XYZ ");
+  for (i, v) in [2024u16, 1, 1, 0, 0, 0].iter().enumerate() {
+    header[24 + i * 2..26 + i * 2].copy_from_slice(&v.to_be_bytes());
+  }
+  header[36..40].copy_from_slice(b"acsp");
+  for (i, v) in [0.964_2, 1.0, 0.824_9].into_iter().enumerate() {
+    header[68 + i * 4..72 + i * 4].copy_from_slice(&s15f16(v));
+  }
+
+  [header, table, body].concat()
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+  let mut crc = !0u32;
+  for &b in bytes {
+    crc ^= b as u32;
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xEDB8_8320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
+
+fn chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+  let mut out = (data.len() as u32).to_be_bytes().to_vec();
+  out.extend_from_slice(kind);
+  out.extend_from_slice(data);
+  out.extend_from_slice(&crc32(&out[4..]).to_be_bytes());
+  out
+}
+
+// Signature (8 bytes) plus the IHDR chunk (4 + 4 + 13 + 4).
+const IHDR_END: usize = 33;
+
+/// Inserts `chunks` right after IHDR, where color-space chunks have to go.
+pub(crate) fn insert_after_ihdr(png: &[u8], chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+  let mut out = png[..IHDR_END].to_vec();
+  for (kind, data) in chunks {
+    out.extend_from_slice(&chunk(kind, data));
+  }
+  out.extend_from_slice(&png[IHDR_END..]);
+  out
+}
+
+/// Tags an encoded RGB(A)/indexed PNG as Display P3: cICP (P3-D65 primaries, sRGB
+/// transfer, full range) and an iCCP profile.
+pub(crate) fn tag_display_p3(png: Vec<u8>) -> Vec<u8> {
+  let mut iccp = b"Display P3\0\0".to_vec();
+  iccp.extend_from_slice(&miniz_oxide::deflate::compress_to_vec_zlib(&display_p3_icc(), 9));
+  insert_after_ihdr(&png, &[(b"cICP", &[12, 13, 0, 1][..]), (b"iCCP", iccp.as_slice())])
+}
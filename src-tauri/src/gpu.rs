@@ -0,0 +1,253 @@
+// Optional GPU raster backend: usvg trees go through vello_svg into a vello scene, rendered by
+// wgpu into a texture and read back. Requests opt in with `engine: "gpu"`; it needs the `gpu`
+// cargo feature, and anything it can't do falls back to resvg/tiny-skia: no usable adapter,
+// an output larger than the device's textures, or filters. vello_svg draws no filter primitive
+// at all, so any SVG with a filter anywhere (including in masks, text and embedded SVG images)
+// renders on the CPU; the GPU only speeds up filter-free artwork.
+use std::cell::Cell;
+
+use resvg::{tiny_skia, usvg};
+
+use crate::convert::ConvertRequest;
+
+thread_local! {
+  // Whether the last render on this thread went through the GPU, for the item's `engine`.
+  static USED_GPU: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Reports (and clears) whether this thread rendered on the GPU since the last call.
+pub(crate) fn take_used() -> bool {
+  USED_GPU.with(|u| u.replace(false))
+}
+
+pub(crate) fn validate_engine(engine: &str) -> Result<(), String> {
+  match engine {
+    "cpu" | "gpu" => Ok(()),
+    _ => Err("Invalid engine (expected cpu or gpu).".into()),
+  }
+}
+
+fn wanted(req: &ConvertRequest) -> bool {
+  req.engine.as_deref() == Some("gpu")
+}
+
+fn uses_filters(group: &usvg::Group) -> bool {
+  !group.filters().is_empty()
+    || group.mask().is_some_and(|m| uses_filters(m.root()))
+    || group.children().iter().any(|node| match node {
+      usvg::Node::Group(g) => uses_filters(g),
+      usvg::Node::Text(text) => uses_filters(text.flattened()),
+      usvg::Node::Image(image) => match image.kind() {
+        usvg::ImageKind::SVG(tree) => uses_filters(tree.root()),
+        _ => false,
+      },
+      usvg::Node::Path(_) => false,
+    })
+}
+
+/// Renders on the GPU when the request asks for it and it can; `None` means "use the CPU".
+pub(crate) fn render(
+  tree: &usvg::Tree,
+  req: &ConvertRequest,
+  transform: usvg::Transform,
+  out_w: u32,
+  out_h: u32,
+  background: Option<tiny_skia::Color>,
+) -> Option<tiny_skia::Pixmap> {
+  if !wanted(req) || uses_filters(tree.root()) {
+    return None;
+  }
+  let pixmap = backend::render(tree, transform, out_w, out_h, background)?;
+  USED_GPU.with(|u| u.set(true));
+  Some(pixmap)
+}
+
+#[cfg(feature = "gpu")]
+mod backend {
+  use std::sync::{mpsc, Mutex, OnceLock};
+
+  use resvg::{tiny_skia, usvg};
+  use vello::{
+    kurbo::Affine, peniko::Color, wgpu, AaConfig, AaSupport, RenderParams, Renderer, RendererOptions, Scene,
+  };
+
+  struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    renderer: Renderer,
+  }
+
+  // Set up once on first use; `None` when there is no adapter the renderer can run on.
+  fn gpu() -> Option<&'static Mutex<Gpu>> {
+    static GPU: OnceLock<Option<Mutex<Gpu>>> = OnceLock::new();
+    GPU
+      .get_or_init(|| match init() {
+        Ok(gpu) => Some(Mutex::new(gpu)),
+        Err(err) => {
+          log::info!("GPU rendering unavailable, using the CPU: {err}");
+          None
+        }
+      })
+      .as_ref()
+  }
+
+  fn init() -> Result<Gpu, String> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+      power_preference: wgpu::PowerPreference::HighPerformance,
+      ..Default::default()
+    }))
+    .ok_or_else(|| "no adapter".to_string())?;
+    let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+      .map_err(|e| e.to_string())?;
+    let renderer = Renderer::new(
+      &device,
+      RendererOptions {
+        use_cpu: false,
+        antialiasing_support: AaSupport::area_only(),
+        num_init_threads: None,
+        pipeline_cache: None,
+      },
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(Gpu {
+      device,
+      queue,
+      renderer,
+    })
+  }
+
+  pub(super) fn render(
+    tree: &usvg::Tree,
+    transform: usvg::Transform,
+    out_w: u32,
+    out_h: u32,
+    background: Option<tiny_skia::Color>,
+  ) -> Option<tiny_skia::Pixmap> {
+    let mut gpu = gpu()?.lock().unwrap_or_else(|e| e.into_inner());
+    let max = gpu.device.limits().max_texture_dimension_2d;
+    if out_w > max || out_h > max {
+      return None;
+    }
+    match render_on(&mut gpu, tree, transform, out_w, out_h, background) {
+      Ok(pixmap) => Some(pixmap),
+      Err(err) => {
+        log::warn!("GPU render failed, using the CPU: {err}");
+        None
+      }
+    }
+  }
+
+  fn render_on(
+    gpu: &mut Gpu,
+    tree: &usvg::Tree,
+    t: usvg::Transform,
+    out_w: u32,
+    out_h: u32,
+    background: Option<tiny_skia::Color>,
+  ) -> Result<tiny_skia::Pixmap, String> {
+    let mut scene = Scene::new();
+    let affine = Affine::new([t.sx, t.ky, t.kx, t.sy, t.tx, t.ty].map(f64::from));
+    scene.append(&vello_svg::render_tree(tree), Some(affine));
+
+    let size = wgpu::Extent3d {
+      width: out_w,
+      height: out_h,
+      depth_or_array_layers: 1,
+    };
+    let texture = gpu.device.create_texture(&wgpu::TextureDescriptor {
+      label: Some("svg-to-png target"),
+      size,
+      mip_level_count: 1,
+      sample_count: 1,
+      dimension: wgpu::TextureDimension::D2,
+      format: wgpu::TextureFormat::Rgba8Unorm,
+      usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+      view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let base_color = background.map_or(Color::TRANSPARENT, |c| {
+      Color::new([c.red(), c.green(), c.blue(), c.alpha()])
+    });
+    let Gpu {
+      device,
+      queue,
+      renderer,
+    } = gpu;
+    renderer
+      .render_to_texture(
+        device,
+        queue,
+        &scene,
+        &view,
+        &RenderParams {
+          base_color,
+          width: out_w,
+          height: out_h,
+          antialiasing_method: AaConfig::Area,
+        },
+      )
+      .map_err(|e| e.to_string())?;
+
+    // Texture rows are copied out padded to wgpu's alignment.
+    let row = out_w * 4;
+    let padded_row = row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("svg-to-png readback"),
+      size: padded_row as u64 * out_h as u64,
+      usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+      texture.as_image_copy(),
+      wgpu::TexelCopyBufferInfo {
+        buffer: &buffer,
+        layout: wgpu::TexelCopyBufferLayout {
+          offset: 0,
+          bytes_per_row: Some(padded_row),
+          rows_per_image: None,
+        },
+      },
+      size,
+    );
+    queue.submit([encoder.finish()]);
+    let slice = buffer.slice(..);
+    let (tx, rx) = mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |res| {
+      let _ = tx.send(res);
+    });
+    let _ = device.poll(wgpu::Maintain::Wait);
+    rx.recv().map_err(|e| e.to_string())?.map_err(|e| e.to_string())?;
+
+    // vello writes straight (unpremultiplied) RGBA; tiny-skia pixmaps are premultiplied.
+    let mut pixmap = tiny_skia::Pixmap::new(out_w, out_h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+    {
+      let mapped = slice.get_mapped_range();
+      let pixels = pixmap.pixels_mut();
+      for (y, src) in mapped.chunks(padded_row as usize).enumerate() {
+        for (x, px) in src[..row as usize].chunks_exact(4).enumerate() {
+          let c = tiny_skia::ColorU8::from_rgba(px[0], px[1], px[2], px[3]);
+          pixels[y * out_w as usize + x] = c.premultiply();
+        }
+      }
+    }
+    buffer.unmap();
+    Ok(pixmap)
+  }
+}
+
+#[cfg(not(feature = "gpu"))]
+mod backend {
+  use resvg::{tiny_skia, usvg};
+
+  pub(super) fn render(
+    _tree: &usvg::Tree,
+    _transform: usvg::Transform,
+    _out_w: u32,
+    _out_h: u32,
+    _background: Option<tiny_skia::Color>,
+  ) -> Option<tiny_skia::Pixmap> {
+    None
+  }
+}
@@ -0,0 +1,87 @@
+use resvg::tiny_skia;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{gamut, linear::srgb_to_linear};
+
+// Experimental HDR output: 16-bit PNGs in BT.2020 primaries with the PQ (SMPTE ST 2084)
+// transfer, tagged with a cICP chunk. SDR white lands on `sdrWhite` nits; `boost`
+// expands highlights and saturated colors toward `peak` nits (a simple inverse tone map).
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct HdrOptions {
+  pub sdr_white: Option<f32>, // nits for sRGB white, default 203 (BT.2408)
+  pub peak: Option<f32>,      // nits the boost expands toward, default 1000
+  pub boost: Option<f32>,     // 0 (none, default) .. 1 (full inverse tone map)
+}
+
+const DEFAULT_SDR_WHITE: f32 = 203.0;
+const DEFAULT_PEAK: f32 = 1000.0;
+const PQ_MAX_NITS: f32 = 10_000.0;
+
+// Linear sRGB → linear BT.2020 (both D65).
+const SRGB_TO_BT2020: [[f32; 3]; 3] = [
+  [0.627_404, 0.329_283, 0.043_313],
+  [0.069_097, 0.919_540, 0.011_362],
+  [0.016_391, 0.088_013, 0.895_595],
+];
+
+pub(crate) fn validate(opts: &HdrOptions) -> Result<(), String> {
+  let white = opts.sdr_white.unwrap_or(DEFAULT_SDR_WHITE);
+  let peak = opts.peak.unwrap_or(DEFAULT_PEAK);
+  if !(1.0..=PQ_MAX_NITS).contains(&white) || !(1.0..=PQ_MAX_NITS).contains(&peak) {
+    return Err("HDR luminance must be between 1 and 10000 nits.".into());
+  }
+  if peak < white {
+    return Err("HDR peak must be at least the SDR white level.".into());
+  }
+  if opts.boost.is_some_and(|b| !(0.0..=1.0).contains(&b)) {
+    return Err("HDR boost must be between 0 and 1.".into());
+  }
+  Ok(())
+}
+
+fn pq_encode(nits: f32) -> f32 {
+  const M1: f32 = 0.159_301_76;
+  const M2: f32 = 78.843_75;
+  const C1: f32 = 0.835_937_5;
+  const C2: f32 = 18.851_563;
+  const C3: f32 = 18.687_5;
+  let y = (nits / PQ_MAX_NITS).clamp(0.0, 1.0).powf(M1);
+  ((C1 + C2 * y) / (1.0 + C3 * y)).powf(M2)
+}
+
+/// Encodes `pixmap` as a 16-bit RGBA PQ/BT.2020 PNG with a cICP chunk.
+pub(crate) fn encode(pixmap: &tiny_skia::Pixmap, opts: HdrOptions) -> Result<Vec<u8>, String> {
+  let white = opts.sdr_white.unwrap_or(DEFAULT_SDR_WHITE);
+  let headroom = opts.peak.unwrap_or(DEFAULT_PEAK) / white - 1.0;
+  let boost = opts.boost.unwrap_or(0.0);
+
+  let mut data = Vec::with_capacity(pixmap.pixels().len() * 8);
+  for px in pixmap.pixels() {
+    let c = px.demultiply();
+    let lin = [c.red(), c.green(), c.blue()].map(|v| srgb_to_linear(v as f32 / 255.0));
+    let rgb = SRGB_TO_BT2020.map(|row| (row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]).max(0.0));
+    // Expand by the brightest channel so saturated colors gain as much as bright ones;
+    // the square keeps midtones near their SDR level.
+    let m = rgb[0].max(rgb[1]).max(rgb[2]);
+    let gain = 1.0 + boost * headroom * m * m;
+    for v in rgb {
+      let code = (pq_encode(v * gain * white) * 65535.0).round() as u16;
+      data.extend_from_slice(&code.to_be_bytes());
+    }
+    data.extend_from_slice(&(c.alpha() as u16 * 257).to_be_bytes());
+  }
+
+  let mut out = Vec::new();
+  let mut encoder = png::Encoder::new(&mut out, pixmap.width(), pixmap.height());
+  encoder.set_color(png::ColorType::Rgba);
+  encoder.set_depth(png::BitDepth::Sixteen);
+  let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+  writer.write_image_data(&data).map_err(|e| e.to_string())?;
+  writer.finish().map_err(|e| e.to_string())?;
+  // BT.2020 primaries, PQ transfer, RGB (identity matrix), full range.
+  Ok(gamut::insert_after_ihdr(&out, &[(b"cICP", &[9, 16, 0, 1][..])]))
+}
@@ -0,0 +1,131 @@
+use std::{
+  collections::VecDeque,
+  fs,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::layout;
+
+// Files each recent batch wrote, so a run with the wrong settings can be reverted with
+// `undo_batch`. Only files the batch created or overwrote are listed; skipped outputs and
+// the sources are never touched. History lives in memory for the app session.
+const MAX_BATCHES: usize = 20;
+
+struct BatchFiles {
+  batch_id: String,
+  files: Vec<PathBuf>,
+  run_dir: Option<PathBuf>, // removed too once empty
+}
+
+#[derive(Default, Clone)]
+pub struct BatchHistory(Arc<Mutex<VecDeque<BatchFiles>>>);
+
+impl BatchHistory {
+  fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<BatchFiles>> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  pub(crate) fn record(&self, batch_id: &str, files: Vec<PathBuf>, run_dir: Option<PathBuf>) {
+    let mut batches = self.lock();
+    if batches.len() == MAX_BATCHES {
+      batches.pop_front();
+    }
+    batches.push_back(BatchFiles {
+      batch_id: batch_id.to_string(),
+      files,
+      run_dir,
+    });
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+  pub removed: u32,
+  pub missing: u32, // already gone, e.g. moved or deleted by hand
+  pub failed: Vec<String>,
+}
+
+/// Deletes (or moves to the trash) every file the batch wrote. Each batch can be undone once.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn undo_batch(
+  history: State<'_, BatchHistory>,
+  batch_id: String,
+  trash: Option<bool>,
+) -> Result<UndoResult, String> {
+  let batch = {
+    let mut batches = history.lock();
+    let i = batches.iter().position(|b| b.batch_id == batch_id);
+    i.and_then(|i| batches.remove(i))
+      .ok_or_else(|| "This batch can no longer be undone.".to_string())?
+  };
+  let to_trash = trash.unwrap_or(false);
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let mut result = UndoResult {
+      removed: 0,
+      missing: 0,
+      failed: Vec::new(),
+    };
+    for path in &batch.files {
+      // Outputs past Windows' path limit were written through the verbatim form.
+      let io_path = match layout::io_path(path) {
+        Ok(io_path) => io_path,
+        Err(err) => {
+          result.failed.push(format!("{}: {err}", path.display()));
+          continue;
+        }
+      };
+      if !io_path.exists() {
+        result.missing += 1;
+        continue;
+      }
+      let removed = if to_trash {
+        move_to_trash(&io_path)
+      } else {
+        fs::remove_file(&io_path).map_err(|e| e.to_string())
+      };
+      match removed {
+        Ok(()) => result.removed += 1,
+        Err(err) => result.failed.push(format!("{}: {err}", path.display())),
+      }
+    }
+    // A run folder belongs to the batch, so it goes too once empty, along with any
+    // subfolders made inside it. Folders outside it may predate the batch.
+    if let Some(run_dir) = batch.run_dir {
+      let mut dirs: Vec<PathBuf> = batch
+        .files
+        .iter()
+        .flat_map(|p| p.ancestors().skip(1).take_while(|d| d.starts_with(&run_dir)))
+        .map(PathBuf::from)
+        .collect();
+      dirs.sort();
+      dirs.dedup();
+      dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+      for dir in dirs {
+        // Fails (and is skipped) unless the folder is empty.
+        if let Ok(dir) = layout::io_path(&dir) {
+          let _ = fs::remove_dir(dir);
+        }
+      }
+    }
+    Ok(result)
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+/// Moves `path` to the system trash. Android and iOS have no trash to move it to.
+#[cfg(desktop)]
+pub(crate) fn move_to_trash(path: &Path) -> Result<(), String> {
+  trash::delete(path).map_err(|e| e.to_string())
+}
+
+#[cfg(mobile)]
+pub(crate) fn move_to_trash(_path: &Path) -> Result<(), String> {
+  Err("There's no trash on this device.".into())
+}
@@ -0,0 +1,298 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use resvg::{tiny_skia, usvg};
+use serde::Serialize;
+
+use crate::convert::{is_svg, parse_bg_color};
+
+const ICO_SIZES: [u32; 3] = [16, 32, 48];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IconBundle {
+  pub files: Vec<String>,
+  pub snippet: Option<String>,  // path of the written favicon.html snippet (favicon bundle)
+  pub manifest: Option<String>, // path of the written manifest.webmanifest fragment (PWA icons)
+}
+
+fn load_tree(svg_path: &Path) -> Result<usvg::Tree, String> {
+  if !svg_path.is_file() || !is_svg(svg_path) {
+    return Err("Invalid SVG file path.".into());
+  }
+  let data = fs::read(svg_path).map_err(|e| e.to_string())?;
+  let opt = usvg::Options::default();
+  usvg::Tree::from_data(&data, &opt).map_err(|e| e.to_string())
+}
+
+fn parse_optional_bg(background: Option<&str>) -> Result<Option<tiny_skia::Color>, String> {
+  match background.map(str::trim).filter(|s| !s.is_empty()) {
+    Some(bg) => parse_bg_color(bg)
+      .map(Some)
+      .ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string()),
+    None => Ok(None),
+  }
+}
+
+/// Renders the tree centered inside a `w`×`h` canvas without distortion, leaving
+/// `padding` (fraction of the shorter side) free on every edge.
+pub(crate) fn render_icon(
+  tree: &usvg::Tree,
+  w: u32,
+  h: u32,
+  padding: f32,
+  background: Option<tiny_skia::Color>,
+) -> Result<tiny_skia::Pixmap, String> {
+  let mut pixmap = tiny_skia::Pixmap::new(w, h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  if let Some(c) = background {
+    pixmap.fill(c);
+  }
+  let inset = (w.min(h) as f32) * padding;
+  let avail_w = (w as f32 - inset * 2.0).max(1.0);
+  let avail_h = (h as f32 - inset * 2.0).max(1.0);
+  let size = tree.size();
+  let scale = (avail_w / size.width()).min(avail_h / size.height());
+  let tx = (w as f32 - size.width() * scale) * 0.5;
+  let ty = (h as f32 - size.height() * scale) * 0.5;
+  let mut pm = pixmap.as_mut();
+  resvg::render(tree, usvg::Transform::from_row(scale, 0.0, 0.0, scale, tx, ty), &mut pm);
+  Ok(pixmap)
+}
+
+fn write_png(path: PathBuf, pixmap: &tiny_skia::Pixmap, files: &mut Vec<String>) -> Result<(), String> {
+  let png = pixmap.encode_png().map_err(|e| e.to_string())?;
+  fs::write(&path, png).map_err(|e| e.to_string())?;
+  files.push(path.to_string_lossy().to_string());
+  Ok(())
+}
+
+// ICO container with PNG-compressed entries (supported by every browser and Windows Vista+).
+fn encode_ico(images: &[(u32, Vec<u8>)]) -> Vec<u8> {
+  let mut out = Vec::new();
+  out.extend_from_slice(&0u16.to_le_bytes()); // reserved
+  out.extend_from_slice(&1u16.to_le_bytes()); // type: icon
+  out.extend_from_slice(&(images.len() as u16).to_le_bytes());
+  let mut offset = 6 + 16 * images.len() as u32;
+  for (size, png) in images {
+    // A size byte of 0 means 256.
+    let dim = if *size >= 256 { 0 } else { *size as u8 };
+    out.push(dim);
+    out.push(dim);
+    out.push(0); // palette colors
+    out.push(0); // reserved
+    out.extend_from_slice(&1u16.to_le_bytes()); // color planes
+    out.extend_from_slice(&32u16.to_le_bytes()); // bits per pixel
+    out.extend_from_slice(&(png.len() as u32).to_le_bytes());
+    out.extend_from_slice(&offset.to_le_bytes());
+    offset += png.len() as u32;
+  }
+  for (_, png) in images {
+    out.extend_from_slice(png);
+  }
+  out
+}
+
+const FAVICON_SNIPPET: &str = r#"<link rel="icon" href="/favicon.ico" sizes="48x48">
+<link rel="icon" type="image/png" sizes="32x32" href="/favicon-32x32.png">
+<link rel="icon" type="image/png" sizes="16x16" href="/favicon-16x16.png">
+<link rel="apple-touch-icon" sizes="180x180" href="/apple-touch-icon.png">
+"#;
+
+/// Generates the standard favicon set from one SVG: 16/32/48 PNGs, favicon.ico,
+/// apple-touch-icon (180) and android-chrome 192/512, plus an optional HTML snippet.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_favicon_bundle(
+  svg_path: String,
+  output_dir: String,
+  background: Option<String>,
+  html_snippet: Option<bool>,
+) -> Result<IconBundle, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let tree = load_tree(Path::new(&svg_path))?;
+    let bg = parse_optional_bg(background.as_deref())?;
+    let dir = PathBuf::from(output_dir);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    let mut ico_images = Vec::new();
+    for size in ICO_SIZES {
+      let pixmap = render_icon(&tree, size, size, 0.0, bg)?;
+      ico_images.push((size, pixmap.encode_png().map_err(|e| e.to_string())?));
+      write_png(dir.join(format!("favicon-{size}x{size}.png")), &pixmap, &mut files)?;
+    }
+    let ico_path = dir.join("favicon.ico");
+    fs::write(&ico_path, encode_ico(&ico_images)).map_err(|e| e.to_string())?;
+    files.push(ico_path.to_string_lossy().to_string());
+
+    // iOS fills transparency with black, so the touch icon gets a white fallback background.
+    let touch_bg = bg.unwrap_or(tiny_skia::Color::WHITE);
+    write_png(
+      dir.join("apple-touch-icon.png"),
+      &render_icon(&tree, 180, 180, 0.0, Some(touch_bg))?,
+      &mut files,
+    )?;
+    for size in [192, 512] {
+      write_png(
+        dir.join(format!("android-chrome-{size}x{size}.png")),
+        &render_icon(&tree, size, size, 0.0, bg)?,
+        &mut files,
+      )?;
+    }
+
+    let snippet = if html_snippet.unwrap_or(false) {
+      let p = dir.join("favicon.html");
+      fs::write(&p, FAVICON_SNIPPET).map_err(|e| e.to_string())?;
+      files.push(p.to_string_lossy().to_string());
+      Some(p.to_string_lossy().to_string())
+    } else {
+      None
+    };
+
+    Ok(IconBundle {
+      files,
+      snippet,
+      manifest: None,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+const PWA_SIZES: [u32; 8] = [72, 96, 128, 144, 152, 192, 384, 512];
+const PWA_MASKABLE_SIZES: [u32; 2] = [192, 512];
+// Maskable icons must keep content inside the central 80% circle, so the content box is
+// the square inscribed in it: (1 - 0.8/sqrt(2)) / 2 of the size on each side.
+const MASKABLE_PADDING: f32 = 0.22;
+
+/// Renders the PWA icon sizes (plus maskable variants with safe-zone padding) and writes
+/// a `manifest.webmanifest` fragment listing them. `src_prefix` is prepended to each `src`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_pwa_icons(
+  svg_path: String,
+  output_dir: String,
+  background: Option<String>,
+  maskable_background: Option<String>,
+  src_prefix: Option<String>,
+) -> Result<IconBundle, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let tree = load_tree(Path::new(&svg_path))?;
+    let bg = parse_optional_bg(background.as_deref())?;
+    // Maskable icons are cropped by the launcher, so they need an opaque background.
+    let mask_bg = parse_optional_bg(maskable_background.as_deref())?
+      .or(bg)
+      .unwrap_or(tiny_skia::Color::WHITE);
+    let prefix = src_prefix.unwrap_or_default();
+    let dir = PathBuf::from(output_dir);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    let mut icons = Vec::new();
+    for size in PWA_SIZES {
+      let name = format!("icon-{size}x{size}.png");
+      write_png(dir.join(&name), &render_icon(&tree, size, size, 0.0, bg)?, &mut files)?;
+      icons.push(serde_json::json!({
+        "src": format!("{prefix}{name}"),
+        "sizes": format!("{size}x{size}"),
+        "type": "image/png",
+        "purpose": "any",
+      }));
+    }
+    for size in PWA_MASKABLE_SIZES {
+      let name = format!("icon-maskable-{size}x{size}.png");
+      write_png(
+        dir.join(&name),
+        &render_icon(&tree, size, size, MASKABLE_PADDING, Some(mask_bg))?,
+        &mut files,
+      )?;
+      icons.push(serde_json::json!({
+        "src": format!("{prefix}{name}"),
+        "sizes": format!("{size}x{size}"),
+        "type": "image/png",
+        "purpose": "maskable",
+      }));
+    }
+
+    let manifest_path = dir.join("manifest.webmanifest");
+    let fragment = serde_json::json!({ "icons": icons });
+    fs::write(
+      &manifest_path,
+      serde_json::to_string_pretty(&fragment).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| e.to_string())?;
+    files.push(manifest_path.to_string_lossy().to_string());
+
+    Ok(IconBundle {
+      files,
+      snippet: None,
+      manifest: Some(manifest_path.to_string_lossy().to_string()),
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+const MSIX_SCALES: [u32; 5] = [100, 125, 150, 200, 400];
+// (asset name, base width, base height) at scale-100.
+const MSIX_LOGOS: [(&str, u32, u32); 6] = [
+  ("Square44x44Logo", 44, 44),
+  ("Square71x71Logo", 71, 71),
+  ("Square150x150Logo", 150, 150),
+  ("Square310x310Logo", 310, 310),
+  ("Wide310x150Logo", 310, 150),
+  ("StoreLogo", 50, 50),
+];
+const MSIX_TARGET_SIZES: [u32; 5] = [16, 24, 32, 48, 256];
+
+/// Renders the MSIX/UWP logo assets (all scale variants plus Square44x44 target sizes)
+/// into an `Assets` folder, matching the names a package manifest expects.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_windows_tiles(
+  svg_path: String,
+  output_dir: String,
+  background: Option<String>,
+) -> Result<IconBundle, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let tree = load_tree(Path::new(&svg_path))?;
+    let bg = parse_optional_bg(background.as_deref())?;
+    let dir = PathBuf::from(output_dir).join("Assets");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    for (name, base_w, base_h) in MSIX_LOGOS {
+      for scale in MSIX_SCALES {
+        let w = (base_w * scale + 50) / 100;
+        let h = (base_h * scale + 50) / 100;
+        write_png(
+          dir.join(format!("{name}.scale-{scale}.png")),
+          &render_icon(&tree, w, h, 0.0, bg)?,
+          &mut files,
+        )?;
+      }
+    }
+    // Taskbar/start list icons are picked by exact pixel size; the unplated variants
+    // are drawn without the accent-colored plate.
+    for size in MSIX_TARGET_SIZES {
+      let pixmap = render_icon(&tree, size, size, 0.0, bg)?;
+      write_png(
+        dir.join(format!("Square44x44Logo.targetsize-{size}.png")),
+        &pixmap,
+        &mut files,
+      )?;
+      write_png(
+        dir.join(format!("Square44x44Logo.targetsize-{size}_altform-unplated.png")),
+        &pixmap,
+        &mut files,
+      )?;
+    }
+
+    Ok(IconBundle {
+      files,
+      snippet: None,
+      manifest: None,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
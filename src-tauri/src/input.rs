@@ -0,0 +1,90 @@
+use std::{fs::File, io::Read, ops::Deref, path::Path};
+
+use flate2::read::GzDecoder;
+use memmap2::Mmap;
+
+// Machine-generated SVGs can run to hundreds of MB. Those are memory-mapped rather than read,
+// so the file's bytes aren't held in memory a second time next to the parsed tree, and sizes
+// the parser can't realistically handle are refused before anything is loaded.
+//
+// Gzip-compressed SVGs (.svgz, common from map and CAD tools) are inflated here, so every
+// later step sees plain SVG text.
+
+// Smaller files are cheaper to read than to map.
+const MMAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+// Parsing needs several times the file size in memory on top of the file itself.
+pub(crate) const MAX_SOURCE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// A source file's bytes, read or mapped.
+pub(crate) enum SourceBytes {
+  Owned(Vec<u8>),
+  Mapped(Mmap),
+}
+
+impl Deref for SourceBytes {
+  type Target = [u8];
+
+  fn deref(&self) -> &[u8] {
+    match self {
+      Self::Owned(data) => data,
+      Self::Mapped(map) => map,
+    }
+  }
+}
+
+/// Refuses sources too large to convert, with the size in the message.
+pub(crate) fn check_size(path: &Path, len: u64) -> Result<(), String> {
+  if len <= MAX_SOURCE_BYTES {
+    return Ok(());
+  }
+  let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+  Err(format!(
+    "{name} is {} MB; files over {} MB can't be converted (parsing needs several times that in memory).",
+    len / (1024 * 1024),
+    MAX_SOURCE_BYTES / (1024 * 1024)
+  ))
+}
+
+/// Reads a source file, mapping it instead when it is large, and inflating it if gzipped.
+pub(crate) fn read_source(path: &Path) -> Result<SourceBytes, String> {
+  let mut file = File::open(path).map_err(|e| e.to_string())?;
+  let len = file.metadata().map_err(|e| e.to_string())?.len();
+  check_size(path, len)?;
+  let data = if len < MMAP_THRESHOLD {
+    let mut data = Vec::with_capacity(len as usize);
+    file.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    SourceBytes::Owned(data)
+  } else {
+    // SAFETY: the map is read-only and lives only while one file converts. If another program
+    // truncates the file meanwhile, reads past the new end fault; sources are inputs the user
+    // picked, so that is left as their risk rather than copying every large file.
+    let map = unsafe { Mmap::map(&file) }.map_err(|e| e.to_string())?;
+    SourceBytes::Mapped(map)
+  };
+  if data.starts_with(&GZIP_MAGIC) {
+    return inflate(path, &data).map(SourceBytes::Owned);
+  }
+  Ok(data)
+}
+
+/// Takes a source already in memory (e.g. read out of an archive), inflating it if gzipped.
+pub(crate) fn from_bytes(path: &Path, data: Vec<u8>) -> Result<SourceBytes, String> {
+  check_size(path, data.len() as u64)?;
+  if data.starts_with(&GZIP_MAGIC) {
+    return inflate(path, &data).map(SourceBytes::Owned);
+  }
+  Ok(SourceBytes::Owned(data))
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// The inflated size gets the same cap as a plain file, which also stops gzip bombs early.
+fn inflate(path: &Path, gz: &[u8]) -> Result<Vec<u8>, String> {
+  let mut data = Vec::new();
+  GzDecoder::new(gz)
+    .take(MAX_SOURCE_BYTES + 1)
+    .read_to_end(&mut data)
+    .map_err(|e| format!("Failed to decompress: {e}"))?;
+  check_size(path, data.len() as u64)?;
+  Ok(data)
+}
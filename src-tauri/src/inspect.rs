@@ -0,0 +1,184 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Content in an SVG whose static render may not match what the user sees in a browser.
+#[derive(Debug, Clone, Copy, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SvgFeatures {
+  pub animated: bool, // SMIL elements or CSS animations
+  pub scripted: bool, // <script> or inline event handlers
+  pub embedded_raster: bool, // base64 data: images (PNG/JPEG/...)
+  pub external_refs: bool, // images/CSS loaded from other files or URLs
+}
+
+impl SvgFeatures {
+  pub fn any(&self) -> bool {
+    self.animated || self.scripted || self.embedded_raster || self.external_refs
+  }
+
+  /// Short warning codes for item events.
+  pub fn warnings(&self) -> Vec<String> {
+    let mut out = Vec::new();
+    if self.animated {
+      out.push("animated".to_string());
+    }
+    if self.scripted {
+      out.push("scripted".to_string());
+    }
+    if self.embedded_raster {
+      out.push("embeddedRaster".to_string());
+    }
+    if self.external_refs {
+      out.push("externalRefs".to_string());
+    }
+    out
+  }
+}
+
+const CSS_ANIMATION_PROPERTIES: [&str; 2] = ["animation:", "animation-name"];
+
+// Markers are lowercase; the source is matched against them ignoring ASCII case, in place, so
+// a large file isn't copied just to be lowercased.
+fn find(text: &[u8], marker: &str) -> Option<usize> {
+  text
+    .windows(marker.len())
+    .position(|w| w.eq_ignore_ascii_case(marker.as_bytes()))
+}
+
+fn starts_with(value: &[u8], prefix: &str) -> bool {
+  value
+    .get(..prefix.len())
+    .is_some_and(|v| v.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+fn trim(mut value: &[u8]) -> &[u8] {
+  while let [first, rest @ ..] = value {
+    if !first.is_ascii_whitespace() {
+      break;
+    }
+    value = rest;
+  }
+  while let [rest @ .., last] = value {
+    if !last.is_ascii_whitespace() {
+      break;
+    }
+    value = rest;
+  }
+  value
+}
+
+// Elements whose `href` / `xlink:href` loads a resource; on others (e.g. `<a>`) it's only a link.
+const LOADING_ELEMENTS: [&str; 3] = ["image", "use", "feimage"];
+
+// The element name at the start of `tag` (just past its '<'), without any namespace prefix.
+fn tag_name(tag: &[u8]) -> &[u8] {
+  let end = tag
+    .iter()
+    .position(|&c| c.is_ascii_whitespace() || c == b'/' || c == b'>')
+    .unwrap_or(tag.len());
+  let name = &tag[..end];
+  match name.iter().rposition(|&c| c == b':') {
+    Some(colon) => &name[colon + 1..],
+    None => name,
+  }
+}
+
+// Name of the element whose tag `before` ends inside.
+fn enclosing_tag(before: &[u8]) -> &[u8] {
+  match before.iter().rposition(|&c| c == b'<') {
+    Some(open) => tag_name(&before[open + 1..]),
+    None => &[],
+  }
+}
+
+// An `on…=` event handler attribute starting at `attr`, e.g. `onload="…"` or `onclick = "…"`.
+fn is_event_handler(attr: &[u8]) -> bool {
+  if !starts_with(attr, "on") {
+    return false;
+  }
+  let name_len = attr[2..].iter().take_while(|c| c.is_ascii_alphabetic()).count();
+  name_len > 0 && trim(&attr[2 + name_len..]).first() == Some(&b'=')
+}
+
+#[derive(Default)]
+struct Markers {
+  smil: bool,          // <animate…> or <set>
+  css_animation: bool, // @keyframes or animation properties
+  script: bool,        // <script>, javascript: URLs or on… handlers
+  import: bool,        // CSS @import
+}
+
+// One pass over the source, looking only at positions where a marker can start.
+fn scan_markers(data: &[u8]) -> Markers {
+  let mut found = Markers::default();
+  for (i, &c) in data.iter().enumerate() {
+    let rest = &data[i..];
+    match c {
+      b'<' => {
+        let name = tag_name(&rest[1..]);
+        if starts_with(name, "animate") || name.eq_ignore_ascii_case(b"set") {
+          found.smil = true;
+        } else if name.eq_ignore_ascii_case(b"script") {
+          found.script = true;
+        }
+      }
+      c if c.is_ascii_whitespace() => found.script |= is_event_handler(&rest[1..]),
+      b'@' => {
+        found.css_animation |= starts_with(rest, "@keyframes");
+        found.import |= starts_with(rest, "@import");
+      }
+      b'a' | b'A' => found.css_animation |= CSS_ANIMATION_PROPERTIES.iter().any(|m| starts_with(rest, m)),
+      b'j' | b'J' => found.script |= starts_with(rest, "javascript:"),
+      _ => {}
+    }
+  }
+  found
+}
+
+// Values of `href="…"` on resource-loading elements and of `url(…)` references.
+fn reference_values(text: &[u8]) -> Vec<&[u8]> {
+  let mut out = Vec::new();
+  for (marker, closer) in [("href=", None), ("url(", Some(b')'))] {
+    let mut rest = text;
+    while let Some(pos) = find(rest, marker) {
+      let at = text.len() - rest.len() + pos;
+      rest = &rest[pos + marker.len()..];
+      if closer.is_none() {
+        let tag = enclosing_tag(&text[..at]);
+        if !LOADING_ELEMENTS.iter().any(|e| tag.eq_ignore_ascii_case(e.as_bytes())) {
+          continue;
+        }
+      }
+      let trimmed = trim(rest);
+      let (value, quote) = match trimmed.first() {
+        Some(q @ (b'"' | b'\'')) => (&trimmed[1..], Some(*q)),
+        _ => (trimmed, None),
+      };
+      let end = value
+        .iter()
+        .position(|&c| Some(c) == quote || Some(c) == closer || (quote.is_none() && c.is_ascii_whitespace()))
+        .unwrap_or(value.len());
+      out.push(trim(&value[..end]));
+    }
+  }
+  out
+}
+
+/// Cheap text-level scan; no parsing, so it also works on files usvg rejects.
+pub(crate) fn inspect_svg(data: &[u8]) -> SvgFeatures {
+  let markers = scan_markers(data);
+  let refs = reference_values(data);
+  SvgFeatures {
+    animated: markers.smil || markers.css_animation,
+    scripted: markers.script,
+    embedded_raster: refs
+      .iter()
+      .any(|r| starts_with(r, "data:image/") && !starts_with(r, "data:image/svg")),
+    // Fragment links (#id) and data: URIs are self-contained; anything else is loaded from elsewhere.
+    external_refs: markers.import
+      || refs
+        .iter()
+        .any(|r| !r.is_empty() && !r.starts_with(b"#") && !starts_with(r, "data:") && !starts_with(r, "javascript:")),
+  }
+}
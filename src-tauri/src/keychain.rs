@@ -0,0 +1,45 @@
+// Secrets (upload secret keys, SSH key passphrases) live in the system keychain, never in the
+// settings file. The keychain crate only covers desktop platforms; on Android and iOS every
+// lookup fails, which leaves uploads and SFTP unavailable there.
+
+#[cfg(desktop)]
+pub(crate) fn get(service: &str, account: &str) -> Result<String, String> {
+  keyring::Entry::new(service, account)
+    .and_then(|e| e.get_password())
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(desktop)]
+pub(crate) fn set(service: &str, account: &str, secret: &str) -> Result<(), String> {
+  keyring::Entry::new(service, account)
+    .and_then(|e| e.set_password(secret))
+    .map_err(|e| e.to_string())
+}
+
+/// Removes a stored secret; one that was never stored counts as removed.
+#[cfg(desktop)]
+pub(crate) fn delete(service: &str, account: &str) -> Result<(), String> {
+  let entry = keyring::Entry::new(service, account).map_err(|e| e.to_string())?;
+  match entry.delete_credential() {
+    Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+    Err(e) => Err(e.to_string()),
+  }
+}
+
+#[cfg(mobile)]
+const UNAVAILABLE: &str = "The system keychain isn't available on this device.";
+
+#[cfg(mobile)]
+pub(crate) fn get(_service: &str, _account: &str) -> Result<String, String> {
+  Err(UNAVAILABLE.into())
+}
+
+#[cfg(mobile)]
+pub(crate) fn set(_service: &str, _account: &str, _secret: &str) -> Result<(), String> {
+  Err(UNAVAILABLE.into())
+}
+
+#[cfg(mobile)]
+pub(crate) fn delete(_service: &str, _account: &str) -> Result<(), String> {
+  Err(UNAVAILABLE.into())
+}
@@ -0,0 +1,400 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{convert::ConvertRequest, filters::ColorVision, svgout};
+
+// Output file stem: the SVG stem, prefixed with its relative folder (folder mode)
+// or parent folder name (file mode with an output dir) to reduce collisions, in the
+// requested Unicode form and made safe to write on every platform.
+pub(crate) fn output_stem(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+) -> String {
+  let raw = raw_output_stem(svg_path, root, out_dir);
+  sanitize_stem(&normalize_stem(&raw, req.file_name_form.as_deref()))
+}
+
+/// The original stem when normalizing or sanitizing changed it, so the rename can be reported.
+pub(crate) fn renamed_stem(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+) -> Option<String> {
+  let raw = raw_output_stem(svg_path, root, out_dir);
+  (output_stem(svg_path, req, root, out_dir) != raw).then_some(raw)
+}
+
+pub(crate) fn validate_file_name_form(form: &str) -> Result<(), String> {
+  match form {
+    "keep" | "nfc" | "ascii" => Ok(()),
+    _ => Err("Invalid file name form (expected keep, nfc or ascii).".into()),
+  }
+}
+
+// macOS hands out decomposed (NFD) names, which other systems treat as different files
+// from the composed names typed there. "nfc" composes; "ascii" transliterates (é → e).
+fn normalize_stem(stem: &str, form: Option<&str>) -> String {
+  match form {
+    Some("nfc") => stem.nfc().collect(),
+    Some("ascii") => deunicode::deunicode_with_tofu(stem, "_"),
+    _ => stem.to_string(),
+  }
+}
+
+const RESERVED_NAMES: [&str; 22] = [
+  "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+  "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// Replaces characters Windows or macOS reject (`<>:"/\|?*` and controls), drops trailing
+// dots and spaces, and suffixes reserved device names like `CON` with an underscore.
+fn sanitize_stem(stem: &str) -> String {
+  let mut name: String = stem
+    .chars()
+    .map(|c| if c.is_control() || "<>:\"/\\|?*".contains(c) { '_' } else { c })
+    .collect();
+  name.truncate(name.trim_end_matches(['.', ' ']).len());
+  if name.is_empty() {
+    return "output".into();
+  }
+  let device = name.split('.').next().unwrap_or_default();
+  if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(device)) {
+    name.push('_');
+  }
+  name
+}
+
+fn raw_output_stem(svg_path: &Path, root: Option<&Path>, out_dir: Option<&Path>) -> String {
+  let base = svg_path
+    .file_stem()
+    .and_then(|s| s.to_str())
+    .unwrap_or("output")
+    .to_string();
+
+  let mut rel_prefix = String::new();
+  if let Some(root) = root {
+    if let Ok(rel) = svg_path.strip_prefix(root) {
+      if let Some(parent) = rel.parent() {
+        let p = parent.to_string_lossy();
+        if !p.is_empty() && p != "." {
+          rel_prefix = p.replace(['/', '\\'], "_");
+        }
+      }
+    }
+  }
+  // When exporting multiple files to a single output directory (file mode),
+  // prefix with the parent folder name to reduce collisions.
+  if rel_prefix.is_empty() && root.is_none() && out_dir.is_some() {
+    if let Some(parent_name) = svg_path
+      .parent()
+      .and_then(|p| p.file_name())
+      .and_then(|s| s.to_str())
+      .filter(|s| !s.is_empty())
+    {
+      rel_prefix = parent_name.to_string();
+    }
+  }
+
+  if rel_prefix.is_empty() {
+    base
+  } else {
+    format!("{rel_prefix}_{base}")
+  }
+}
+
+// Folder the outputs for `svg_path` are written to.
+pub(crate) fn output_base_dir(svg_path: &Path, out_dir: Option<&Path>) -> PathBuf {
+  match out_dir {
+    Some(d) => d.to_path_buf(),
+    None => svg_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+  }
+}
+
+fn make_output_path(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+  out_w: u32,
+  out_h: u32,
+) -> PathBuf {
+  let stem = output_stem(svg_path, req, root, out_dir);
+  output_base_dir(svg_path, out_dir).join(format!("{stem}_{out_w}x{out_h}.png"))
+}
+
+// Windows rejects paths of MAX_PATH characters or more unless they use the `\\?\`
+// verbatim form, which deep folder-mode trees plus the `_WxH` suffix easily reach.
+const MAX_PATH: usize = 260;
+// Longest single file or folder name NTFS, APFS and ext4 accept.
+const MAX_NAME: usize = 255;
+
+/// The path to read and write `path` through: verbatim-prefixed on Windows when it's too
+/// long for the regular form. Reported paths stay in the regular form.
+pub(crate) fn io_path(path: &Path) -> Result<PathBuf, String> {
+  if let Some(name) = path.components().map(|c| c.as_os_str()).find(|c| c.len() > MAX_NAME) {
+    return Err(format!("Output name is longer than {MAX_NAME} characters: {}", name.to_string_lossy()));
+  }
+  let display = path.to_string_lossy();
+  if !cfg!(windows) || display.encode_utf16().count() < MAX_PATH || display.starts_with(r"\\?\") {
+    return Ok(path.to_path_buf());
+  }
+  let abs = std::path::absolute(path).map_err(|e| {
+    format!("Output path is longer than {MAX_PATH} characters and couldn't be resolved ({e}).")
+  })?;
+  // Verbatim paths skip separator normalization, so they must use backslashes only.
+  let abs = abs.to_string_lossy().replace('/', "\\");
+  Ok(PathBuf::from(match abs.strip_prefix(r"\\") {
+    Some(unc) => format!(r"\\?\UNC\{unc}"),
+    None => format!(r"\\?\{abs}"),
+  }))
+}
+
+// Run folder name like `2024-06-01_1432`, in UTC since no timezone database is bundled.
+fn run_folder_name(now: SystemTime) -> String {
+  let secs = now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+  let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+  // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+  let z = days + 719_468;
+  let era = z.div_euclid(146_097);
+  let doe = z.rem_euclid(146_097);
+  let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+  let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+  let mp = (5 * doy + 2) / 153;
+  let day = doy - (153 * mp + 2) / 5 + 1;
+  let month = if mp < 10 { mp + 3 } else { mp - 9 };
+  let year = yoe + era * 400 + i64::from(month <= 2);
+  format!("{year:04}-{month:02}-{day:02}_{:02}{:02}", rem / 3_600, rem % 3_600 / 60)
+}
+
+/// Creates a fresh dated folder under `out_dir` for one batch, suffixed `_2`, `_3`, ...
+/// when a run in the same minute already made one.
+pub(crate) fn create_run_dir(out_dir: &Path) -> Result<PathBuf, String> {
+  let name = run_folder_name(SystemTime::now());
+  let mut dir = out_dir.join(&name);
+  let mut n = 2;
+  while dir.exists() {
+    dir = out_dir.join(format!("{name}_{n}"));
+    n += 1;
+  }
+  fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  Ok(dir)
+}
+
+const ANDROID_DENSITIES: [(&str, f64); 5] = [
+  ("mdpi", 1.0),
+  ("hdpi", 1.5),
+  ("xhdpi", 2.0),
+  ("xxhdpi", 3.0),
+  ("xxxhdpi", 4.0),
+];
+
+// Android resource names may only contain lowercase letters, digits and underscores.
+fn android_resource_name(stem: &str) -> String {
+  let mut name: String = stem
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+    .collect();
+  if !name.starts_with(|c: char| c.is_ascii_lowercase()) {
+    name.insert_str(0, "ic_");
+  }
+  name
+}
+
+pub(crate) struct OutputTarget {
+  pub path: PathBuf,
+  pub width: u32,
+  pub height: u32,
+  pub color_vision: Option<ColorVision>, // simulate this deficiency on the render
+}
+
+/// Everything to write for one SVG: the PNGs to render plus any sidecar files
+/// (e.g. an asset catalog's `Contents.json`), written after the PNGs.
+pub(crate) struct OutputPlan {
+  pub targets: Vec<OutputTarget>,
+  pub extra_files: Vec<(PathBuf, String)>,
+}
+
+impl From<Vec<OutputTarget>> for OutputPlan {
+  fn from(targets: Vec<OutputTarget>) -> Self {
+    Self {
+      targets,
+      extra_files: Vec::new(),
+    }
+  }
+}
+
+// One `<name>.imageset` folder per SVG with 1x/2x/3x PNGs and a Contents.json, ready for Xcode.
+fn plan_ios_imageset(stem: &str, dir: &Path, base_w: u32, base_h: u32) -> Result<OutputPlan, String> {
+  let set_dir = dir.join(format!("{stem}.imageset"));
+  let mut targets = Vec::new();
+  let mut images = Vec::new();
+  for (m, suffix) in [(1, ""), (2, "@2x"), (3, "@3x")] {
+    let file_name = format!("{stem}{suffix}.png");
+    images.push(serde_json::json!({
+      "idiom": "universal",
+      "filename": file_name,
+      "scale": format!("{m}x"),
+    }));
+    let (width, height) = scaled_size(base_w, base_h, m)?;
+    targets.push(OutputTarget {
+      path: set_dir.join(&file_name),
+      width,
+      height,
+      color_vision: None,
+    });
+  }
+  let contents = serde_json::json!({
+    "images": images,
+    "info": { "version": 1, "author": "xcode" },
+  });
+  Ok(OutputPlan {
+    targets,
+    extra_files: vec![(
+      set_dir.join("Contents.json"),
+      serde_json::to_string_pretty(&contents).unwrap_or_default(),
+    )],
+  })
+}
+
+pub(crate) fn validate_target(target: &str) -> Result<(), String> {
+  match target {
+    "android" | "ios" => Ok(()),
+    _ => Err("Invalid target preset.".into()),
+  }
+}
+
+pub(crate) fn validate_group_by(group_by: &str) -> Result<(), String> {
+  match group_by {
+    "none" | "size" | "format" => Ok(()),
+    _ => Err("Invalid output grouping (expected none, size or format).".into()),
+  }
+}
+
+// Moves each output into a `64x64/` or `png/` subfolder of where it would have gone.
+// Platform targets already have their own folder structure and are left alone.
+fn group_outputs(plan: &mut OutputPlan, req: &ConvertRequest) {
+  if req.target.is_some() {
+    return;
+  }
+  let format = svgout::vector_format(req).unwrap_or("png");
+  for t in &mut plan.targets {
+    let folder = match req.group_by.as_deref() {
+      Some("size") => format!("{}x{}", t.width, t.height),
+      Some("format") => format.to_string(),
+      _ => return,
+    };
+    if let (Some(parent), Some(name)) = (t.path.parent(), t.path.file_name()) {
+      t.path = parent.join(folder).join(name);
+    }
+  }
+}
+
+// Every PNG to produce for one SVG, given its 1x output size. Color-vision variants
+// follow the output they're derived from, with the deficiency appended to the name
+// (and land in the same group folder).
+pub(crate) fn plan_outputs(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+  base_w: u32,
+  base_h: u32,
+) -> Result<OutputPlan, String> {
+  let mut plan = plan_base_outputs(svg_path, req, root, out_dir, base_w, base_h)?;
+  group_outputs(&mut plan, req);
+  // Validated with the rest of the batch options.
+  let variants = ColorVision::from_request(req).unwrap_or_default();
+  if variants.is_empty() {
+    return Ok(plan);
+  }
+  let mut targets = Vec::with_capacity(plan.targets.len() * (variants.len() + 1));
+  for t in plan.targets {
+    let stem = t.path.file_stem().and_then(|s| s.to_str()).unwrap_or("output").to_string();
+    let extra: Vec<OutputTarget> = variants
+      .iter()
+      .map(|kind| OutputTarget {
+        path: t.path.with_file_name(format!("{stem}_{}.png", kind.name())),
+        width: t.width,
+        height: t.height,
+        color_vision: Some(*kind),
+      })
+      .collect();
+    targets.push(t);
+    targets.extend(extra);
+  }
+  plan.targets = targets;
+  Ok(plan)
+}
+
+// The 1x size times an integer scale, refused rather than wrapped when it doesn't fit.
+fn scaled_size(base_w: u32, base_h: u32, m: u32) -> Result<(u32, u32), String> {
+  match (base_w.checked_mul(m), base_h.checked_mul(m)) {
+    (Some(w), Some(h)) => Ok((w, h)),
+    _ => Err(format!(
+      "Output size {base_w}x{base_h} is too large for the {m}x variant."
+    )),
+  }
+}
+
+fn plan_base_outputs(
+  svg_path: &Path,
+  req: &ConvertRequest,
+  root: Option<&Path>,
+  out_dir: Option<&Path>,
+  base_w: u32,
+  base_h: u32,
+) -> Result<OutputPlan, String> {
+  if req.target.as_deref() == Some("ios") {
+    let stem = output_stem(svg_path, req, root, out_dir);
+    return plan_ios_imageset(&stem, &output_base_dir(svg_path, out_dir), base_w, base_h);
+  }
+  if req.target.as_deref() == Some("android") {
+    // The computed size is the mdpi (1x) baseline.
+    let name = android_resource_name(&output_stem(svg_path, req, root, out_dir));
+    let dir = output_base_dir(svg_path, out_dir);
+    return Ok(
+      ANDROID_DENSITIES
+        .into_iter()
+        .map(|(density, factor)| OutputTarget {
+          path: dir.join(format!("drawable-{density}")).join(format!("{name}.png")),
+          width: ((base_w as f64) * factor).round().max(1.0) as u32,
+          height: ((base_h as f64) * factor).round().max(1.0) as u32,
+          color_vision: None,
+        })
+        .collect::<Vec<_>>()
+        .into(),
+    );
+  }
+  if req.density_set.unwrap_or(false) {
+    let stem = output_stem(svg_path, req, root, out_dir);
+    let dir = output_base_dir(svg_path, out_dir);
+    let mut targets = Vec::with_capacity(3);
+    for (m, suffix) in [(1, ""), (2, "@2x"), (3, "@3x")] {
+      let (width, height) = scaled_size(base_w, base_h, m)?;
+      targets.push(OutputTarget {
+        path: dir.join(format!("{stem}{suffix}.png")),
+        width,
+        height,
+        color_vision: None,
+      });
+    }
+    return Ok(targets.into());
+  }
+  Ok(
+    vec![OutputTarget {
+      path: make_output_path(svg_path, req, root, out_dir, base_w, base_h),
+      width: base_w,
+      height: base_h,
+      color_vision: None,
+    }]
+    .into(),
+  )
+}
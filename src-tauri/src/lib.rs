@@ -1,4 +1,53 @@
+mod animate;
+mod archive;
+mod batches;
+mod conflict;
 mod convert;
+mod dropzone;
+mod dupes;
+mod encode;
+mod eps;
+mod extract;
+mod filters;
+mod finder;
+mod gamut;
+mod gpu;
+mod hdr;
+mod history;
+mod icons;
+mod input;
+mod inspect;
+mod keychain;
+mod layout;
+mod linear;
+mod lottie;
+mod metadata;
+mod mobile;
+mod monitor;
+mod montage;
+mod passthrough;
+mod pdf;
+mod postfx;
+mod preprocess;
+mod presets;
+mod preview;
+mod print;
+mod progress;
+mod queue;
+mod report;
+mod resample;
+mod rules;
+mod selftest;
+mod sprite;
+mod svgout;
+mod trace;
+mod upload;
+mod walk;
+mod watermark;
+mod webhook;
+mod settings;
+mod sftp;
+mod window;
 
 use tauri::Manager;
 
@@ -6,25 +55,25 @@ use tauri::Manager;
 pub fn run() {
   tauri::Builder::default()
     .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_share::init())
     .setup(|app| {
+      let store = settings::SettingsStore::load(app.handle());
+      let prefs = store.get();
+      app.manage(store);
+      app.manage(dropzone::DropZoneState::default());
+      app.manage(preview::PreviewState::default());
+      app.manage(conflict::ConflictRegistry::default());
+      app.manage(history::BatchHistory::default());
+      app.manage(monitor::BatchMonitors::default());
+      app.manage(batches::ActiveBatches::default());
+      app.manage(queue::JobQueue::default());
+
       if let Some(win) = app.get_webview_window("main") {
-        // Force a consistent startup window size (avoid macOS restore geometry surprises).
-        let _ = win.set_size(tauri::Size::Logical(tauri::LogicalSize::<f64> {
-          width: 1240.0,
-          height: 830.0,
-        }));
-        let _ = win.set_resizable(false);
-        let _ = win.center();
+        // Restore the last saved geometry ourselves (avoid macOS restore geometry surprises).
+        window::restore_geometry(&win, &prefs.window);
 
         // Best-effort: apply a vibrancy style on macOS so the desktop can show through.
-        // Note: Some variants are deprecated upstream, but still work on current Tauri.
-        let _ = win.set_effects(Some(
-          tauri::window::EffectsBuilder::new()
-            .effect(tauri::window::Effect::UnderWindowBackground)
-            .state(tauri::window::EffectState::Active)
-            .radius(22.0)
-            .build(),
-        ));
+        let _ = window::apply_vibrancy(&win, prefs.window.vibrancy);
 
         let _ = win.show();
         let _ = win.set_focus();
@@ -39,11 +88,56 @@ pub fn run() {
       }
       Ok(())
     })
+    .on_window_event(window::on_window_event)
     .invoke_handler(tauri::generate_handler![
       convert::get_svg_size,
       convert::count_svg_files,
       convert::scan_svg_folder_sizes,
-      convert::convert_svg_to_png
+      convert::list_svg_files,
+      convert::estimate_batch_output,
+      convert::convert_svg_to_png,
+      convert::convert_svg_to_png_streamed,
+      batches::list_active_batches,
+      queue::list_jobs,
+      queue::reorder_job,
+      queue::bump_job,
+      queue::set_low_power,
+      presets::list_size_presets,
+      lottie::lottie_supported,
+      conflict::resolve_conflict,
+      history::undo_batch,
+      window::get_window_options,
+      window::set_window_options,
+      window::get_system_theme,
+      window::set_vibrancy,
+      metadata::get_metadata_settings,
+      metadata::set_metadata_settings,
+      upload::get_upload_settings,
+      upload::set_upload_settings,
+      sftp::set_sftp_key_passphrase,
+      webhook::get_webhook_settings,
+      webhook::set_webhook_settings,
+      icons::generate_favicon_bundle,
+      icons::generate_pwa_icons,
+      icons::generate_windows_tiles,
+      montage::generate_montage,
+      extract::extract_embedded_images,
+      animate::export_animation_frames,
+      print::print_render,
+      progress::get_progress_file_path,
+      monitor::open_progress_window,
+      trace::vectorize_image,
+      dropzone::set_active_preset,
+      dropzone::enter_drop_zone_mode,
+      dropzone::exit_drop_zone_mode,
+      mobile::convert_shared_svg,
+      mobile::share_shared_output,
+      mobile::clear_shared_outputs,
+      selftest::run_self_test,
+      preview::open_preview,
+      preview::update_preview,
+      preview::close_preview,
+      preview::preview_batch
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
@@ -0,0 +1,89 @@
+// Lottie (Bodymovin JSON) input, rendered through rlottie. The renderer needs the
+// native rlottie library, so it's behind the `lottie` cargo feature; without it,
+// .json files are simply not picked up as inputs.
+use std::path::Path;
+
+use resvg::tiny_skia;
+
+use crate::convert::{ConvertRequest, SvgSize};
+
+const SUPPORTED: bool = cfg!(feature = "lottie");
+
+// Lottie files are plain .json; only ones that look like an animation count as input.
+pub(crate) fn is_lottie(path: &Path) -> bool {
+  SUPPORTED
+    && path
+      .extension()
+      .and_then(|s| s.to_str())
+      .is_some_and(|s| s.eq_ignore_ascii_case("json"))
+    && std::fs::read(path)
+      .ok()
+      .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+      .is_some_and(|v| v.get("layers").is_some_and(|l| l.is_array()) && v.get("fr").is_some())
+}
+
+#[cfg(feature = "lottie")]
+pub(crate) struct Animation {
+  inner: rlottie::Animation,
+}
+
+// Nothing can be loaded without the renderer, so there are no values of this type.
+#[cfg(not(feature = "lottie"))]
+pub(crate) enum Animation {}
+
+#[cfg(feature = "lottie")]
+impl Animation {
+  pub(crate) fn load(data: &[u8], path: &Path) -> Result<Self, String> {
+    let resources = path.parent().unwrap_or(Path::new("."));
+    // An empty cache key keeps rlottie from caching every file of a batch.
+    rlottie::Animation::from_data(data.to_vec(), "", resources)
+      .map(|inner| Animation { inner })
+      .ok_or_else(|| "Failed to parse Lottie file.".to_string())
+  }
+
+  pub(crate) fn size(&self) -> SvgSize {
+    let size = self.inner.size();
+    SvgSize {
+      width: size.width.max(1) as u32,
+      height: size.height.max(1) as u32,
+    }
+  }
+
+  /// Renders the frame shown at `req.time` seconds (the first frame by default) as a
+  /// transparent, premultiplied pixmap; the animation is fitted into `w`×`h`.
+  pub(crate) fn render(&mut self, req: &ConvertRequest, w: u32, h: u32) -> Result<tiny_skia::Pixmap, String> {
+    let last = self.inner.totalframe().saturating_sub(1);
+    let frame = ((req.time.unwrap_or(0.0) * self.inner.framerate()).round().max(0.0) as usize).min(last);
+
+    let mut surface = rlottie::Surface::new(rlottie::Size::new(w as usize, h as usize));
+    self.inner.render(frame, &mut surface);
+
+    let mut pixmap = tiny_skia::Pixmap::new(w, h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+    // rlottie writes premultiplied BGRA; tiny-skia wants premultiplied RGBA.
+    for (dst, src) in pixmap.data_mut().chunks_exact_mut(4).zip(surface.data()) {
+      dst.copy_from_slice(&[src.r, src.g, src.b, src.a]);
+    }
+    Ok(pixmap)
+  }
+}
+
+#[cfg(not(feature = "lottie"))]
+impl Animation {
+  pub(crate) fn load(_data: &[u8], _path: &Path) -> Result<Self, String> {
+    Err("This build doesn't include Lottie support.".into())
+  }
+
+  pub(crate) fn size(&self) -> SvgSize {
+    match *self {}
+  }
+
+  pub(crate) fn render(&mut self, _req: &ConvertRequest, _w: u32, _h: u32) -> Result<tiny_skia::Pixmap, String> {
+    match *self {}
+  }
+}
+
+/// Whether this build can convert Lottie files, so the UI only offers them when it can.
+#[tauri::command]
+pub fn lottie_supported() -> bool {
+  SUPPORTED
+}
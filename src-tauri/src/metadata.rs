@@ -0,0 +1,106 @@
+use tauri::State;
+
+use crate::{
+  gamut,
+  settings::{MetadataSettings, SettingsStore},
+};
+
+// Authorship metadata for written PNGs: plain iTXt entries for viewers that show PNG
+// text, plus an XMP packet (dc:creator, dc:rights, xmpRights:UsageTerms) for DAMs and
+// editors that only read XMP.
+
+const XMP_KEYWORD: &str = "XML:com.adobe.xmp";
+
+fn clean(v: Option<String>) -> Option<String> {
+  v.map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+impl MetadataSettings {
+  pub(crate) fn is_empty(&self) -> bool {
+    self.author.is_none() && self.copyright.is_none() && self.license.is_none()
+  }
+}
+
+fn escape_xml(s: &str) -> String {
+  s.replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+fn itxt(keyword: &str, text: &str) -> Vec<u8> {
+  // Keyword, then uncompressed, with empty language tag and translated keyword.
+  [keyword.as_bytes(), b"\0\0\0\0\0", text.as_bytes()].concat()
+}
+
+fn xmp_packet(meta: &MetadataSettings) -> String {
+  let alt = |v: &str| {
+    format!(
+      "<rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt>",
+      escape_xml(v)
+    )
+  };
+  let mut props = String::new();
+  if let Some(a) = &meta.author {
+    props.push_str(&format!(
+      "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+      escape_xml(a)
+    ));
+  }
+  if let Some(c) = &meta.copyright {
+    props.push_str(&format!("<dc:rights>{}</dc:rights>", alt(c)));
+  }
+  if let Some(l) = &meta.license {
+    props.push_str(&format!("<xmpRights:UsageTerms>{}</xmpRights:UsageTerms>", alt(l)));
+  }
+  format!(
+    concat!(
+      "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>",
+      "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"><rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+      "<rdf:Description rdf:about=\"\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" ",
+      "xmlns:xmpRights=\"http://ns.adobe.com/xap/1.0/rights/\">{}</rdf:Description>",
+      "</rdf:RDF></x:xmpmeta><?xpacket end=\"r\"?>"
+    ),
+    props
+  )
+}
+
+/// Adds the configured author/copyright/license to an encoded PNG.
+pub(crate) fn embed(png: &[u8], meta: &MetadataSettings) -> Vec<u8> {
+  let mut chunks = Vec::new();
+  for (keyword, value) in [
+    ("Author", &meta.author),
+    ("Copyright", &meta.copyright),
+    ("License", &meta.license),
+  ] {
+    if let Some(v) = value {
+      chunks.push(itxt(keyword, v));
+    }
+  }
+  chunks.push(itxt(XMP_KEYWORD, &xmp_packet(meta)));
+  let refs: Vec<(&[u8; 4], &[u8])> = chunks.iter().map(|c| (b"iTXt", c.as_slice())).collect();
+  gamut::insert_after_ihdr(png, &refs)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_metadata_settings(store: State<'_, SettingsStore>) -> MetadataSettings {
+  store.get().metadata
+}
+
+/// Replaces the stored attribution; blank or missing fields are cleared.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_metadata_settings(
+  store: State<'_, SettingsStore>,
+  author: Option<String>,
+  copyright: Option<String>,
+  license: Option<String>,
+) -> Result<MetadataSettings, String> {
+  let meta = MetadataSettings {
+    author: clean(author),
+    copyright: clean(copyright),
+    license: clean(license),
+  };
+  store.update(|s| s.metadata = meta.clone());
+  store.save()?;
+  Ok(meta)
+}
@@ -0,0 +1,139 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use resvg::usvg;
+use serde::{Deserialize, Serialize};
+use tauri::{
+  ipc::{InvokeBody, Request},
+  AppHandle, Manager,
+};
+use tauri_plugin_share::ShareExt;
+
+use crate::{
+  convert::{render_tree, usvg_options, ConvertRequest},
+  input,
+  preprocess::preprocess_svg,
+};
+
+// Shared files arrive as Android content URIs or sandboxed iOS files, which can't be opened
+// like desktop paths. The frontend reads the shared item and sends its bytes as the raw
+// request body, with the options as JSON in the `x-shared-options` header (anything outside
+// ASCII escaped as \uXXXX, so it stays a valid header value). The PNG lands in the app cache,
+// from where `share_shared_output` hands it to the platform share dialog.
+const SHARED_DIR: &str = "shared";
+const OPTIONS_HEADER: &str = "x-shared-options";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SharedOptions {
+  file_name: String,
+  size_mode: String,
+  scale: Option<f64>,
+  width: Option<u32>,
+  height: Option<u32>,
+  background: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedConvertResult {
+  pub png: String,
+  pub width: u32,
+  pub height: u32,
+}
+
+fn shared_output_name(file_name: &str, w: u32, h: u32) -> String {
+  // Display names from content providers may carry path segments or odd characters.
+  let stem = file_name
+    .rsplit(['/', '\\'])
+    .next()
+    .unwrap_or(file_name)
+    .trim_end_matches(".svg")
+    .trim_end_matches(".SVG");
+  let stem: String = stem
+    .chars()
+    .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect();
+  let stem = if stem.is_empty() { "shared".to_string() } else { stem };
+  format!("{stem}_{w}x{h}.png")
+}
+
+fn shared_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  Ok(app.path().app_cache_dir().map_err(|e| e.to_string())?.join(SHARED_DIR))
+}
+
+#[tauri::command]
+pub async fn convert_shared_svg(app: AppHandle, request: Request<'_>) -> Result<SharedConvertResult, String> {
+  let InvokeBody::Raw(body) = request.body() else {
+    return Err("Send the shared SVG's bytes as the request body.".into());
+  };
+  if body.is_empty() {
+    return Err("No shared SVG data was provided.".into());
+  }
+  let options = request
+    .headers()
+    .get(OPTIONS_HEADER)
+    .ok_or_else(|| format!("Missing the {OPTIONS_HEADER} header."))?
+    .to_str()
+    .map_err(|e| e.to_string())
+    .and_then(|json| serde_json::from_str::<SharedOptions>(json).map_err(|e| e.to_string()))?;
+  let file_name = options.file_name;
+  // Checked before the body is copied, so an oversized share is refused without doubling it.
+  input::check_size(Path::new(&file_name), body.len() as u64)?;
+  let data = input::from_bytes(Path::new(&file_name), body.clone())?;
+  let out_dir = shared_dir(&app)?;
+
+  let req = ConvertRequest {
+    input_mode: "file".into(),
+    input_path: file_name.clone(),
+    size_mode: options.size_mode,
+    scale: options.scale,
+    width: options.width,
+    height: options.height,
+    background: options.background,
+    ..Default::default()
+  };
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let data = preprocess_svg(&data, &req)?;
+    let opt = usvg_options(&req);
+    let tree = usvg::Tree::from_data(&data, &opt).map_err(|e| e.to_string())?;
+    let pixmap = render_tree(&tree, &req)?;
+    let (w, h) = (pixmap.width(), pixmap.height());
+
+    fs::create_dir_all(&out_dir).map_err(|e| e.to_string())?;
+    let out_path = out_dir.join(shared_output_name(&file_name, w, h));
+    let png = pixmap.encode_png().map_err(|e| e.to_string())?;
+    fs::write(&out_path, png).map_err(|e| e.to_string())?;
+
+    Ok(SharedConvertResult {
+      png: out_path.to_string_lossy().to_string(),
+      width: w,
+      height: h,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+/// Opens the platform share dialog for a PNG from `convert_shared_svg`.
+#[tauri::command(rename_all = "camelCase")]
+pub fn share_shared_output(app: AppHandle, png: String) -> Result<(), String> {
+  // Only this app's own conversions are handed out, and only those can be on Android.
+  if Path::new(&png).parent() != Some(shared_dir(&app)?.as_path()) {
+    return Err("Only converted shared files can be shared.".into());
+  }
+  app.share().share_file(&png, "image/png")
+}
+
+/// Removes PNGs left over from previous share-sheet conversions.
+#[tauri::command(rename_all = "camelCase")]
+pub fn clear_shared_outputs(app: AppHandle) -> Result<(), String> {
+  let dir = shared_dir(&app)?;
+  if dir.is_dir() {
+    fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
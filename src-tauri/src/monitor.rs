@@ -0,0 +1,71 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+use crate::convert::BatchMessage;
+
+// Detached progress windows: each one watches a single batch and gets that batch's messages
+// as `batch-monitor` events, whichever way the batch itself reports (window events or a channel).
+const MONITOR_WIDTH: f64 = 420.0;
+const MONITOR_HEIGHT: f64 = 240.0;
+
+/// Batch id -> labels of the progress windows watching it.
+#[derive(Default, Clone)]
+pub struct BatchMonitors(Arc<Mutex<HashMap<String, Vec<String>>>>);
+
+impl BatchMonitors {
+  fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<String>>> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Passes `msg` on to the batch's monitor windows; the summary ends the subscription.
+  pub(crate) fn forward(&self, app: &AppHandle, msg: &BatchMessage) {
+    let mut monitors = self.lock();
+    let batch_id = msg.batch_id();
+    let Some(labels) = monitors.get_mut(batch_id) else {
+      return;
+    };
+    // Windows the user has closed drop out here.
+    labels.retain(|label| app.get_webview_window(label).is_some());
+    for label in labels.iter() {
+      let _ = app.emit_to(label.as_str(), "batch-monitor", msg);
+    }
+    if labels.is_empty() || matches!(msg, BatchMessage::Summary(_)) {
+      monitors.remove(batch_id);
+    }
+  }
+}
+
+/// Opens a small window that follows the batch `batch_id` until it finishes. Returns its label.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn open_progress_window(
+  app: AppHandle,
+  monitors: State<'_, BatchMonitors>,
+  batch_id: String,
+) -> Result<String, String> {
+  let id = uuid::Uuid::parse_str(&batch_id).map_err(|_| "Invalid batch id.".to_string())?;
+  let label = format!("monitor-{}", id.simple());
+  if let Some(win) = app.get_webview_window(&label) {
+    win.set_focus().map_err(|e| e.to_string())?;
+    return Ok(label);
+  }
+  // Subscribe before the page loads so no message falls between the two.
+  monitors.lock().entry(batch_id.clone()).or_default().push(label.clone());
+  let url = WebviewUrl::App(format!("index.html?monitor={batch_id}").into());
+  let built = WebviewWindowBuilder::new(&app, &label, url)
+    .title("Converting…")
+    .inner_size(MONITOR_WIDTH, MONITOR_HEIGHT)
+    .resizable(false)
+    .always_on_top(true)
+    .build();
+  if let Err(err) = built {
+    if let Some(labels) = monitors.lock().get_mut(&batch_id) {
+      labels.retain(|l| *l != label);
+    }
+    return Err(err.to_string());
+  }
+  Ok(label)
+}
@@ -0,0 +1,122 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use resvg::{tiny_skia, usvg};
+use serde::Serialize;
+
+use crate::{
+  convert::{is_svg, parse_bg_color, MAX_PIXELS},
+  icons::render_icon,
+};
+
+const DEFAULT_CELL: u32 = 128;
+const DEFAULT_GAP: u32 = 16;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MontageResult {
+  pub png: String,
+  pub width: u32,
+  pub height: u32,
+  pub placed: u32,
+  // Inputs that couldn't be read or parsed; their cells are left out.
+  pub skipped: Vec<String>,
+}
+
+fn load_tree(path: &Path) -> Result<usvg::Tree, String> {
+  if !path.is_file() || !is_svg(path) {
+    return Err("Invalid SVG file path.".into());
+  }
+  let data = fs::read(path).map_err(|e| e.to_string())?;
+  usvg::Tree::from_data(&data, &usvg::Options::default()).map_err(|e| e.to_string())
+}
+
+/// Lays several SVGs out on one grid (row-major, each fitted into a square cell) and
+/// writes the result as a single PNG.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn generate_montage(
+  svg_paths: Vec<String>,
+  output_path: String,
+  columns: Option<u32>,
+  cell_size: Option<u32>,
+  gap: Option<u32>,
+  background: Option<String>,
+) -> Result<MontageResult, String> {
+  if svg_paths.is_empty() {
+    return Err("No SVG files to compose.".into());
+  }
+  tauri::async_runtime::spawn_blocking(move || {
+    let bg = match background.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      Some(c) => Some(parse_bg_color(c).ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string())?),
+      None => None,
+    };
+    let mut skipped = Vec::new();
+    let trees: Vec<usvg::Tree> = svg_paths
+      .iter()
+      .filter_map(|p| match load_tree(Path::new(p)) {
+        Ok(t) => Some(t),
+        Err(_) => {
+          skipped.push(p.clone());
+          None
+        }
+      })
+      .collect();
+    if trees.is_empty() {
+      return Err("None of the SVG files could be read.".into());
+    }
+
+    let n = trees.len() as u32;
+    let cols = columns
+      .filter(|c| *c > 0)
+      .unwrap_or_else(|| (n as f64).sqrt().ceil() as u32)
+      .min(n);
+    let rows = n.div_ceil(cols);
+    let cell = cell_size.unwrap_or(DEFAULT_CELL).max(1);
+    let gap = gap.unwrap_or(DEFAULT_GAP);
+    // Sized in u64 so huge cell or gap values are refused instead of wrapping; once under
+    // the cap every dimension and cell offset below fits in u32.
+    let span = |k: u32| k as u64 * cell as u64 + (k as u64 + 1) * gap as u64;
+    let (width, height) = (span(cols), span(rows));
+    if width.saturating_mul(height) > MAX_PIXELS {
+      return Err("Montage is too large; use fewer columns or a smaller cell size.".into());
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    let mut canvas = tiny_skia::Pixmap::new(width, height).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+    if let Some(c) = bg {
+      canvas.fill(c);
+    }
+    for (i, tree) in trees.iter().enumerate() {
+      let (col, row) = (i as u32 % cols, i as u32 / cols);
+      let x = gap + col * (cell + gap);
+      let y = gap + row * (cell + gap);
+      let tile = render_icon(tree, cell, cell, 0.0, None)?;
+      canvas.draw_pixmap(
+        x as i32,
+        y as i32,
+        tile.as_ref(),
+        &tiny_skia::PixmapPaint::default(),
+        tiny_skia::Transform::identity(),
+        None,
+      );
+    }
+
+    let out = PathBuf::from(&output_path);
+    if let Some(parent) = out.parent() {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let png = canvas.encode_png().map_err(|e| e.to_string())?;
+    fs::write(&out, png).map_err(|e| e.to_string())?;
+    Ok(MontageResult {
+      png: out.to_string_lossy().to_string(),
+      width,
+      height,
+      placed: n,
+      skipped,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,82 @@
+use resvg::{tiny_skia, usvg};
+
+use crate::trace::decode_jpeg;
+
+// Raster passthrough: SVGs that only wrap one full-canvas PNG or JPEG (common for exported
+// screenshots and design handoffs) are drawn by scaling the decoded image directly. At
+// 1:1 this copies pixels exactly instead of resampling them through resvg. Other embedded
+// formats (GIF, WebP, nested SVG) render through resvg as usual.
+
+const EPSILON: f32 = 0.01;
+
+fn is_plain_group(g: &usvg::Group) -> bool {
+  g.opacity() == usvg::Opacity::ONE && g.clip_path().is_none() && g.mask().is_none() && g.filters().is_empty()
+}
+
+/// The embedded PNG or JPEG, if the whole document is a single image covering the canvas.
+pub(crate) fn full_canvas_raster(tree: &usvg::Tree) -> Option<tiny_skia::Pixmap> {
+  let mut group = tree.root();
+  let image = loop {
+    if !is_plain_group(group) {
+      return None;
+    }
+    let [child] = group.children() else {
+      return None;
+    };
+    match child {
+      usvg::Node::Group(g) => group = g,
+      usvg::Node::Image(img) => break img,
+      _ => return None,
+    }
+  };
+  if !image.is_visible() {
+    return None;
+  }
+  let bbox = image.abs_bounding_box();
+  let size = tree.size();
+  let covers = bbox.x().abs() < EPSILON
+    && bbox.y().abs() < EPSILON
+    && (bbox.width() - size.width()).abs() < EPSILON
+    && (bbox.height() - size.height()).abs() < EPSILON;
+  if !covers {
+    return None;
+  }
+  match image.kind() {
+    usvg::ImageKind::PNG(data) => tiny_skia::Pixmap::decode_png(data).ok(),
+    usvg::ImageKind::JPEG(data) => decode_jpeg(data).ok(),
+    _ => None,
+  }
+}
+
+/// Draws `raster` over the canvas area with the same `transform` the tree would use.
+pub(crate) fn draw(
+  raster: &tiny_skia::Pixmap,
+  tree: &usvg::Tree,
+  transform: usvg::Transform,
+  out_w: u32,
+  out_h: u32,
+  background: Option<tiny_skia::Color>,
+) -> Result<tiny_skia::Pixmap, String> {
+  let mut pixmap = tiny_skia::Pixmap::new(out_w, out_h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  if let Some(c) = background {
+    pixmap.fill(c);
+  }
+  let size = tree.size();
+  let fit = transform.pre_scale(
+    size.width() / raster.width() as f32,
+    size.height() / raster.height() as f32,
+  );
+  // Integer-aligned 1:1 placement copies pixels; anything else gets bicubic scaling.
+  let exact =
+    fit.sx == 1.0 && fit.sy == 1.0 && fit.kx == 0.0 && fit.ky == 0.0 && fit.tx.fract() == 0.0 && fit.ty.fract() == 0.0;
+  let paint = tiny_skia::PixmapPaint {
+    quality: if exact {
+      tiny_skia::FilterQuality::Nearest
+    } else {
+      tiny_skia::FilterQuality::Bicubic
+    },
+    ..Default::default()
+  };
+  pixmap.draw_pixmap(0, 0, raster.as_ref(), &paint, fit, None);
+  Ok(pixmap)
+}
@@ -0,0 +1,269 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str};
+use resvg::tiny_skia;
+
+use crate::report::OutputRecord;
+
+// A4 portrait, in points.
+const PAGE_W: f32 = 595.0;
+const PAGE_H: f32 = 842.0;
+const MARGIN: f32 = 36.0;
+const CAPTION_H: f32 = 40.0;
+const CAPTION_MAX_CHARS: usize = 90;
+
+// Contact sheet grid: cells per page, label height under each cell, and the thumbnail
+// resolution relative to the cell size in points.
+const SHEET_COLUMNS: usize = 4;
+const SHEET_ROWS: usize = 5;
+const SHEET_GAP: f32 = 14.0;
+const LABEL_H: f32 = 24.0;
+const LABEL_MAX_CHARS: usize = 26;
+const THUMB_PX_PER_PT: f32 = 2.0;
+
+/// Collects the batch's PNG outputs into `review.pdf` in `dir`: one image per page,
+/// fitted into the page and captioned with its file name and pixel size.
+pub(crate) fn write_review_pdf(dir: &Path, records: &[OutputRecord]) -> Result<PathBuf, String> {
+  let pngs = png_records(records)?;
+
+  let mut alloc = Ref::new(1);
+  let catalog_id = alloc.bump();
+  let tree_id = alloc.bump();
+  let font_id = alloc.bump();
+  let mut pdf = Pdf::new();
+  let mut page_ids = Vec::with_capacity(pngs.len());
+
+  for r in pngs {
+    let pixmap = tiny_skia::Pixmap::load_png(&r.png).map_err(|e| e.to_string())?;
+    let page_id = alloc.bump();
+    let content_id = alloc.bump();
+    let image_id = alloc.bump();
+    write_image(&mut pdf, &mut alloc, image_id, &pixmap);
+
+    // Fit the image into the area above the caption, centered.
+    let (box_w, box_h) = (PAGE_W - 2.0 * MARGIN, PAGE_H - 2.0 * MARGIN - CAPTION_H);
+    let scale = (box_w / pixmap.width() as f32).min(box_h / pixmap.height() as f32);
+    let (w, h) = (pixmap.width() as f32 * scale, pixmap.height() as f32 * scale);
+    let x = MARGIN + (box_w - w) / 2.0;
+    let y = MARGIN + CAPTION_H + (box_h - h) / 2.0;
+
+    let name = r
+      .png
+      .file_name()
+      .map(|s| s.to_string_lossy().to_string())
+      .unwrap_or_default();
+    let mut content = Content::new();
+    content.save_state();
+    content.transform([w, 0.0, 0.0, h, x, y]);
+    content.x_object(Name(b"Im0"));
+    content.restore_state();
+    content.begin_text();
+    content.set_font(Name(b"F0"), 11.0);
+    content.next_line(MARGIN, MARGIN + 18.0);
+    content.show(Str(caption_text(&name, CAPTION_MAX_CHARS).as_bytes()));
+    content.set_fill_gray(0.45);
+    content.set_font(Name(b"F0"), 9.0);
+    content.next_line(0.0, -14.0);
+    content.show(Str(format!("{} x {} px", r.width, r.height).as_bytes()));
+    content.end_text();
+    pdf.stream(content_id, &content.finish());
+
+    let mut page = pdf.page(page_id);
+    page.media_box(Rect::new(0.0, 0.0, PAGE_W, PAGE_H));
+    page.parent(tree_id);
+    page.contents(content_id);
+    let mut resources = page.resources();
+    resources.x_objects().pair(Name(b"Im0"), image_id);
+    resources.fonts().pair(Name(b"F0"), font_id);
+    resources.finish();
+    page.finish();
+    page_ids.push(page_id);
+  }
+
+  pdf.catalog(catalog_id).pages(tree_id);
+  let count = page_ids.len() as i32;
+  pdf.pages(tree_id).kids(page_ids).count(count);
+  pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let path = dir.join("review.pdf");
+  fs::write(&path, pdf.finish()).map_err(|e| e.to_string())?;
+  Ok(path)
+}
+
+/// Writes `contact-sheet.pdf` into `dir`: the batch's PNG outputs as labelled thumbnails
+/// in a grid, paginated; the printable counterpart of the HTML contact sheet.
+pub(crate) fn write_contact_sheet_pdf(dir: &Path, records: &[OutputRecord]) -> Result<PathBuf, String> {
+  let pngs = png_records(records)?;
+
+  let mut alloc = Ref::new(1);
+  let catalog_id = alloc.bump();
+  let tree_id = alloc.bump();
+  let font_id = alloc.bump();
+  let mut pdf = Pdf::new();
+  let mut page_ids = Vec::new();
+
+  let cell_w = (PAGE_W - 2.0 * MARGIN - (SHEET_COLUMNS - 1) as f32 * SHEET_GAP) / SHEET_COLUMNS as f32;
+  let cell_h = (PAGE_H - 2.0 * MARGIN - (SHEET_ROWS - 1) as f32 * SHEET_GAP) / SHEET_ROWS as f32 - LABEL_H;
+
+  for chunk in pngs.chunks(SHEET_COLUMNS * SHEET_ROWS) {
+    let page_id = alloc.bump();
+    let content_id = alloc.bump();
+    let mut content = Content::new();
+    let mut images = Vec::with_capacity(chunk.len());
+
+    for (i, r) in chunk.iter().enumerate() {
+      let pixmap = tiny_skia::Pixmap::load_png(&r.png).map_err(|e| e.to_string())?;
+      let thumb = thumbnail(&pixmap, cell_w * THUMB_PX_PER_PT, cell_h * THUMB_PX_PER_PT)?;
+      let image_id = alloc.bump();
+      write_image(&mut pdf, &mut alloc, image_id, thumb.as_ref().unwrap_or(&pixmap));
+      let name = format!("Im{i}");
+
+      let (col, row) = ((i % SHEET_COLUMNS) as f32, (i / SHEET_COLUMNS) as f32);
+      let cell_x = MARGIN + col * (cell_w + SHEET_GAP);
+      let label_y = PAGE_H - MARGIN - (row + 1.0) * (cell_h + LABEL_H) - row * SHEET_GAP;
+      let cell_y = label_y + LABEL_H;
+
+      // Fit into the cell without enlarging past the original pixel size.
+      let scale = (cell_w / pixmap.width() as f32)
+        .min(cell_h / pixmap.height() as f32)
+        .min(1.0);
+      let (w, h) = (pixmap.width() as f32 * scale, pixmap.height() as f32 * scale);
+      content.save_state();
+      content.transform([w, 0.0, 0.0, h, cell_x + (cell_w - w) / 2.0, cell_y + (cell_h - h) / 2.0]);
+      content.x_object(Name(name.as_bytes()));
+      content.restore_state();
+
+      let file_name = r
+        .png
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+      content.begin_text();
+      content.set_fill_gray(0.0);
+      content.set_font(Name(b"F0"), 8.0);
+      content.next_line(cell_x, label_y + 13.0);
+      content.show(Str(caption_text(&file_name, LABEL_MAX_CHARS).as_bytes()));
+      content.set_fill_gray(0.45);
+      content.set_font(Name(b"F0"), 7.0);
+      content.next_line(0.0, -10.0);
+      content.show(Str(format!("{} x {} px", r.width, r.height).as_bytes()));
+      content.end_text();
+      images.push((name, image_id));
+    }
+    pdf.stream(content_id, &content.finish());
+
+    let mut page = pdf.page(page_id);
+    page.media_box(Rect::new(0.0, 0.0, PAGE_W, PAGE_H));
+    page.parent(tree_id);
+    page.contents(content_id);
+    let mut resources = page.resources();
+    let mut x_objects = resources.x_objects();
+    for (name, id) in &images {
+      x_objects.pair(Name(name.as_bytes()), *id);
+    }
+    x_objects.finish();
+    resources.fonts().pair(Name(b"F0"), font_id);
+    resources.finish();
+    page.finish();
+    page_ids.push(page_id);
+  }
+
+  pdf.catalog(catalog_id).pages(tree_id);
+  let count = page_ids.len() as i32;
+  pdf.pages(tree_id).kids(page_ids).count(count);
+  pdf.type1_font(font_id).base_font(Name(b"Helvetica"));
+
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let path = dir.join("contact-sheet.pdf");
+  fs::write(&path, pdf.finish()).map_err(|e| e.to_string())?;
+  Ok(path)
+}
+
+fn png_records(records: &[OutputRecord]) -> Result<Vec<&OutputRecord>, String> {
+  let pngs: Vec<&OutputRecord> = records
+    .iter()
+    .filter(|r| r.png.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")))
+    .collect();
+  if pngs.is_empty() {
+    return Err("No PNG outputs to collect into a PDF.".into());
+  }
+  Ok(pngs)
+}
+
+// A downscaled copy when the image is larger than `max_w`×`max_h`, to keep the sheet small.
+fn thumbnail(pixmap: &tiny_skia::Pixmap, max_w: f32, max_h: f32) -> Result<Option<tiny_skia::Pixmap>, String> {
+  let scale = (max_w / pixmap.width() as f32).min(max_h / pixmap.height() as f32);
+  if scale >= 1.0 {
+    return Ok(None);
+  }
+  let w = ((pixmap.width() as f32 * scale).round() as u32).max(1);
+  let h = ((pixmap.height() as f32 * scale).round() as u32).max(1);
+  let mut thumb = tiny_skia::Pixmap::new(w, h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  let paint = tiny_skia::PixmapPaint {
+    quality: tiny_skia::FilterQuality::Bicubic,
+    ..Default::default()
+  };
+  thumb.draw_pixmap(
+    0,
+    0,
+    pixmap.as_ref(),
+    &paint,
+    tiny_skia::Transform::from_scale(w as f32 / pixmap.width() as f32, h as f32 / pixmap.height() as f32),
+    None,
+  );
+  Ok(Some(thumb))
+}
+
+// Writes the pixmap as a deflated RGB image, with a soft mask when it has any transparency.
+fn write_image(pdf: &mut Pdf, alloc: &mut Ref, image_id: Ref, pixmap: &tiny_skia::Pixmap) {
+  let mut rgb = Vec::with_capacity(pixmap.pixels().len() * 3);
+  let mut alpha = Vec::with_capacity(pixmap.pixels().len());
+  for p in pixmap.pixels() {
+    let c = p.demultiply();
+    rgb.extend_from_slice(&[c.red(), c.green(), c.blue()]);
+    alpha.push(c.alpha());
+  }
+  let (w, h) = (pixmap.width() as i32, pixmap.height() as i32);
+
+  let mask_id = alpha.iter().any(|&a| a < 255).then(|| {
+    let mask_id = alloc.bump();
+    let data = miniz_oxide::deflate::compress_to_vec_zlib(&alpha, 6);
+    let mut mask = pdf.image_xobject(mask_id, &data);
+    mask.filter(Filter::FlateDecode);
+    mask.width(w);
+    mask.height(h);
+    mask.color_space().device_gray();
+    mask.bits_per_component(8);
+    mask.finish();
+    mask_id
+  });
+
+  let data = miniz_oxide::deflate::compress_to_vec_zlib(&rgb, 6);
+  let mut image = pdf.image_xobject(image_id, &data);
+  image.filter(Filter::FlateDecode);
+  image.width(w);
+  image.height(h);
+  image.color_space().device_rgb();
+  image.bits_per_component(8);
+  if let Some(mask_id) = mask_id {
+    image.s_mask(mask_id);
+  }
+  image.finish();
+}
+
+// The standard Helvetica font only covers ASCII reliably; long names are shortened.
+fn caption_text(name: &str, max_chars: usize) -> String {
+  let ascii: String = name
+    .chars()
+    .map(|c| if c.is_ascii() && !c.is_ascii_control() { c } else { '?' })
+    .collect();
+  if ascii.len() > max_chars {
+    format!("{}...", &ascii[..max_chars - 3])
+  } else {
+    ascii
+  }
+}
@@ -0,0 +1,346 @@
+use resvg::tiny_skia;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::convert::{parse_bg_color, ConvertRequest, MAX_PIXELS};
+
+// Effects that work on the rendered, still transparent pixmap. They run before the
+// background is flattened in, so shadows and outlines follow the artwork's alpha.
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ShadowOptions {
+  pub offset_x: Option<f32>, // px, default 0
+  pub offset_y: Option<f32>, // px, default 4
+  pub blur: Option<f32>,     // blur radius (sigma) in px, default 4
+  pub color: Option<String>, // "#RRGGBB", default black
+  pub opacity: Option<f32>,  // 0..1, default 0.5
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TileOptions {
+  pub width: u32,            // canvas px at 1x
+  pub height: u32,           // canvas px at 1x
+  pub offset_x: Option<f32>, // shifts the tile grid, px at 1x
+  pub offset_y: Option<f32>,
+  pub mirror: Option<bool>, // flip every other tile so edges always meet seamlessly
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OutlineOptions {
+  pub width: f32,            // px
+  pub color: Option<String>, // "#RRGGBB", default white
+}
+
+/// Whether `req` asks for any pass that needs the transparent render.
+pub(crate) fn active(req: &ConvertRequest) -> bool {
+  req.shadow.is_some() || req.outline.is_some()
+}
+
+pub(crate) fn validate(req: &ConvertRequest) -> Result<(), String> {
+  if let Some(s) = &req.shadow {
+    parse_color(s.color.as_deref(), "shadow")?;
+    if s.blur.is_some_and(|b| !(0.0..=256.0).contains(&b)) {
+      return Err("Shadow blur must be between 0 and 256.".into());
+    }
+    if s.opacity.is_some_and(|o| !(0.0..=1.0).contains(&o)) {
+      return Err("Shadow opacity must be between 0 and 1.".into());
+    }
+  }
+  if let Some(o) = &req.outline {
+    parse_color(o.color.as_deref(), "outline")?;
+    if !(0.0..=256.0).contains(&o.width) {
+      return Err("Outline width must be between 0 and 256.".into());
+    }
+  }
+  if req.corner_radius.is_some_and(|r| !r.is_finite() || r < 0.0) {
+    return Err("Corner radius must be a non-negative number.".into());
+  }
+  if let Some(t) = &req.tile {
+    if t.width == 0 || t.height == 0 {
+      return Err("Tile canvas width/height must be positive.".into());
+    }
+    if t.width as u64 * t.height as u64 > MAX_PIXELS {
+      return Err("Tile canvas is too large.".into());
+    }
+  }
+  if let Some(m) = req.mask.as_deref() {
+    if m != "circle" {
+      return Err(format!("Invalid mask: {m}"));
+    }
+  }
+  Ok(())
+}
+
+fn parse_color(s: Option<&str>, what: &str) -> Result<Option<tiny_skia::Color>, String> {
+  match s.map(str::trim).filter(|s| !s.is_empty()) {
+    Some(c) => parse_bg_color(c)
+      .map(Some)
+      .ok_or_else(|| format!("Invalid {what} color (expected #RRGGBB).")),
+    None => Ok(None),
+  }
+}
+
+fn alpha_plane(pixmap: &tiny_skia::Pixmap) -> Vec<f32> {
+  pixmap.pixels().iter().map(|p| p.alpha() as f32 / 255.0).collect()
+}
+
+// Solid `color` with per-pixel coverage from `alpha`, as a pixmap to draw under the art.
+fn colorize(alpha: &[f32], w: u32, h: u32, color: tiny_skia::Color) -> Option<tiny_skia::Pixmap> {
+  let mut layer = tiny_skia::Pixmap::new(w, h)?;
+  for (dst, a) in layer.pixels_mut().iter_mut().zip(alpha) {
+    let mut c = color;
+    c.apply_opacity(a.clamp(0.0, 1.0));
+    *dst = c.premultiply().to_color_u8();
+  }
+  Some(layer)
+}
+
+// Draws `art` on top of `under`, returning the combined layer.
+fn under(mut under: tiny_skia::Pixmap, art: &tiny_skia::Pixmap) -> tiny_skia::Pixmap {
+  under.draw_pixmap(
+    0,
+    0,
+    art.as_ref(),
+    &tiny_skia::PixmapPaint::default(),
+    tiny_skia::Transform::identity(),
+    None,
+  );
+  under
+}
+
+// Exact 1D squared Euclidean distance transform (Felzenszwalb & Huttenlocher).
+fn edt_1d(f: &[f32], out: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+  let intersect = |q: usize, p: usize| {
+    ((f[q] as f64 + (q * q) as f64) - (f[p] as f64 + (p * p) as f64)) / (2.0 * (q as f64 - p as f64))
+  };
+  let mut k = 0;
+  v[0] = 0;
+  z[0] = f32::NEG_INFINITY;
+  z[1] = f32::INFINITY;
+  for q in 1..f.len() {
+    let mut s = intersect(q, v[k]) as f32;
+    while s <= z[k] {
+      k -= 1;
+      s = intersect(q, v[k]) as f32;
+    }
+    k += 1;
+    v[k] = q;
+    z[k] = s;
+    z[k + 1] = f32::INFINITY;
+  }
+  k = 0;
+  for (q, o) in out.iter_mut().enumerate() {
+    while z[k + 1] < q as f32 {
+      k += 1;
+    }
+    let d = q as f32 - v[k] as f32;
+    *o = d * d + f[v[k]];
+  }
+}
+
+/// Distance in px from each pixel to the nearest mostly-opaque pixel.
+fn distance_to_shape(alpha: &[f32], w: usize, h: usize) -> Vec<f32> {
+  const FAR: f32 = 1e12;
+  let mut grid: Vec<f32> = alpha.iter().map(|a| if *a >= 0.5 { 0.0 } else { FAR }).collect();
+  let n = w.max(h);
+  let (mut f, mut out) = (vec![0.0; n], vec![0.0; n]);
+  let (mut v, mut z) = (vec![0usize; n], vec![0.0f32; n + 1]);
+  for x in 0..w {
+    for y in 0..h {
+      f[y] = grid[y * w + x];
+    }
+    edt_1d(&f[..h], &mut out[..h], &mut v, &mut z);
+    for y in 0..h {
+      grid[y * w + x] = out[y];
+    }
+  }
+  for row in grid.chunks_mut(w) {
+    f[..w].copy_from_slice(row);
+    edt_1d(&f[..w], &mut out[..w], &mut v, &mut z);
+    row.copy_from_slice(&out[..w]);
+  }
+  grid.iter_mut().for_each(|d| *d = d.sqrt());
+  grid
+}
+
+fn apply_outline(pixmap: tiny_skia::Pixmap, opts: &OutlineOptions) -> Result<tiny_skia::Pixmap, String> {
+  if opts.width <= 0.0 {
+    return Ok(pixmap);
+  }
+  let (w, h) = (pixmap.width(), pixmap.height());
+  let alpha = alpha_plane(&pixmap);
+  let dist = distance_to_shape(&alpha, w as usize, h as usize);
+  // Half a pixel of falloff keeps the outer edge anti-aliased.
+  let coverage: Vec<f32> = dist
+    .iter()
+    .zip(&alpha)
+    .map(|(d, a)| (opts.width + 0.5 - d).clamp(0.0, 1.0).max(*a))
+    .collect();
+  let color = parse_color(opts.color.as_deref(), "outline")?.unwrap_or(tiny_skia::Color::WHITE);
+  let layer = colorize(&coverage, w, h, color).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  Ok(under(layer, &pixmap))
+}
+
+// Running-sum box blur along rows of `src` into `dst`, edges clamped to zero.
+fn box_blur_rows(src: &[f32], dst: &mut [f32], w: usize, r: usize) {
+  let norm = 1.0 / (2 * r + 1) as f32;
+  for (s, d) in src.chunks(w).zip(dst.chunks_mut(w)) {
+    let mut acc: f32 = s[..r.min(w)].iter().sum();
+    for x in 0..w {
+      if x + r < w {
+        acc += s[x + r];
+      }
+      if x > r {
+        acc -= s[x - r - 1];
+      }
+      d[x] = acc * norm;
+    }
+  }
+}
+
+fn transpose(src: &[f32], w: usize, h: usize) -> Vec<f32> {
+  let mut out = vec![0.0; src.len()];
+  for y in 0..h {
+    for x in 0..w {
+      out[x * h + y] = src[y * w + x];
+    }
+  }
+  out
+}
+
+/// Approximates a Gaussian with three box passes per axis.
+fn blur(alpha: &[f32], w: usize, h: usize, sigma: f32) -> Vec<f32> {
+  if sigma <= 0.0 {
+    return alpha.to_vec();
+  }
+  let r = ((12.0 * sigma * sigma / 3.0 + 1.0).sqrt() / 2.0).round().max(1.0) as usize;
+  let mut a = alpha.to_vec();
+  let mut tmp = vec![0.0; a.len()];
+  for (width, height) in [(w, h), (h, w)] {
+    for _ in 0..3 {
+      box_blur_rows(&a, &mut tmp, width, r);
+      std::mem::swap(&mut a, &mut tmp);
+    }
+    a = transpose(&a, width, height);
+  }
+  a
+}
+
+fn apply_shadow(pixmap: tiny_skia::Pixmap, opts: &ShadowOptions) -> Result<tiny_skia::Pixmap, String> {
+  let (w, h) = (pixmap.width() as usize, pixmap.height() as usize);
+  let (dx, dy) = (
+    opts.offset_x.unwrap_or(0.0).round() as i64,
+    opts.offset_y.unwrap_or(4.0).round() as i64,
+  );
+  let alpha = alpha_plane(&pixmap);
+  let mut shifted = vec![0.0; alpha.len()];
+  for y in 0..h as i64 {
+    for x in 0..w as i64 {
+      let (sx, sy) = (x - dx, y - dy);
+      if sx >= 0 && sy >= 0 && (sx as usize) < w && (sy as usize) < h {
+        shifted[y as usize * w + x as usize] = alpha[sy as usize * w + sx as usize];
+      }
+    }
+  }
+  let opacity = opts.opacity.unwrap_or(0.5);
+  let shadow: Vec<f32> = blur(&shifted, w, h, opts.blur.unwrap_or(4.0))
+    .into_iter()
+    .map(|a| a * opacity)
+    .collect();
+  let color = parse_color(opts.color.as_deref(), "shadow")?.unwrap_or(tiny_skia::Color::BLACK);
+  let layer =
+    colorize(&shadow, w as u32, h as u32, color).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  Ok(under(layer, &pixmap))
+}
+
+/// Runs the requested effects on a transparent render: outline first, so the shadow
+/// is cast by the outlined shape.
+pub(crate) fn apply(mut pixmap: tiny_skia::Pixmap, req: &ConvertRequest) -> Result<tiny_skia::Pixmap, String> {
+  if let Some(o) = &req.outline {
+    pixmap = apply_outline(pixmap, o)?;
+  }
+  if let Some(s) = &req.shadow {
+    pixmap = apply_shadow(pixmap, s)?;
+  }
+  Ok(pixmap)
+}
+
+/// Composites `pixmap` over a solid background.
+pub(crate) fn flatten(pixmap: tiny_skia::Pixmap, background: tiny_skia::Color) -> Result<tiny_skia::Pixmap, String> {
+  let mut base =
+    tiny_skia::Pixmap::new(pixmap.width(), pixmap.height()).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  base.fill(background);
+  Ok(under(base, &pixmap))
+}
+
+// Cubic control-point distance for approximating a quarter circle.
+const KAPPA: f32 = 0.552_284_8;
+
+fn rounded_rect(w: f32, h: f32, r: f32) -> Option<tiny_skia::Path> {
+  let r = r.min(w / 2.0).min(h / 2.0);
+  let k = r * KAPPA;
+  let mut pb = tiny_skia::PathBuilder::new();
+  pb.move_to(r, 0.0);
+  pb.line_to(w - r, 0.0);
+  pb.cubic_to(w - r + k, 0.0, w, r - k, w, r);
+  pb.line_to(w, h - r);
+  pb.cubic_to(w, h - r + k, w - r + k, h, w - r, h);
+  pb.line_to(r, h);
+  pb.cubic_to(r - k, h, 0.0, h - r + k, 0.0, h - r);
+  pb.line_to(0.0, r);
+  pb.cubic_to(0.0, r - k, r - k, 0.0, r, 0.0);
+  pb.close();
+  pb.finish()
+}
+
+/// Clips the final image (background included) to the requested shape. A circle mask
+/// takes precedence over rounded corners.
+pub(crate) fn apply_mask(mut pixmap: tiny_skia::Pixmap, req: &ConvertRequest) -> Result<tiny_skia::Pixmap, String> {
+  let (w, h) = (pixmap.width() as f32, pixmap.height() as f32);
+  let path = if req.mask.as_deref() == Some("circle") {
+    tiny_skia::PathBuilder::from_circle(w / 2.0, h / 2.0, w.min(h) / 2.0)
+  } else if let Some(radius) = req.corner_radius.filter(|r| *r > 0.0) {
+    rounded_rect(w, h, radius)
+  } else {
+    return Ok(pixmap);
+  };
+  let path = path.ok_or_else(|| "Failed to build mask.".to_string())?;
+  let mut mask =
+    tiny_skia::Mask::new(pixmap.width(), pixmap.height()).ok_or_else(|| "Failed to allocate mask.".to_string())?;
+  mask.fill_path(&path, tiny_skia::FillRule::Winding, true, tiny_skia::Transform::identity());
+  pixmap.apply_mask(&mask);
+  Ok(pixmap)
+}
+
+/// Repeats the rendered image across the tile canvas. `scale` is the output's density
+/// factor, so @2x variants get a canvas twice the size.
+pub(crate) fn tile(pixmap: &tiny_skia::Pixmap, opts: &TileOptions, scale: f32) -> Result<tiny_skia::Pixmap, String> {
+  let w = ((opts.width as f32 * scale).round() as u32).max(1);
+  let h = ((opts.height as f32 * scale).round() as u32).max(1);
+  if w as u64 * h as u64 > MAX_PIXELS {
+    return Err("Tile canvas is too large.".into());
+  }
+  let mut canvas = tiny_skia::Pixmap::new(w, h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  let spread = if opts.mirror.unwrap_or(false) {
+    tiny_skia::SpreadMode::Reflect
+  } else {
+    tiny_skia::SpreadMode::Repeat
+  };
+  let offset = tiny_skia::Transform::from_translate(
+    opts.offset_x.unwrap_or(0.0) * scale,
+    opts.offset_y.unwrap_or(0.0) * scale,
+  );
+  let paint = tiny_skia::Paint {
+    shader: tiny_skia::Pattern::new(pixmap.as_ref(), spread, tiny_skia::FilterQuality::Nearest, 1.0, offset),
+    ..Default::default()
+  };
+  let rect = tiny_skia::Rect::from_xywh(0.0, 0.0, w as f32, h as f32).ok_or_else(|| "Invalid tile canvas.".to_string())?;
+  canvas.fill_rect(rect, &paint, tiny_skia::Transform::identity(), None);
+  Ok(canvas)
+}
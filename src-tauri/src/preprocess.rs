@@ -0,0 +1,218 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{
+  animate,
+  convert::{parse_shape_rendering, parse_text_rendering, ConvertRequest},
+};
+
+/// Font weight and width applied to all text, as `font-weight` and `font-stretch`. They
+/// steer font matching to the closest installed face; the renderer doesn't set variable-font
+/// coordinates, so a variable font is drawn at its default instance.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FontAxes {
+  pub weight: Option<f32>, // 1-1000
+  pub width: Option<f32>,  // percent (100 = normal), matched to the nearest font-stretch keyword
+}
+
+// font-stretch keywords by the width percentage CSS assigns them.
+const STRETCHES: [(f32, &str); 9] = [
+  (50.0, "ultra-condensed"),
+  (62.5, "extra-condensed"),
+  (75.0, "condensed"),
+  (87.5, "semi-condensed"),
+  (100.0, "normal"),
+  (112.5, "semi-expanded"),
+  (125.0, "expanded"),
+  (150.0, "extra-expanded"),
+  (200.0, "ultra-expanded"),
+];
+
+/// Source-level rewrites applied before usvg parses the SVG. Returns the input untouched
+/// when no option needs them.
+pub(crate) fn preprocess_svg<'a>(data: &'a [u8], req: &ConvertRequest) -> Result<Cow<'a, [u8]>, String> {
+  let mut text: Option<String> = None;
+  if let Some(time) = req.time {
+    let src = String::from_utf8_lossy(data);
+    if src.contains("<anim") || src.contains("<set") {
+      text = Some(animate::evaluate_at(&src, time)?);
+    }
+  }
+  if let Some(scheme) = req.color_scheme.as_deref() {
+    validate_color_scheme(scheme)?;
+    let src = text.take().unwrap_or_else(|| String::from_utf8_lossy(data).into_owned());
+    text = Some(if src.contains("prefers-color-scheme") {
+      apply_color_scheme(&src, scheme)
+    } else {
+      src
+    });
+  }
+  if let Some(axes) = req.font_axes {
+    let css = font_axes_css(&axes)?;
+    if !css.is_empty() {
+      let src = text.take().unwrap_or_else(|| String::from_utf8_lossy(data).into_owned());
+      text = Some(inject_style(&src, &css));
+    }
+  }
+  if req.force_rendering_hints.unwrap_or(false) {
+    let css = rendering_hints_css(req);
+    if !css.is_empty() {
+      let src = text.take().unwrap_or_else(|| String::from_utf8_lossy(data).into_owned());
+      text = Some(inject_style(&src, &css));
+    }
+  }
+  Ok(match text {
+    Some(t) => Cow::Owned(t.into_bytes()),
+    None => Cow::Borrowed(data),
+  })
+}
+
+fn font_axes_css(axes: &FontAxes) -> Result<String, String> {
+  let mut decls = Vec::new();
+  if let Some(w) = axes.weight {
+    if !(1.0..=1000.0).contains(&w) {
+      return Err("Font weight must be between 1 and 1000.".into());
+    }
+    decls.push(format!("font-weight:{}", w.round()));
+  }
+  if let Some(w) = axes.width {
+    if !(50.0..=200.0).contains(&w) {
+      return Err("Font width must be between 50% and 200%.".into());
+    }
+    let (_, keyword) = STRETCHES
+      .iter()
+      .min_by(|a, b| (a.0 - w).abs().total_cmp(&(b.0 - w).abs()))
+      .copied()
+      .unwrap_or((100.0, "normal"));
+    decls.push(format!("font-stretch:{keyword}"));
+  }
+  if decls.is_empty() {
+    return Ok(String::new());
+  }
+  Ok(format!("text,tspan,textPath{{{}}}", decls.join(";")))
+}
+
+/// Byte range of the root `<svg ...>` start tag, skipping `>` inside quoted values.
+pub(crate) fn root_tag(text: &str) -> Option<(usize, usize)> {
+  let start = text.find("<svg")?;
+  let mut quote: Option<char> = None;
+  for (i, c) in text[start..].char_indices() {
+    match (quote, c) {
+      (Some(q), c) if c == q => quote = None,
+      (Some(_), _) => {}
+      (None, '"' | '\'') => quote = Some(c),
+      (None, '>') => return Some((start, start + i + 1)),
+      _ => {}
+    }
+  }
+  None
+}
+
+fn root_tag_end(text: &str) -> Option<usize> {
+  root_tag(text).map(|(_, end)| end)
+}
+
+/// Adds a `<style>` element as the first child of the root element. CSS rules outrank
+/// presentation attributes, so the injected values win unless an inline style is set.
+fn inject_style(text: &str, css: &str) -> String {
+  let Some(end) = root_tag_end(text) else {
+    return text.to_string();
+  };
+  // A self-closing root has no content to style.
+  if text[..end].ends_with("/>") {
+    return text.to_string();
+  }
+  format!("{}<style>{css}</style>{}", &text[..end], &text[end..])
+}
+
+/// Checks preprocessing options up front so a bad value fails the batch, not every item.
+pub(crate) fn validate_options(req: &ConvertRequest) -> Result<(), String> {
+  if let Some(scheme) = req.color_scheme.as_deref() {
+    validate_color_scheme(scheme)?;
+  }
+  if let Some(axes) = &req.font_axes {
+    font_axes_css(axes)?;
+  }
+  if req.time.is_some_and(|t| !t.is_finite() || t < 0.0) {
+    return Err("Time must be a non-negative number of seconds.".into());
+  }
+  if let Some(v) = req.shape_rendering.as_deref() {
+    parse_shape_rendering(v)?;
+  }
+  if let Some(v) = req.text_rendering.as_deref() {
+    parse_text_rendering(v)?;
+  }
+  Ok(())
+}
+
+// Without forcing, the hints only fill in for elements that don't set their own; icon
+// sets often ship with geometricPrecision baked in, which blurs 16px exports.
+fn rendering_hints_css(req: &ConvertRequest) -> String {
+  let mut css = String::new();
+  if let Some(v) = req.shape_rendering.as_deref() {
+    css.push_str(&format!("*{{shape-rendering:{v}}}"));
+  }
+  if let Some(v) = req.text_rendering.as_deref() {
+    css.push_str(&format!("text,tspan,textPath{{text-rendering:{v}}}"));
+  }
+  css
+}
+
+fn validate_color_scheme(scheme: &str) -> Result<(), String> {
+  match scheme {
+    "light" | "dark" => Ok(()),
+    _ => Err("Invalid color scheme (expected light or dark).".into()),
+  }
+}
+
+// Index just past the brace that closes the block opened at `open`.
+fn matching_brace(text: &str, open: usize) -> Option<usize> {
+  let mut depth = 0usize;
+  for (i, c) in text[open..].char_indices() {
+    match c {
+      '{' => depth += 1,
+      '}' => {
+        depth -= 1;
+        if depth == 0 {
+          return Some(open + i + 1);
+        }
+      }
+      _ => {}
+    }
+  }
+  None
+}
+
+/// Emulates `prefers-color-scheme`: rules inside matching `@media` blocks are unwrapped
+/// so they always apply, and blocks for the other scheme are dropped. usvg ignores
+/// `@media` entirely, so without this the dark variant could never be rendered.
+fn apply_color_scheme(text: &str, scheme: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+  while let Some(pos) = rest.find("@media") {
+    let Some(open_rel) = rest[pos..].find('{') else {
+      break;
+    };
+    let open = pos + open_rel;
+    let condition = &rest[pos + "@media".len()..open];
+    if !condition.contains("prefers-color-scheme") {
+      out.push_str(&rest[..open + 1]);
+      rest = &rest[open + 1..];
+      continue;
+    }
+    let Some(close) = matching_brace(rest, open) else {
+      break;
+    };
+    out.push_str(&rest[..pos]);
+    if condition.contains(scheme) {
+      out.push_str(&rest[open + 1..close - 1]);
+    }
+    rest = &rest[close..];
+  }
+  out.push_str(rest);
+  out
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::convert::ConvertRequest;
+
+// Named output sizes for common social, store and web targets, so a request can say
+// `sizePreset: "og-image"` instead of 1200×630. Sizes follow each platform's current
+// recommended upload size.
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizePreset {
+  pub id: &'static str,
+  pub name: &'static str,
+  pub category: &'static str, // "social" | "video" | "app" | "web"
+  pub width: u32,
+  pub height: u32,
+}
+
+const fn preset(id: &'static str, name: &'static str, category: &'static str, width: u32, height: u32) -> SizePreset {
+  SizePreset {
+    id,
+    name,
+    category,
+    width,
+    height,
+  }
+}
+
+const PRESETS: &[SizePreset] = &[
+  preset("og-image", "Open Graph image", "social", 1200, 630),
+  preset("twitter-card", "X/Twitter large summary card", "social", 1200, 628),
+  preset("twitter-header", "X/Twitter header", "social", 1500, 500),
+  preset("facebook-cover", "Facebook page cover", "social", 851, 315),
+  preset("instagram-square", "Instagram square post", "social", 1080, 1080),
+  preset("instagram-portrait", "Instagram portrait post", "social", 1080, 1350),
+  preset("instagram-story", "Instagram/TikTok story", "social", 1080, 1920),
+  preset("linkedin-banner", "LinkedIn profile banner", "social", 1584, 396),
+  preset("pinterest-pin", "Pinterest pin", "social", 1000, 1500),
+  preset("youtube-thumbnail", "YouTube thumbnail", "video", 1280, 720),
+  preset("youtube-banner", "YouTube channel banner", "video", 2560, 1440),
+  preset("app-store-icon", "App Store icon", "app", 1024, 1024),
+  preset(
+    "app-store-iphone-6.9",
+    "App Store screenshot, iPhone 6.9\"",
+    "app",
+    1320,
+    2868,
+  ),
+  preset(
+    "app-store-iphone-6.5",
+    "App Store screenshot, iPhone 6.5\"",
+    "app",
+    1242,
+    2688,
+  ),
+  preset(
+    "app-store-ipad-13",
+    "App Store screenshot, iPad 13\"",
+    "app",
+    2064,
+    2752,
+  ),
+  preset("play-store-icon", "Google Play icon", "app", 512, 512),
+  preset("play-store-feature", "Google Play feature graphic", "app", 1024, 500),
+  preset("play-store-phone", "Google Play phone screenshot", "app", 1080, 1920),
+  preset("apple-touch-icon", "Apple touch icon", "web", 180, 180),
+];
+
+pub(crate) fn find(id: &str) -> Option<&'static SizePreset> {
+  PRESETS.iter().find(|p| p.id == id)
+}
+
+/// Swaps a named preset for the exact width/height it stands for, so the rest of the
+/// pipeline only ever sees explicit sizes.
+pub(crate) fn resolve(req: &mut ConvertRequest) -> Result<(), String> {
+  let Some(id) = req.size_preset.as_deref().map(str::trim).filter(|s| !s.is_empty()) else {
+    return Ok(());
+  };
+  let p = find(id).ok_or_else(|| format!("Unknown size preset: {id}"))?;
+  req.size_mode = "exact".into();
+  req.width = Some(p.width);
+  req.height = Some(p.height);
+  Ok(())
+}
+
+// Size conventions in file names: `banner_1200x630.svg` (or `-1200x630`) renders at exactly
+// 1200×630 and `icon@2x.svg` at twice its intrinsic size. An explicit size wins over a density.
+fn file_name_size(svg: &Path) -> Option<FileNameSize> {
+  let stem = svg.file_stem()?.to_str()?;
+  let tail = stem.rsplit(['_', '-']).next().filter(|t| t.len() < stem.len());
+  if let Some((w, h)) = tail.and_then(|t| t.split_once(['x', 'X'])) {
+    if let (Ok(w), Ok(h)) = (w.parse::<u32>(), h.parse::<u32>()) {
+      if w > 0 && h > 0 {
+        return Some(FileNameSize::Exact(w, h));
+      }
+    }
+  }
+  let (_, density) = stem.rsplit_once('@')?;
+  let scale: f64 = density.strip_suffix(['x', 'X'])?.parse().ok()?;
+  (scale.is_finite() && scale > 0.0).then_some(FileNameSize::Scale(scale))
+}
+
+enum FileNameSize {
+  Exact(u32, u32),
+  Scale(f64),
+}
+
+/// With `sizeFromFileName` on, replaces the request's size with the one `svg`'s name encodes.
+pub(crate) fn apply_file_name(svg: &Path, req: &mut ConvertRequest) {
+  if !req.size_from_file_name.unwrap_or(false) {
+    return;
+  }
+  match file_name_size(svg) {
+    Some(FileNameSize::Exact(w, h)) => {
+      req.size_mode = "exact".into();
+      req.width = Some(w);
+      req.height = Some(h);
+    }
+    Some(FileNameSize::Scale(s)) => {
+      req.size_mode = "scale".into();
+      req.scale = Some(s);
+    }
+    None => {}
+  }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_size_presets() -> Vec<SizePreset> {
+  PRESETS.to_vec()
+}
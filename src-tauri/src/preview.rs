@@ -0,0 +1,209 @@
+use std::{
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use resvg::usvg;
+use serde::Serialize;
+use tauri::{
+  ipc::{Channel, InvokeResponseBody},
+  State,
+};
+
+use crate::{
+  convert::{
+    collect_svgs, compute_output_size, is_svg, item_request, render_tree, render_tree_at, tree_size, usvg_options,
+    validate_request, ConvertRequest, SvgSize,
+  },
+  input,
+  preprocess::preprocess_svg,
+  presets,
+  rules::RuleSet,
+};
+
+const DEFAULT_THUMBNAIL_SIZE: u32 = 160;
+const MAX_THUMBNAILS: u32 = 64;
+
+// Live preview keeps the parsed tree around between slider moves and pushes frames
+// over an IPC channel as raw bytes: an 8-byte header (width, height as little-endian
+// u32) followed by straight-alpha RGBA rows, ready for `new ImageData(...)`.
+
+struct PreviewSession {
+  tree: Arc<usvg::Tree>,
+  channel: Channel,
+}
+
+#[derive(Default)]
+pub struct PreviewState {
+  session: Mutex<Option<PreviewSession>>,
+  // Bumped on every update so stale renders from a fast-moving slider are dropped.
+  generation: Arc<AtomicU64>,
+}
+
+impl PreviewState {
+  fn lock(&self) -> std::sync::MutexGuard<'_, Option<PreviewSession>> {
+    self.session.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+fn encode_frame(pixmap: &resvg::tiny_skia::Pixmap) -> Vec<u8> {
+  let mut frame = Vec::with_capacity(8 + pixmap.data().len());
+  frame.extend_from_slice(&pixmap.width().to_le_bytes());
+  frame.extend_from_slice(&pixmap.height().to_le_bytes());
+  for px in pixmap.pixels() {
+    let c = px.demultiply();
+    frame.extend_from_slice(&[c.red(), c.green(), c.blue(), c.alpha()]);
+  }
+  frame
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn open_preview(state: State<'_, PreviewState>, path: String, channel: Channel) -> Result<SvgSize, String> {
+  let svg_path = PathBuf::from(&path);
+  if !svg_path.is_file() || !is_svg(&svg_path) {
+    return Err("Invalid SVG file path.".into());
+  }
+  let tree = tauri::async_runtime::spawn_blocking(move || {
+    let data = input::read_source(&svg_path)?;
+    let req = ConvertRequest::default();
+    let data = preprocess_svg(&data, &req)?;
+    usvg::Tree::from_data(&data, &usvg_options(&req)).map_err(|e| e.to_string())
+  })
+  .await
+  .map_err(|e| e.to_string())??;
+
+  let size = tree_size(&tree);
+  state.generation.fetch_add(1, Ordering::SeqCst);
+  *state.lock() = Some(PreviewSession {
+    tree: Arc::new(tree),
+    channel,
+  });
+  Ok(size)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn update_preview(
+  state: State<'_, PreviewState>,
+  size_mode: String,
+  scale: Option<f64>,
+  width: Option<u32>,
+  height: Option<u32>,
+  background: Option<String>,
+) -> Result<(), String> {
+  let (tree, channel) = {
+    let session = state.lock();
+    let session = session.as_ref().ok_or_else(|| "No preview is open.".to_string())?;
+    (session.tree.clone(), session.channel.clone())
+  };
+  let generation = state.generation.clone();
+  let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+  let req = ConvertRequest {
+    size_mode,
+    scale,
+    width,
+    height,
+    background,
+    ..Default::default()
+  };
+
+  tauri::async_runtime::spawn_blocking(move || {
+    if generation.load(Ordering::SeqCst) != current {
+      return Ok(());
+    }
+    let pixmap = render_tree(&tree, &req)?;
+    if generation.load(Ordering::SeqCst) != current {
+      return Ok(());
+    }
+    channel
+      .send(InvokeResponseBody::Raw(encode_frame(&pixmap)))
+      .map_err(|e| e.to_string())
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn close_preview(state: State<'_, PreviewState>) {
+  state.generation.fetch_add(1, Ordering::SeqCst);
+  *state.lock() = None;
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchThumbnail {
+  pub svg: String,
+  pub png: Option<String>, // data: URL, usable directly as an <img> src
+  // Size the real conversion would produce, not the thumbnail's.
+  pub out_width: Option<u32>,
+  pub out_height: Option<u32>,
+  pub error: Option<String>,
+}
+
+// The thumbnail as a PNG data: URL, plus the size the real conversion would produce.
+fn render_thumbnail(svg: &Path, req: &ConvertRequest, max_edge: u32) -> Result<(String, u32, u32), String> {
+  let data = input::read_source(svg)?;
+  let data = preprocess_svg(&data, req)?;
+  let tree = usvg::Tree::from_data(&data, &usvg_options(req)).map_err(|e| e.to_string())?;
+  let (w, h) = compute_output_size(req, &tree_size(&tree))?;
+  let fit = (max_edge as f64 / w.max(h) as f64).min(1.0);
+  let tw = ((w as f64 * fit).round() as u32).max(1);
+  let th = ((h as f64 * fit).round() as u32).max(1);
+  let pixmap = render_tree_at(&tree, req, tw, th)?;
+  let png = pixmap.encode_png().map_err(|e| e.to_string())?;
+  Ok((format!("data:image/png;base64,{}", STANDARD.encode(png)), w, h))
+}
+
+/// Renders the first `count` files of a planned batch as small thumbnails with the batch
+/// settings applied, so settings can be checked on a sample before converting everything.
+/// Thumbnails come back inline, since the webview can't load files from the app's cache.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn preview_batch(
+  mut req: ConvertRequest,
+  count: Option<u32>,
+  thumbnail_size: Option<u32>,
+) -> Result<Vec<BatchThumbnail>, String> {
+  // Settled as `convert_batch` does, so a request the batch would refuse fails here too.
+  presets::resolve(&mut req)?;
+  validate_request(&mut req)?;
+  let mut rules = RuleSet::build(&req)?;
+  for rule_req in rules.requests_mut() {
+    presets::resolve(rule_req)?;
+    validate_request(rule_req)?;
+  }
+  let count = count.unwrap_or(12).clamp(1, MAX_THUMBNAILS) as usize;
+  let max_edge = thumbnail_size.unwrap_or(DEFAULT_THUMBNAIL_SIZE).max(1);
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let svgs = collect_svgs(&req)?;
+    Ok(
+      svgs
+        .iter()
+        .take(count)
+        .map(|svg| {
+          let mut thumb = BatchThumbnail {
+            svg: svg.to_string_lossy().to_string(),
+            png: None,
+            out_width: None,
+            out_height: None,
+            error: None,
+          };
+          match render_thumbnail(svg, &item_request(&rules, svg, &req), max_edge) {
+            Ok((png, w, h)) => {
+              thumb.png = Some(png);
+              thumb.out_width = Some(w);
+              thumb.out_height = Some(h);
+            }
+            Err(e) => thumb.error = Some(e),
+          }
+          thumb
+        })
+        .collect(),
+    )
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,163 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use resvg::usvg;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::convert::{is_svg, render_tree, tree_size, usvg_options, ConvertRequest};
+
+const PRINT_DIR: &str = "print";
+const DEFAULT_DPI: u32 = 300;
+const MM_PER_INCH: f64 = 25.4;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintJob {
+  pub width: u32,
+  pub height: u32,
+  pub dpi: u32,
+  pub width_mm: f64,
+  pub height_mm: f64,
+  pub job: Option<String>, // spooler job id, where the platform reports one
+}
+
+/// Renders `svg_path` at a physical size (millimetres, height following the aspect ratio
+/// when omitted) and resolution, then hands it to the system print pipeline: CUPS `lp`
+/// on macOS/Linux, System.Drawing printing on Windows.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn print_render(
+  app: AppHandle,
+  svg_path: String,
+  width_mm: f64,
+  height_mm: Option<f64>,
+  dpi: Option<u32>,
+  background: Option<String>,
+  printer: Option<String>,
+  copies: Option<u32>,
+) -> Result<PrintJob, String> {
+  if !(width_mm.is_finite() && width_mm > 0.0) || height_mm.is_some_and(|h| !(h.is_finite() && h > 0.0)) {
+    return Err("Print size must be positive.".into());
+  }
+  let dpi = dpi.unwrap_or(DEFAULT_DPI).clamp(72, 2400);
+  let copies = copies.unwrap_or(1).clamp(1, 999);
+  let dir = app.path().app_cache_dir().map_err(|e| e.to_string())?.join(PRINT_DIR);
+
+  tauri::async_runtime::spawn_blocking(move || {
+    let svg = PathBuf::from(&svg_path);
+    if !svg.is_file() || !is_svg(&svg) {
+      return Err("Invalid SVG file path.".into());
+    }
+    let data = fs::read(&svg).map_err(|e| e.to_string())?;
+    let mut req = ConvertRequest {
+      size_mode: "exact".into(),
+      background,
+      ..Default::default()
+    };
+    let tree = usvg::Tree::from_data(&data, &usvg_options(&req)).map_err(|e| e.to_string())?;
+    let src = tree_size(&tree);
+    let height_mm = height_mm.unwrap_or(width_mm * src.height as f64 / src.width as f64);
+    let px = |mm: f64| ((mm / MM_PER_INCH * dpi as f64).round() as u32).max(1);
+    let (width, height) = (px(width_mm), px(height_mm));
+    req.width = Some(width);
+    req.height = Some(height);
+    let png = render_tree(&tree, &req)?.encode_png().map_err(|e| e.to_string())?;
+
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("{}.png", uuid::Uuid::new_v4().simple()));
+    fs::write(&path, png).map_err(|e| e.to_string())?;
+    // Both spoolers have taken a copy of the file by the time the command returns.
+    let job = send_to_printer(&path, width_mm, height_mm, dpi, printer.as_deref(), copies);
+    let _ = fs::remove_file(&path);
+
+    Ok(PrintJob {
+      width,
+      height,
+      dpi,
+      width_mm,
+      height_mm,
+      job: job?,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
+
+#[cfg(not(windows))]
+fn send_to_printer(
+  path: &std::path::Path,
+  _width_mm: f64,
+  _height_mm: f64,
+  dpi: u32,
+  printer: Option<&str>,
+  copies: u32,
+) -> Result<Option<String>, String> {
+  let mut cmd = Command::new("lp");
+  if let Some(p) = printer.filter(|p| !p.trim().is_empty()) {
+    cmd.arg("-d").arg(p);
+  }
+  // `ppi` makes CUPS print the image at its physical size instead of fitting the page.
+  cmd
+    .arg("-n")
+    .arg(copies.to_string())
+    .arg("-o")
+    .arg(format!("ppi={dpi}"))
+    .arg("--")
+    .arg(path);
+  let out = cmd.output().map_err(|e| format!("Failed to run lp: {e}"))?;
+  if !out.status.success() {
+    return Err(format!(
+      "Printing failed: {}",
+      String::from_utf8_lossy(&out.stderr).trim()
+    ));
+  }
+  // "request id is Printer-42 (1 file(s))"
+  let stdout = String::from_utf8_lossy(&out.stdout);
+  Ok(
+    stdout
+      .split_whitespace()
+      .skip_while(|w| *w != "is")
+      .nth(1)
+      .map(str::to_string),
+  )
+}
+
+#[cfg(windows)]
+fn send_to_printer(
+  path: &std::path::Path,
+  width_mm: f64,
+  height_mm: f64,
+  _dpi: u32,
+  printer: Option<&str>,
+  copies: u32,
+) -> Result<Option<String>, String> {
+  // Values go in through the environment so nothing user-supplied is spliced into the script.
+  const SCRIPT: &str = r#"
+Add-Type -AssemblyName System.Drawing
+$img = [System.Drawing.Image]::FromFile($env:PRINT_FILE)
+$doc = New-Object System.Drawing.Printing.PrintDocument
+if ($env:PRINT_PRINTER) { $doc.PrinterSettings.PrinterName = $env:PRINT_PRINTER }
+$doc.PrinterSettings.Copies = [int]$env:PRINT_COPIES
+$doc.add_PrintPage({ param($s, $e)
+  $e.Graphics.PageUnit = [System.Drawing.GraphicsUnit]::Millimeter
+  $e.Graphics.DrawImage($img, 0, 0, [single]$env:PRINT_WIDTH_MM, [single]$env:PRINT_HEIGHT_MM)
+})
+$doc.Print()
+$img.Dispose()
+"#;
+  let out = Command::new("powershell")
+    .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+    .env("PRINT_FILE", path)
+    .env("PRINT_PRINTER", printer.unwrap_or_default())
+    .env("PRINT_COPIES", copies.to_string())
+    .env("PRINT_WIDTH_MM", width_mm.to_string())
+    .env("PRINT_HEIGHT_MM", height_mm.to_string())
+    .output()
+    .map_err(|e| format!("Failed to run PowerShell: {e}"))?;
+  if !out.status.success() {
+    return Err(format!(
+      "Printing failed: {}",
+      String::from_utf8_lossy(&out.stderr).trim()
+    ));
+  }
+  Ok(None)
+}
@@ -0,0 +1,88 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::{batches::ActiveBatches, convert::ConvertRequest};
+
+// Mirrors batch progress into a JSON file for tools that can't listen to Tauri events
+// (shell scripts, status bars, CI wrappers). Each update goes through a temp file and a
+// rename, so readers never see a half-written file.
+const PROGRESS_FILE: &str = "progress.json";
+
+pub(crate) struct ProgressFile {
+  path: PathBuf,
+}
+
+fn default_path<R: Runtime>(app: &impl Manager<R>) -> Result<PathBuf, String> {
+  Ok(
+    app
+      .path()
+      .app_cache_dir()
+      .map_err(|e| e.to_string())?
+      .join(PROGRESS_FILE),
+  )
+}
+
+impl ProgressFile {
+  /// The progress file the request asks for: `progressPath` if set, else the app cache one.
+  /// While another running batch writes the shared default file, this one gets
+  /// `progress-<batch id>.json` beside it; two batches can't share an explicit path.
+  pub(crate) fn for_request<R: Runtime>(
+    app: &impl Manager<R>,
+    req: &ConvertRequest,
+    batch_id: &str,
+  ) -> Result<Option<Self>, String> {
+    if !req.progress_file.unwrap_or(false) {
+      return Ok(None);
+    }
+    let active = app.try_state::<ActiveBatches>();
+    let claim = |path: &Path| match &active {
+      Some(a) => a.claim_progress_path(batch_id, path),
+      None => true,
+    };
+    let path = match req.progress_path.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+      Some(p) => {
+        let path = PathBuf::from(p);
+        if !claim(&path) {
+          return Err("Another running batch is already writing that progress file.".into());
+        }
+        path
+      }
+      None => {
+        let path = default_path(app)?;
+        if claim(&path) {
+          path
+        } else {
+          let own = path.with_file_name(format!("progress-{batch_id}.json"));
+          claim(&own);
+          own
+        }
+      }
+    };
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    Ok(Some(Self { path }))
+  }
+
+  pub(crate) fn write(&self, progress: &impl Serialize) {
+    let tmp = self.path.with_extension("json.tmp");
+    let res = serde_json::to_vec_pretty(progress)
+      .map_err(|e| e.to_string())
+      .and_then(|data| fs::write(&tmp, data).map_err(|e| e.to_string()))
+      .and_then(|()| fs::rename(&tmp, &self.path).map_err(|e| e.to_string()));
+    if let Err(err) = res {
+      log::warn!("failed to write progress file: {err}");
+    }
+  }
+}
+
+/// Where batches write their progress file when no `progressPath` is given.
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_progress_file_path(app: AppHandle) -> Result<String, String> {
+  Ok(default_path(&app)?.to_string_lossy().to_string())
+}
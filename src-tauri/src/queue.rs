@@ -0,0 +1,283 @@
+use std::{
+  sync::{Arc, Condvar, Mutex},
+  time::Duration,
+};
+
+use serde::Serialize;
+use tauri::State;
+#[cfg(desktop)]
+use thread_priority::{set_current_thread_priority, ThreadPriority};
+
+// Running batches share one render slot per core. Jobs are kept in queue order (higher
+// priority first, then oldest first) and each free slot goes to the first job with an item
+// waiting, so once slots run short a small urgent batch still gets ahead of a 10k-file
+// background one. `bump_job` and `reorder_job` change the order by hand.
+//
+// Low-power jobs are for long runs in the background: between them they render one item at a
+// time, on a lowest-priority thread, with a pause after each file. `set_low_power` switches a
+// running job either way; the next item picks it up.
+
+// The pause after a low-power item matches its render time (so the job uses at most about
+// half a core), up to this long.
+const MAX_PACING: Duration = Duration::from_secs(2);
+
+struct Job {
+  batch_id: String,
+  priority: i32,
+  waiting: bool, // has an item blocked in `acquire`
+  running: u32,  // slots held
+  low_power: bool,
+}
+
+struct Queue {
+  jobs: Vec<Job>,
+  free: usize,
+}
+
+#[derive(Clone)]
+pub struct JobQueue(Arc<(Mutex<Queue>, Condvar)>);
+
+impl Default for JobQueue {
+  fn default() -> Self {
+    let slots = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    Self(Arc::new((
+      Mutex::new(Queue {
+        jobs: Vec::new(),
+        free: slots,
+      }),
+      Condvar::new(),
+    )))
+  }
+}
+
+impl JobQueue {
+  fn lock(&self) -> std::sync::MutexGuard<'_, Queue> {
+    self.0 .0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  // Wakes blocked items so they recheck whose turn it is.
+  fn changed(&self) {
+    self.0 .1.notify_all();
+  }
+
+  /// Queues a batch behind every job of equal or higher priority. Dropping the guard removes it.
+  pub(crate) fn add(&self, batch_id: &str, priority: i32, low_power: bool) -> JobGuard {
+    let mut q = self.lock();
+    let at = q
+      .jobs
+      .iter()
+      .position(|j| j.priority < priority)
+      .unwrap_or(q.jobs.len());
+    q.jobs.insert(
+      at,
+      Job {
+        batch_id: batch_id.to_string(),
+        priority,
+        waiting: false,
+        running: 0,
+        low_power,
+      },
+    );
+    JobGuard {
+      queue: self.clone(),
+      batch_id: batch_id.to_string(),
+    }
+  }
+
+  pub(crate) fn is_low_power(&self, batch_id: &str) -> bool {
+    self.lock().jobs.iter().any(|j| j.batch_id == batch_id && j.low_power)
+  }
+
+  /// Blocks until `batch_id` is the first waiting job and a slot is free.
+  pub(crate) fn acquire(&self, batch_id: &str) -> Slot {
+    let mut q = self.lock();
+    loop {
+      let (first_waiting, low_power) = {
+        let Some(job) = q.jobs.iter_mut().find(|j| j.batch_id == batch_id) else {
+          // Not queued (e.g. already removed): don't hold it up.
+          return Slot(None, false);
+        };
+        job.waiting = true;
+        let low_power = job.low_power;
+        (
+          q.jobs.iter().find(|j| j.waiting).map(|j| j.batch_id == batch_id),
+          low_power,
+        )
+      };
+      // Low-power jobs wait their turn behind each other for a single slot.
+      let low_power_busy = low_power && q.jobs.iter().any(|j| j.low_power && j.running > 0);
+      if q.free > 0 && first_waiting == Some(true) && !low_power_busy {
+        q.free -= 1;
+        if let Some(job) = q.jobs.iter_mut().find(|j| j.batch_id == batch_id) {
+          job.waiting = false;
+          job.running += 1;
+        }
+        // Another free slot may now belong to the next job in line.
+        self.changed();
+        return Slot(Some((self.clone(), batch_id.to_string())), low_power);
+      }
+      // A low-power job stuck behind another gives way to the jobs queued after it.
+      if low_power_busy {
+        if let Some(job) = q.jobs.iter_mut().find(|j| j.batch_id == batch_id) {
+          job.waiting = false;
+        }
+        if q.jobs.iter().any(|j| j.waiting) {
+          self.changed();
+        }
+      }
+      q = self.0 .1.wait(q).unwrap_or_else(|e| e.into_inner());
+    }
+  }
+
+  fn position(q: &Queue, batch_id: &str) -> Result<usize, String> {
+    q.jobs
+      .iter()
+      .position(|j| j.batch_id == batch_id)
+      .ok_or_else(|| "No such job in the queue.".to_string())
+  }
+
+  fn move_to(&self, batch_id: &str, to: usize) -> Result<(), String> {
+    let mut q = self.lock();
+    let from = Self::position(&q, batch_id)?;
+    let mut job = q.jobs.remove(from);
+    let to = to.min(q.jobs.len());
+    // Take the priority of the job now ahead of it (at the front, at least that of the one
+    // behind), so jobs added later still line up around it.
+    job.priority = match to.checked_sub(1).and_then(|i| q.jobs.get(i)) {
+      Some(ahead) => ahead.priority,
+      None => q
+        .jobs
+        .first()
+        .map_or(job.priority, |next| job.priority.max(next.priority)),
+    };
+    q.jobs.insert(to, job);
+    drop(q);
+    self.changed();
+    Ok(())
+  }
+}
+
+/// How long a low-power job rests after an item that took `render` to convert.
+pub(crate) fn pacing(render: Duration) -> Duration {
+  render.min(MAX_PACING)
+}
+
+/// Runs `f` on a fresh thread at the lowest scheduling priority. A new thread rather than
+/// the caller's, since a pooled thread can't always be raised back afterwards.
+pub(crate) fn at_low_priority<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+  std::thread::scope(|s| {
+    let handle = s.spawn(|| {
+      // Best effort: some platforms refuse, and the pacing still applies.
+      #[cfg(desktop)]
+      let _ = set_current_thread_priority(ThreadPriority::Min);
+      f()
+    });
+    handle.join().unwrap_or_else(|e| std::panic::resume_unwind(e))
+  })
+}
+
+pub(crate) struct JobGuard {
+  queue: JobQueue,
+  batch_id: String,
+}
+
+impl Drop for JobGuard {
+  fn drop(&mut self) {
+    self.queue.lock().jobs.retain(|j| j.batch_id != self.batch_id);
+    self.queue.changed();
+  }
+}
+
+/// A render slot, handed back when dropped. Says whether the job was low-power when it got it.
+pub(crate) struct Slot(Option<(JobQueue, String)>, bool);
+
+impl Slot {
+  /// A slot for a batch run outside the queue, which holds nothing back.
+  pub(crate) fn unqueued() -> Self {
+    Self(None, false)
+  }
+
+  pub(crate) fn low_power(&self) -> bool {
+    self.1
+  }
+
+  /// Hands the slot back while `f` runs (e.g. while an item waits on the user), then waits
+  /// for one again.
+  pub(crate) fn released<T>(&mut self, f: impl FnOnce() -> T) -> T {
+    let Some((queue, batch_id)) = self.0.take() else {
+      return f();
+    };
+    give_back(&queue, &batch_id);
+    let out = f();
+    *self = queue.acquire(&batch_id);
+    out
+  }
+}
+
+fn give_back(queue: &JobQueue, batch_id: &str) {
+  let mut q = queue.lock();
+  q.free += 1;
+  if let Some(job) = q.jobs.iter_mut().find(|j| j.batch_id == batch_id) {
+    job.running = job.running.saturating_sub(1);
+  }
+  drop(q);
+  queue.changed();
+}
+
+impl Drop for Slot {
+  fn drop(&mut self) {
+    if let Some((queue, batch_id)) = self.0.take() {
+      give_back(&queue, &batch_id);
+    }
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedJob {
+  pub batch_id: String,
+  pub position: u32, // 0 = next in line
+  pub priority: i32,
+  pub running: u32, // items rendering right now
+  pub low_power: bool,
+}
+
+/// Every queued batch, in the order free slots go to them.
+#[tauri::command(rename_all = "camelCase")]
+pub fn list_jobs(queue: State<'_, JobQueue>) -> Vec<QueuedJob> {
+  let q = queue.lock();
+  q.jobs
+    .iter()
+    .enumerate()
+    .map(|(i, j)| QueuedJob {
+      batch_id: j.batch_id.clone(),
+      position: i as u32,
+      priority: j.priority,
+      running: j.running,
+      low_power: j.low_power,
+    })
+    .collect()
+}
+
+/// Moves a batch to `position` in the queue (0 = next in line).
+#[tauri::command(rename_all = "camelCase")]
+pub fn reorder_job(queue: State<'_, JobQueue>, batch_id: String, position: u32) -> Result<(), String> {
+  queue.move_to(&batch_id, position as usize)
+}
+
+/// Moves a batch to the front of the queue.
+#[tauri::command(rename_all = "camelCase")]
+pub fn bump_job(queue: State<'_, JobQueue>, batch_id: String) -> Result<(), String> {
+  queue.move_to(&batch_id, 0)
+}
+
+/// Switches a running batch in or out of low-power mode, starting with its next item.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_low_power(queue: State<'_, JobQueue>, batch_id: String, enabled: bool) -> Result<(), String> {
+  let mut q = queue.lock();
+  let i = JobQueue::position(&q, &batch_id)?;
+  q.jobs[i].low_power = enabled;
+  drop(q);
+  queue.changed();
+  Ok(())
+}
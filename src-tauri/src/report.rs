@@ -0,0 +1,277 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use ts_rs::TS;
+
+use crate::convert::ConvertRequest;
+
+const CONTACT_SHEET_FILE: &str = "index.html";
+const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+const LARGEST_FILES: usize = 10;
+
+/// One successfully written output, collected during a batch for the post-batch reports.
+#[derive(Debug, Clone)]
+pub(crate) struct OutputRecord {
+  pub svg: PathBuf,
+  pub png: PathBuf,
+  pub width: u32,
+  pub height: u32,
+  pub bytes: u64,
+  pub checksum: Option<String>,
+}
+
+/// Where a batch's bytes went: every file it wrote, reports included.
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct DiskUsage {
+  #[ts(type = "number")]
+  pub total_bytes: u64,
+  pub files: u32,
+  pub largest: Vec<FileUsage>,
+  pub folders: Vec<FolderUsage>, // largest first
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FileUsage {
+  pub path: String,
+  #[ts(type = "number")]
+  pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct FolderUsage {
+  pub path: String,
+  #[ts(type = "number")]
+  pub bytes: u64,
+  pub files: u32,
+}
+
+// Sizes are read back from disk so they match what the files actually take up.
+pub(crate) fn disk_usage(written: &[PathBuf]) -> DiskUsage {
+  let mut usage = DiskUsage::default();
+  let mut files = Vec::with_capacity(written.len());
+  let mut folders: BTreeMap<&Path, (u64, u32)> = BTreeMap::new();
+  for path in written {
+    let Ok(meta) = fs::metadata(path) else { continue };
+    let bytes = meta.len();
+    usage.total_bytes += bytes;
+    usage.files += 1;
+    let folder = folders.entry(path.parent().unwrap_or(Path::new(""))).or_default();
+    folder.0 += bytes;
+    folder.1 += 1;
+    files.push((path, bytes));
+  }
+  files.sort_by(|a, b| b.1.cmp(&a.1));
+  usage.largest = files
+    .into_iter()
+    .take(LARGEST_FILES)
+    .map(|(path, bytes)| FileUsage {
+      path: path.to_string_lossy().to_string(),
+      bytes,
+    })
+    .collect();
+  usage.folders = folders
+    .into_iter()
+    .map(|(path, (bytes, files))| FolderUsage {
+      path: path.to_string_lossy().to_string(),
+      bytes,
+      files,
+    })
+    .collect();
+  usage.folders.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+  usage
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest<'a> {
+  version: u32,
+  generated_at: u64,
+  settings: &'a ConvertRequest,
+  files: Vec<ManifestFile>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ManifestFile {
+  source: String,
+  output: String,
+  width: u32,
+  height: u32,
+  bytes: u64,
+  sha256: String,
+}
+
+fn escape_html(s: &str) -> String {
+  let mut out = String::with_capacity(s.len());
+  for c in s.chars() {
+    match c {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&#39;"),
+      _ => out.push(c),
+    }
+  }
+  out
+}
+
+fn format_bytes(bytes: u64) -> String {
+  const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+  let mut v = bytes as f64;
+  let mut unit = 0;
+  while v >= 1024.0 && unit < UNITS.len() - 1 {
+    v /= 1024.0;
+    unit += 1;
+  }
+  if unit == 0 {
+    format!("{bytes} B")
+  } else {
+    format!("{:.1} {}", v, UNITS[unit])
+  }
+}
+
+// Forward-slash path relative to `dir`, if `path` lives under it.
+fn relative_to(dir: &Path, path: &Path) -> Option<String> {
+  let rel = path.strip_prefix(dir).ok()?;
+  Some(
+    rel
+      .components()
+      .map(|c| c.as_os_str().to_string_lossy().to_string())
+      .collect::<Vec<_>>()
+      .join("/"),
+  )
+}
+
+// Percent-encodes a '/'-separated path for a URL, leaving only unreserved characters and the
+// separators as they are, so names with '#', '?', '%' or spaces still link to the file.
+fn encode_url_path(path: &str) -> String {
+  let mut out = String::with_capacity(path.len());
+  for b in path.bytes() {
+    if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+      out.push(b as char);
+    } else {
+      out.push_str(&format!("%{b:02X}"));
+    }
+  }
+  out
+}
+
+// Prefer a path relative to the sheet so the folder can be moved or zipped as a whole.
+fn image_href(dir: &Path, png: &Path) -> String {
+  if let Some(rel) = relative_to(dir, png) {
+    return encode_url_path(&rel);
+  }
+  let abs = png.to_string_lossy().replace('\\', "/");
+  match abs.as_bytes() {
+    // A UNC share: the server is the URL's host.
+    [b'/', b'/', ..] => format!("file:{}", encode_url_path(&abs)),
+    [b'/', ..] => format!("file://{}", encode_url_path(&abs)),
+    // A drive path (C:/…) still needs the empty host; the drive's colon stays as is.
+    [drive, b':', ..] if drive.is_ascii_alphabetic() => {
+      format!("file:///{}:{}", *drive as char, encode_url_path(&abs[2..]))
+    }
+    _ => format!("file:///{}", encode_url_path(&abs)),
+  }
+}
+
+/// Writes an `index.html` into `dir` showing every output with its source, size and file size.
+pub(crate) fn write_contact_sheet(dir: &Path, records: &[OutputRecord]) -> Result<PathBuf, String> {
+  let mut cards = String::new();
+  for r in records {
+    cards.push_str(&format!(
+      concat!(
+        "<figure>",
+        "<a href=\"{href}\"><img src=\"{href}\" loading=\"lazy\" alt=\"\"></a>",
+        "<figcaption><b>{name}</b><span>{w}×{h} · {size}</span><span class=\"src\">{svg}</span></figcaption>",
+        "</figure>\n"
+      ),
+      href = escape_html(&image_href(dir, &r.png)),
+      name = escape_html(&r.png.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()),
+      w = r.width,
+      h = r.height,
+      size = format_bytes(r.bytes),
+      svg = escape_html(&r.svg.to_string_lossy()),
+    ));
+  }
+
+  let html = format!(
+    r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>SVG → PNG ({count} files)</title>
+<style>
+body {{ font-family: -apple-system, system-ui, sans-serif; margin: 24px; background: #f4f4f5; color: #18181b; }}
+main {{ display: grid; grid-template-columns: repeat(auto-fill, minmax(180px, 1fr)); gap: 16px; }}
+figure {{ margin: 0; padding: 12px; background: #fff; border-radius: 10px; box-shadow: 0 1px 2px rgba(0,0,0,.08); }}
+img {{ display: block; width: 100%; height: 140px; object-fit: contain;
+  background: repeating-conic-gradient(#e4e4e7 0% 25%, #fff 0% 50%) 0 0 / 16px 16px; }}
+figcaption {{ display: flex; flex-direction: column; gap: 2px; margin-top: 8px; font-size: 12px; word-break: break-all; }}
+.src {{ color: #71717a; }}
+</style>
+</head>
+<body>
+<h1>{count} converted files</h1>
+<main>
+{cards}</main>
+</body>
+</html>
+"#,
+    count = records.len(),
+    cards = cards,
+  );
+
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let path = dir.join(CONTACT_SHEET_FILE);
+  fs::write(&path, html).map_err(|e| e.to_string())?;
+  Ok(path)
+}
+
+/// Writes a `manifest.json` into `dir` describing every output and the settings used.
+/// Output paths are relative to the manifest when possible; sources stay absolute.
+pub(crate) fn write_manifest(dir: &Path, req: &ConvertRequest, records: &[OutputRecord]) -> Result<PathBuf, String> {
+  let mut files = Vec::with_capacity(records.len());
+  for r in records {
+    let sha256 = match r.checksum.as_ref() {
+      Some(c) => c.clone(),
+      None => format!("{:x}", Sha256::digest(fs::read(&r.png).map_err(|e| e.to_string())?)),
+    };
+    files.push(ManifestFile {
+      source: r.svg.to_string_lossy().to_string(),
+      output: relative_to(dir, &r.png).unwrap_or_else(|| r.png.to_string_lossy().to_string()),
+      width: r.width,
+      height: r.height,
+      bytes: r.bytes,
+      sha256,
+    });
+  }
+
+  let manifest = Manifest {
+    version: MANIFEST_VERSION,
+    generated_at: SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|d| d.as_secs())
+      .unwrap_or(0),
+    settings: req,
+    files,
+  };
+  let data = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let path = dir.join(MANIFEST_FILE);
+  fs::write(&path, data).map_err(|e| e.to_string())?;
+  Ok(path)
+}
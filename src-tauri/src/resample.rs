@@ -0,0 +1,237 @@
+use std::collections::VecDeque;
+
+use resvg::{tiny_skia, usvg};
+
+use crate::convert::MAX_PIXELS;
+
+// Supersampled rendering: the SVG is rasterized at an integer multiple of the output
+// size, then filtered down. Pixels are processed as premultiplied floats so filters
+// don't bleed color out of transparent areas, a few rows at a time so only the 8-bit
+// render counts against MAX_PIXELS.
+const MAX_SUPERSAMPLE: u32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Filter {
+  Box,
+  Nearest,
+  Bilinear,
+  CatmullRom,
+  Lanczos3,
+}
+
+impl Filter {
+  pub(crate) fn parse(s: &str) -> Result<Self, String> {
+    match s {
+      "box" => Ok(Self::Box),
+      "nearest" => Ok(Self::Nearest),
+      "bilinear" => Ok(Self::Bilinear),
+      "catmullRom" => Ok(Self::CatmullRom),
+      "lanczos3" => Ok(Self::Lanczos3),
+      _ => Err(format!("Invalid resample filter: {s}")),
+    }
+  }
+
+  fn support(self) -> f32 {
+    match self {
+      Self::Box | Self::Nearest => 0.5,
+      Self::Bilinear => 1.0,
+      Self::CatmullRom => 2.0,
+      Self::Lanczos3 => 3.0,
+    }
+  }
+
+  fn weight(self, t: f32) -> f32 {
+    let t = t.abs();
+    match self {
+      Self::Box | Self::Nearest => (t < 0.5) as u8 as f32,
+      Self::Bilinear => (1.0 - t).max(0.0),
+      Self::CatmullRom => {
+        if t < 1.0 {
+          1.5 * t * t * t - 2.5 * t * t + 1.0
+        } else if t < 2.0 {
+          -0.5 * t * t * t + 2.5 * t * t - 4.0 * t + 2.0
+        } else {
+          0.0
+        }
+      }
+      Self::Lanczos3 => {
+        if t < 1e-6 {
+          1.0
+        } else if t < 3.0 {
+          let pt = std::f32::consts::PI * t;
+          3.0 * pt.sin() * (pt / 3.0).sin() / (pt * pt)
+        } else {
+          0.0
+        }
+      }
+    }
+  }
+}
+
+/// Maps 0..=1 channel values into the space filtering and compositing happen in, and back.
+#[derive(Clone, Copy)]
+pub(crate) struct Transfer {
+  pub decode: fn(f32) -> f32,
+  pub encode: fn(f32) -> f32,
+}
+
+impl Transfer {
+  pub(crate) const IDENTITY: Self = Self {
+    decode: |v| v,
+    encode: |v| v,
+  };
+}
+
+fn supersample_factor(out_w: u32, out_h: u32) -> u32 {
+  let pixels = out_w as u64 * out_h as u64;
+  (1..=MAX_SUPERSAMPLE)
+    .rev()
+    .find(|k| pixels * (*k as u64 * *k as u64) <= MAX_PIXELS)
+    .unwrap_or(1)
+}
+
+// Per-output-sample source taps and normalized weights along one axis.
+fn axis_taps(src_len: u32, dst_len: u32, filter: Filter) -> Vec<Vec<(usize, f32)>> {
+  let scale = src_len as f32 / dst_len as f32;
+  let stretch = scale.max(1.0);
+  let radius = filter.support() * stretch;
+  (0..dst_len)
+    .map(|x| {
+      let center = (x as f32 + 0.5) * scale - 0.5;
+      if filter == Filter::Nearest {
+        let i = ((x as f32 + 0.5) * scale).floor().min(src_len as f32 - 1.0);
+        return vec![(i as usize, 1.0)];
+      }
+      let lo = (center - radius).floor().max(0.0) as i64;
+      let hi = (center + radius).ceil().min(src_len as f32 - 1.0) as i64;
+      let mut taps: Vec<(usize, f32)> = (lo..=hi)
+        .map(|i| (i as usize, filter.weight((i as f32 - center) / stretch)))
+        .filter(|(_, w)| *w != 0.0)
+        .collect();
+      let sum: f32 = taps.iter().map(|(_, w)| w).sum();
+      if sum.abs() > f32::EPSILON {
+        taps.iter_mut().for_each(|(_, w)| *w /= sum);
+      }
+      taps
+    })
+    .collect()
+}
+
+// Filters the supersampled render down to `out_w`×`out_h` one output row at a time, handing
+// each to `emit` as premultiplied floats. Only the horizontally filtered source rows the
+// current output row reaches are kept, so memory beyond the two pixmaps stays a few rows
+// wide however large the render is.
+fn resample_rows(
+  src: &tiny_skia::Pixmap,
+  lut: &[f32; 256],
+  out_w: u32,
+  out_h: u32,
+  filter: Filter,
+  mut emit: impl FnMut(usize, &[[f32; 4]]),
+) {
+  let x_taps = axis_taps(src.width(), out_w, filter);
+  let y_taps = axis_taps(src.height(), out_h, filter);
+  let src_w = src.width() as usize;
+  let filter_row = |y: usize| -> Vec<[f32; 4]> {
+    let row: Vec<[f32; 4]> = src.pixels()[y * src_w..(y + 1) * src_w]
+      .iter()
+      .map(|p| {
+        let c = p.demultiply();
+        let a = c.alpha() as f32 / 255.0;
+        [lut[c.red() as usize] * a, lut[c.green() as usize] * a, lut[c.blue() as usize] * a, a]
+      })
+      .collect();
+    x_taps
+      .iter()
+      .map(|taps| {
+        let mut acc = [0.0f32; 4];
+        for &(i, w) in taps {
+          acc.iter_mut().zip(row[i]).for_each(|(a, s)| *a += s * w);
+        }
+        acc
+      })
+      .collect()
+  };
+
+  // Filtered rows `first..first + rows.len()` of the source.
+  let mut rows: VecDeque<Vec<[f32; 4]>> = VecDeque::new();
+  let mut first = 0;
+  let mut out = vec![[0.0f32; 4]; out_w as usize];
+  for (y, taps) in y_taps.iter().enumerate() {
+    let (Some(&(lo, _)), Some(&(hi, _))) = (taps.first(), taps.last()) else {
+      out.fill([0.0; 4]);
+      emit(y, &out);
+      continue;
+    };
+    if lo < first || lo >= first + rows.len() {
+      rows.clear();
+      first = lo;
+    }
+    while first < lo {
+      rows.pop_front();
+      first += 1;
+    }
+    while first + rows.len() <= hi {
+      rows.push_back(filter_row(first + rows.len()));
+    }
+    for (x, px) in out.iter_mut().enumerate() {
+      let mut acc = [0.0f32; 4];
+      for &(i, w) in taps {
+        acc.iter_mut().zip(rows[i - first][x]).for_each(|(a, s)| *a += s * w);
+      }
+      // Negative lobes ring; keep the result a valid premultiplied color.
+      let a = acc[3].clamp(0.0, 1.0);
+      *px = [acc[0].clamp(0.0, a), acc[1].clamp(0.0, a), acc[2].clamp(0.0, a), a];
+    }
+    emit(y, &out);
+  }
+}
+
+/// Renders `tree` supersampled and filters it down to `out_w`×`out_h`, compositing the
+/// background in the space given by `transfer`.
+pub(crate) fn render_resampled(
+  tree: &usvg::Tree,
+  transform: usvg::Transform,
+  out_w: u32,
+  out_h: u32,
+  background: Option<tiny_skia::Color>,
+  filter: Filter,
+  transfer: Transfer,
+) -> Result<tiny_skia::Pixmap, String> {
+  let k = supersample_factor(out_w, out_h);
+  let (hi_w, hi_h) = (out_w * k, out_h * k);
+  let mut hi = tiny_skia::Pixmap::new(hi_w, hi_h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  resvg::render(tree, transform.post_scale(k as f32, k as f32), &mut hi.as_mut());
+
+  let Transfer { decode, encode } = transfer;
+  let mut lut = [0.0f32; 256];
+  for (i, v) in lut.iter_mut().enumerate() {
+    *v = decode(i as f32 / 255.0);
+  }
+  // Without supersampling every output pixel is just its source pixel.
+  let filter = if k == 1 { Filter::Nearest } else { filter };
+
+  let bg = background.map(|c| [decode(c.red()), decode(c.green()), decode(c.blue()), c.alpha()]);
+  let mut out = tiny_skia::Pixmap::new(out_w, out_h).ok_or_else(|| "Failed to allocate pixmap.".to_string())?;
+  let out_pixels = out.pixels_mut();
+  resample_rows(&hi, &lut, out_w, out_h, filter, |y, row| {
+    let dst_row = &mut out_pixels[y * out_w as usize..(y + 1) * out_w as usize];
+    for (dst, mut px) in dst_row.iter_mut().zip(row.iter().copied()) {
+      if let Some(b) = bg {
+        let rest = 1.0 - px[3];
+        for (v, c) in px.iter_mut().zip(&b[..3]) {
+          *v += c * b[3] * rest;
+        }
+        px[3] += b[3] * rest;
+      }
+      let a = px[3].clamp(0.0, 1.0);
+      let channel = |v: f32| {
+        let straight = if a > 0.0 { v / a } else { 0.0 };
+        (encode(straight.clamp(0.0, 1.0)) * 255.0).round() as u8
+      };
+      let color = tiny_skia::ColorU8::from_rgba(channel(px[0]), channel(px[1]), channel(px[2]), (a * 255.0).round() as u8);
+      *dst = color.premultiply();
+    }
+  });
+  Ok(out)
+}
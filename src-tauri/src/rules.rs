@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use ignore::overrides::{Override, OverrideBuilder};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::convert::ConvertRequest;
+
+// Per-file settings for mixed batches: each rule pairs gitignore-style globs with
+// request fields to override, so `logos/**` and `icons/**` can get different sizes in
+// one run. The first rule whose globs match wins; unmatched files use the request as is.
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConversionRule {
+  pub matches: Vec<String>, // relative to the input folder (folder mode) or file names
+  pub settings: serde_json::Map<String, serde_json::Value>, // ConvertRequest fields, camelCase
+}
+
+// Fields that define the batch itself rather than how one file is converted.
+const BATCH_FIELDS: [&str; 10] = [
+  "inputMode",
+  "inputPath",
+  "inputPaths",
+  "archiveInMemory",
+  "exclude",
+  "rules",
+  "priority",
+  "lowPower",
+  "upload",
+  "sftp",
+];
+
+/// The request's rules, compiled and merged with the base request.
+pub(crate) struct RuleSet {
+  rules: Vec<(Override, ConvertRequest)>,
+}
+
+impl RuleSet {
+  pub(crate) fn build(base: &ConvertRequest) -> Result<Self, String> {
+    let mut rules = Vec::new();
+    for (i, rule) in base.rules.iter().flatten().enumerate() {
+      let n = i + 1;
+      if rule.matches.is_empty() {
+        return Err(format!("Rule {n} has no match patterns."));
+      }
+      if let Some(key) = rule.settings.keys().find(|k| BATCH_FIELDS.contains(&k.as_str())) {
+        return Err(format!("Rule {n} can't override {key}."));
+      }
+      let mut builder = OverrideBuilder::new("");
+      for glob in &rule.matches {
+        builder
+          .add(glob)
+          .map_err(|e| format!("Rule {n}: invalid pattern {glob}: {e}"))?;
+      }
+      let globs = builder.build().map_err(|e| format!("Rule {n}: {e}"))?;
+
+      let mut merged = serde_json::to_value(base).map_err(|e| e.to_string())?;
+      if let Some(obj) = merged.as_object_mut() {
+        // A rule picking its own size mode shouldn't be overridden by the batch's preset.
+        if rule.settings.contains_key("sizeMode") && !rule.settings.contains_key("sizePreset") {
+          obj.remove("sizePreset");
+        }
+        obj.extend(rule.settings.clone());
+      }
+      let mut req: ConvertRequest =
+        serde_json::from_value(merged).map_err(|e| format!("Rule {n}: invalid settings: {e}"))?;
+      // Not serialized, so carried over by hand.
+      req.metadata = base.metadata.clone();
+      rules.push((globs, req));
+    }
+    Ok(Self { rules })
+  }
+
+  /// Merged requests of every rule, for resolving presets and validating up front.
+  pub(crate) fn requests_mut(&mut self) -> impl Iterator<Item = &mut ConvertRequest> {
+    self.rules.iter_mut().map(|(_, req)| req)
+  }
+
+  /// The request to convert `svg` with: the first matching rule's, else `base`.
+  pub(crate) fn request_for<'a>(&'a self, svg: &Path, base: &'a ConvertRequest) -> &'a ConvertRequest {
+    if self.rules.is_empty() {
+      return base;
+    }
+    // Folder mode matches paths relative to the input folder; file mode matches names.
+    let rel = match svg.strip_prefix(&base.input_path) {
+      Ok(rel) if base.input_mode == "folder" => rel,
+      _ => Path::new(svg.file_name().unwrap_or_default()),
+    };
+    self
+      .rules
+      .iter()
+      .find(|(globs, _)| globs.matched(rel, false).is_whitelist())
+      .map(|(_, req)| req)
+      .unwrap_or(base)
+  }
+}
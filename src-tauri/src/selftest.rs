@@ -0,0 +1,91 @@
+use resvg::usvg;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::convert::{render_tree_at, ConvertRequest};
+
+// Reference images use opaque colors on pixel-aligned geometry, so the expected
+// output is exact and independent of anti-aliasing details. Hashes cover the raw
+// RGBA pixmap rather than the PNG, which would also depend on encoder settings.
+const CASES: &[(&str, &str, &str)] = &[
+  (
+    "solid-fill",
+    include_str!("../selftest/solid-fill.svg"),
+    "71205eb7a329a3ead670c77eee185c0fbeb612f7a2b3d6aadbe2af4f9276b60d",
+  ),
+  (
+    "viewbox-scale",
+    include_str!("../selftest/viewbox-scale.svg"),
+    "d8d8c8b3a38088b93be4fbcf02aa9ef2b7383eb267ad56f2bf79e6d47afe4a39",
+  ),
+  (
+    "group-transform",
+    include_str!("../selftest/group-transform.svg"),
+    "f6756c35941fd5ec22594a5df95fe63f5d62ad517b4cbf6c4d95f8a19087c33f",
+  ),
+  (
+    "clip-path",
+    include_str!("../selftest/clip-path.svg"),
+    "a0f5cc6ff26c617e6190d03e9291a3fc05d48301f0eea0066873ade53744672b",
+  ),
+  (
+    "use-reference",
+    include_str!("../selftest/use-reference.svg"),
+    "64d99fb2e2502ed36b520f7e3c4ee55c7856f8db594d1ad88614efc0da083522",
+  ),
+];
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+  pub name: String,
+  pub ok: bool,
+  pub expected: String,
+  pub actual: Option<String>,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+  pub passed: u32,
+  pub failed: u32,
+  pub results: Vec<SelfTestResult>,
+}
+
+fn render_hash(svg: &str) -> Result<String, String> {
+  let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(|e| e.to_string())?;
+  let size = tree.size().to_int_size();
+  let pixmap = render_tree_at(&tree, &ConvertRequest::default(), size.width(), size.height())?;
+  Ok(format!("{:x}", Sha256::digest(pixmap.data())))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn run_self_test() -> Result<SelfTestReport, String> {
+  tauri::async_runtime::spawn_blocking(|| {
+    let results: Vec<SelfTestResult> = CASES
+      .iter()
+      .map(|(name, svg, expected)| {
+        let (actual, error) = match render_hash(svg) {
+          Ok(h) => (Some(h), None),
+          Err(e) => (None, Some(e)),
+        };
+        SelfTestResult {
+          name: name.to_string(),
+          ok: actual.as_deref() == Some(*expected),
+          expected: expected.to_string(),
+          actual,
+          error,
+        }
+      })
+      .collect();
+    let passed = results.iter().filter(|r| r.ok).count() as u32;
+    SelfTestReport {
+      passed,
+      failed: results.len() as u32 - passed,
+      results,
+    }
+  })
+  .await
+  .map_err(|e| e.to_string())
+}
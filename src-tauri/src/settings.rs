@@ -0,0 +1,119 @@
+use std::{fs, path::PathBuf, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+  pub window: WindowSettings,
+  pub metadata: MetadataSettings,
+  pub upload: UploadSettings,
+  pub webhook: WebhookSettings,
+}
+
+/// Attribution embedded into every PNG the batch converter writes; blank fields are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetadataSettings {
+  pub author: Option<String>,
+  pub copyright: Option<String>,
+  pub license: Option<String>,
+}
+
+/// The S3-compatible bucket `upload: true` batches send outputs to. The secret key is kept in
+/// the system keychain under the access key id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UploadSettings {
+  pub endpoint: Option<String>, // e.g. https://<account>.r2.cloudflarestorage.com; AWS when unset
+  pub region: Option<String>,   // default us-east-1
+  pub bucket: Option<String>,
+  pub prefix: Option<String>, // key prefix, e.g. "assets/icons"
+  pub access_key_id: Option<String>,
+  pub path_style: bool, // bucket in the path rather than the host name (MinIO and most self-hosted stores)
+}
+
+/// Where to POST a JSON summary when a batch finishes or fails; no URL, no webhook.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WebhookSettings {
+  pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowSettings {
+  pub resizable: bool,
+  pub min_width: f64,
+  pub min_height: f64,
+  pub vibrancy: bool,
+  // Last known geometry, in logical pixels. `None` until the window has been moved/resized.
+  pub width: Option<f64>,
+  pub height: Option<f64>,
+  pub x: Option<f64>,
+  pub y: Option<f64>,
+}
+
+impl Default for WindowSettings {
+  fn default() -> Self {
+    Self {
+      resizable: true,
+      // The layout is designed for 1240×830; don't let the window shrink below it by default.
+      min_width: 1240.0,
+      min_height: 830.0,
+      vibrancy: true,
+      width: None,
+      height: None,
+      x: None,
+      y: None,
+    }
+  }
+}
+
+/// Settings persisted as JSON in the app config dir, shared as Tauri state.
+pub struct SettingsStore {
+  path: Option<PathBuf>,
+  inner: Mutex<Settings>,
+}
+
+impl SettingsStore {
+  pub fn load(app: &AppHandle) -> Self {
+    let path = app.path().app_config_dir().ok().map(|d| d.join(SETTINGS_FILE));
+    // A missing or corrupt file just falls back to defaults.
+    let settings = path
+      .as_ref()
+      .and_then(|p| fs::read(p).ok())
+      .and_then(|data| serde_json::from_slice::<Settings>(&data).ok())
+      .unwrap_or_default();
+    Self {
+      path,
+      inner: Mutex::new(settings),
+    }
+  }
+
+  pub fn get(&self) -> Settings {
+    self.inner.lock().unwrap_or_else(|e| e.into_inner()).clone()
+  }
+
+  pub fn update<F: FnOnce(&mut Settings)>(&self, f: F) {
+    let mut guard = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard);
+  }
+
+  pub fn save(&self) -> Result<(), String> {
+    let Some(path) = self.path.as_ref() else {
+      return Err("Settings location is unavailable.".into());
+    };
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let data = serde_json::to_vec_pretty(&self.get()).map_err(|e| e.to_string())?;
+    // Write to a sibling temp file first so a crash never leaves a half-written settings file.
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+  }
+}
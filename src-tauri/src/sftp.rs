@@ -0,0 +1,303 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{Manager, Runtime};
+use ts_rs::TS;
+
+use crate::{
+  convert::{ConvertRequest, EVENT_VERSION},
+  keychain,
+  upload::{UploadProgressEvent, UploadReport},
+};
+
+// Remote output (`sftp` in the request): the batch writes to a staging folder in the app cache
+// as if it were the output folder, then everything it wrote is copied to the server over SFTP,
+// keeping paths under the remote folder. Auth is by private key only; a passphrase for the key
+// comes from the system keychain. The server must already be in the user's known_hosts, as
+// for `ssh`. The staging folder is removed when the batch ends, so the batch's summary points
+// at the copies on the server. Connecting and transferring block, so both run off the async
+// runtime, and a server that stops answering times out instead of hanging the batch. There's
+// no SSH client on Android and iOS, so a remote destination fails up front there.
+
+const SCRATCH_DIR: &str = "staging";
+const KEYCHAIN_SERVICE: &str = "svg-to-png.sftp";
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SftpTarget {
+  pub host: String,
+  pub port: Option<u16>, // default 22
+  pub user: String,
+  pub path: String,     // remote folder; created if missing
+  pub key_path: String, // private key file, e.g. ~/.ssh/id_ed25519
+}
+
+impl SftpTarget {
+  fn port(&self) -> u16 {
+    self.port.unwrap_or(22)
+  }
+}
+
+/// The local folder a remote batch writes to; deleted on drop.
+pub(crate) struct Staging {
+  dir: PathBuf,
+  target: SftpTarget,
+  key_file: PathBuf, // `target.key_path` with a leading `~` expanded
+  known_hosts: PathBuf,
+}
+
+impl Drop for Staging {
+  fn drop(&mut self) {
+    if let Err(err) = fs::remove_dir_all(&self.dir) {
+      log::warn!("failed to remove {}: {err}", self.dir.display());
+    }
+  }
+}
+
+/// Checks `req`'s remote destination, if any, is complete without contacting the server.
+pub(crate) fn validate(req: &ConvertRequest) -> Result<(), String> {
+  let Some(target) = &req.sftp else {
+    return Ok(());
+  };
+  if req.output_dir.as_deref().is_some_and(|d| !d.trim().is_empty()) {
+    return Err("Choose either an output folder or an SFTP destination, not both.".into());
+  }
+  if target.host.trim().is_empty() || target.user.trim().is_empty() || target.path.trim().is_empty() {
+    return Err("An SFTP destination needs a host, user and path.".into());
+  }
+  Ok(())
+}
+
+// `~/…` as `ssh` reads it: relative to the user's home folder.
+fn expand_home(path: &str, home: &Path) -> PathBuf {
+  match path.strip_prefix('~') {
+    Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => home.join(rest.trim_start_matches(['/', '\\'])),
+    _ => PathBuf::from(path),
+  }
+}
+
+/// For a remote destination, checks the server can be reached and points `req` at a local
+/// staging folder instead. Blocks while connecting.
+pub(crate) fn stage_output<R: Runtime>(
+  app: &impl Manager<R>,
+  req: &mut ConvertRequest,
+) -> Result<Option<Staging>, String> {
+  let Some(target) = req.sftp.clone() else {
+    return Ok(None);
+  };
+  validate(req)?;
+  let home = app.path().home_dir().map_err(|e| e.to_string())?;
+  let known_hosts = home.join(".ssh").join("known_hosts");
+  let key_file = expand_home(target.key_path.trim(), &home);
+  // Fail now rather than after the whole batch has rendered.
+  session::connect(&target, &key_file, &known_hosts)?;
+
+  let dir = app
+    .path()
+    .app_cache_dir()
+    .map_err(|e| e.to_string())?
+    .join(SCRATCH_DIR)
+    .join(uuid::Uuid::new_v4().simple().to_string());
+  fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  req.output_dir = Some(dir.to_string_lossy().to_string());
+  Ok(Some(Staging {
+    dir,
+    target,
+    key_file,
+    known_hosts,
+  }))
+}
+
+#[cfg(desktop)]
+mod session {
+  use std::{
+    fs::File,
+    io,
+    net::{TcpStream, ToSocketAddrs},
+    path::Path,
+    time::Duration,
+  };
+
+  use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+
+  use super::{keychain, SftpTarget, KEYCHAIN_SERVICE};
+
+  const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+  const IO_TIMEOUT_MS: u32 = 60_000;
+
+  pub(super) type Connection = (Session, Sftp);
+
+  pub(super) fn connect(target: &SftpTarget, key_file: &Path, known_hosts: &Path) -> Result<Connection, String> {
+    let host = target.host.trim();
+    let err = |e: ssh2::Error| format!("SFTP {host}: {}", e.message());
+    let tcp = open_tcp(host, target.port()).map_err(|e| format!("Failed to connect to {host}: {e}"))?;
+    let mut session = Session::new().map_err(err)?;
+    session.set_timeout(IO_TIMEOUT_MS);
+    session.set_tcp_stream(tcp);
+    session.handshake().map_err(err)?;
+
+    let (key, _) = session.host_key().ok_or_else(|| format!("{host} sent no host key."))?;
+    let mut hosts = session.known_hosts().map_err(err)?;
+    // A missing known_hosts file just means nothing is known yet.
+    let _ = hosts.read_file(known_hosts, KnownHostFileKind::OpenSSH);
+    match hosts.check_port(host, target.port(), key) {
+      CheckResult::Match => {}
+      CheckResult::Mismatch => return Err(format!("The host key for {host} doesn't match known_hosts.")),
+      CheckResult::NotFound | CheckResult::Failure => {
+        return Err(format!("{host} isn't in known_hosts; connect once with ssh to add it."))
+      }
+    }
+
+    let passphrase = keychain::get(KEYCHAIN_SERVICE, &target.key_path).ok();
+    session
+      .userauth_pubkey_file(target.user.trim(), None, key_file, passphrase.as_deref())
+      .map_err(err)?;
+    let sftp = session.sftp().map_err(err)?;
+    Ok((session, sftp))
+  }
+
+  // Tries each address `host` resolves to in turn, giving each `CONNECT_TIMEOUT`.
+  fn open_tcp(host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut last = io::Error::new(io::ErrorKind::NotFound, "no address found");
+    for addr in (host, port).to_socket_addrs()? {
+      match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+        Ok(tcp) => return Ok(tcp),
+        Err(err) => last = err,
+      }
+    }
+    Err(last)
+  }
+
+  // Creates `dir` and any missing parents on the server. Remote paths always use '/'.
+  fn make_dirs(sftp: &Sftp, dir: &str) {
+    let mut at = String::new();
+    for part in dir.split('/') {
+      at.push_str(part);
+      at.push('/');
+      if part.is_empty() || sftp.stat(Path::new(&at)).is_ok() {
+        continue;
+      }
+      // Fails if it appeared meanwhile; the file create that follows reports real problems.
+      let _ = sftp.mkdir(Path::new(&at), 0o755);
+    }
+  }
+
+  pub(super) fn put((_, sftp): &Connection, file: &Path, remote: &str) -> Result<(), String> {
+    if let Some((parent, _)) = remote.rsplit_once('/') {
+      make_dirs(sftp, parent);
+    }
+    let mut src = File::open(file).map_err(|e| e.to_string())?;
+    let mut dst = sftp.create(Path::new(remote)).map_err(|e| e.message().to_string())?;
+    io::copy(&mut src, &mut dst).map_err(|e| e.to_string())?;
+    Ok(())
+  }
+}
+
+#[cfg(mobile)]
+mod session {
+  use std::path::Path;
+
+  use super::SftpTarget;
+
+  // Nothing can connect, so there are no values of this type.
+  pub(super) enum Connection {}
+
+  pub(super) fn connect(_target: &SftpTarget, _key_file: &Path, _known_hosts: &Path) -> Result<Connection, String> {
+    Err("SFTP destinations aren't available on this device.".into())
+  }
+
+  pub(super) fn put(connection: &Connection, _file: &Path, _remote: &str) -> Result<(), String> {
+    match *connection {}
+  }
+}
+
+impl Staging {
+  /// Whether `file` was written to the staging folder, and so only lasts until the batch ends.
+  pub(crate) fn holds(&self, file: &Path) -> bool {
+    file.starts_with(&self.dir)
+  }
+
+  // Where a file under the staging folder goes on the server. Remote paths always use '/'.
+  fn remote_for(&self, file: &Path) -> String {
+    let rel = file.strip_prefix(&self.dir).unwrap_or(file);
+    let parts: Vec<_> = rel.iter().map(|c| c.to_string_lossy()).collect();
+    let base = self.target.path.trim().trim_end_matches('/');
+    if parts.is_empty() {
+      base.to_string()
+    } else {
+      format!("{base}/{}", parts.join("/"))
+    }
+  }
+
+  /// `path` as an `sftp://` URL if it's in the staging folder, for reporting once the local copy
+  /// is gone; other paths are returned unchanged.
+  pub(crate) fn remote_url(&self, path: &str) -> String {
+    if !self.holds(Path::new(path)) {
+      return path.to_string();
+    }
+    let remote = self.remote_for(Path::new(path));
+    let sep = if remote.starts_with('/') { "" } else { "/~/" };
+    format!(
+      "sftp://{}@{}:{}{sep}{remote}",
+      self.target.user.trim(),
+      self.target.host.trim(),
+      self.target.port()
+    )
+  }
+
+  /// Copies every file in `files` under the staging folder to the server, reporting each
+  /// through `progress`. Files written elsewhere (e.g. by a rule with its own output folder)
+  /// aren't sent. Blocks until every file is sent or has failed.
+  pub(crate) fn transfer_all(
+    &self,
+    batch_id: &str,
+    files: &[PathBuf],
+    progress: impl Fn(UploadProgressEvent),
+  ) -> UploadReport {
+    let files: Vec<&Path> = files
+      .iter()
+      .filter(|f| self.holds(f) && f.is_file())
+      .map(PathBuf::as_path)
+      .collect();
+    let mut report = UploadReport::default();
+    let connection = session::connect(&self.target, &self.key_file, &self.known_hosts);
+    for (i, file) in files.iter().enumerate() {
+      let remote = self.remote_for(file);
+      let result = match &connection {
+        Ok(connection) => session::put(connection, file, &remote),
+        Err(err) => Err(err.clone()),
+      };
+      let error = result.err();
+      match &error {
+        None => report.uploaded += 1,
+        Some(err) => {
+          log::warn!("failed to transfer {}: {err}", file.display());
+          report.failed += 1;
+        }
+      }
+      progress(UploadProgressEvent {
+        version: EVENT_VERSION,
+        batch_id: batch_id.to_string(),
+        done: i as u32 + 1,
+        total: files.len() as u32,
+        key: remote,
+        error,
+      });
+    }
+    report
+  }
+}
+
+/// Stores the passphrase for a private key in the keychain; an empty one removes it.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_sftp_key_passphrase(key_path: String, passphrase: String) -> Result<(), String> {
+  if passphrase.is_empty() {
+    keychain::delete(KEYCHAIN_SERVICE, &key_path)
+  } else {
+    keychain::set(KEYCHAIN_SERVICE, &key_path, &passphrase)
+  }
+}
@@ -0,0 +1,183 @@
+use std::{
+  collections::HashSet,
+  fs,
+  io::Read,
+  path::{Path, PathBuf},
+};
+
+use resvg::tiny_skia;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{layout, report::OutputRecord};
+
+const MAX_SPRITE_PIXELS: u64 = 80_000_000;
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct SpriteOptions {
+  pub name: Option<String>,   // base file name, default "sprite"
+  pub format: Option<String>, // "css" | "scss"
+  pub padding: Option<u32>,   // gap between icons in px
+  pub prefix: Option<String>, // class prefix, default "icon"
+}
+
+struct Placement {
+  class: String,
+  x: u32,
+  y: u32,
+  width: u32,
+  height: u32,
+  png: PathBuf,
+}
+
+// Width and height from the IHDR chunk, so the sheet can be sized without decoding anything.
+fn png_size(path: &Path) -> Result<(u32, u32), String> {
+  let mut head = [0u8; 24];
+  fs::File::open(layout::io_path(path)?)
+    .and_then(|mut f| f.read_exact(&mut head))
+    .map_err(|e| format!("{}: {e}", path.display()))?;
+  if &head[..8] != b"\x89PNG\r\n\x1a\n" || &head[12..16] != b"IHDR" {
+    return Err(format!("{}: not a PNG file.", path.display()));
+  }
+  let dim = |i: usize| u32::from_be_bytes([head[i], head[i + 1], head[i + 2], head[i + 3]]);
+  Ok((dim(16), dim(20)))
+}
+
+fn class_name(prefix: &str, png: &Path, used: &mut HashSet<String>) -> String {
+  let stem = png.file_stem().and_then(|s| s.to_str()).unwrap_or("item");
+  let mut base = String::new();
+  for c in stem.chars() {
+    if c.is_ascii_alphanumeric() {
+      base.push(c.to_ascii_lowercase());
+    } else if !base.ends_with('-') {
+      base.push('-');
+    }
+  }
+  let base = format!("{prefix}-{}", base.trim_matches('-'));
+  let mut name = base.clone();
+  let mut n = 2;
+  while !used.insert(name.clone()) {
+    name = format!("{base}-{n}");
+    n += 1;
+  }
+  name
+}
+
+// Simple shelf packing: tallest first, rows roughly as wide as the square root of the total area.
+// Sizes come straight from the files, so the sheet is measured in u64; positions are only
+// meaningful once the caller has checked it against the pixel cap.
+fn pack(placements: &mut [Placement], padding: u32) -> (u64, u64) {
+  placements.sort_by(|a, b| b.height.cmp(&a.height).then(a.class.cmp(&b.class)));
+  let padding = padding as u64;
+  let area: u64 = placements
+    .iter()
+    .map(|p| (p.width as u64 + padding).saturating_mul(p.height as u64 + padding))
+    .fold(0, u64::saturating_add);
+  let widest = placements.iter().map(|p| p.width as u64).max().unwrap_or(0);
+  let row_width = ((area as f64).sqrt().ceil() as u64).max(widest);
+
+  let (mut x, mut y, mut row_h, mut sheet_w) = (0u64, 0u64, 0u64, 0u64);
+  for p in placements.iter_mut() {
+    if x > 0 && x + p.width as u64 > row_width {
+      x = 0;
+      y += row_h + padding;
+      row_h = 0;
+    }
+    p.x = x.min(u32::MAX as u64) as u32;
+    p.y = y.min(u32::MAX as u64) as u32;
+    sheet_w = sheet_w.max(x + p.width as u64);
+    x += p.width as u64 + padding;
+    row_h = row_h.max(p.height as u64);
+  }
+  (sheet_w.max(1), (y + row_h).max(1))
+}
+
+fn stylesheet(placements: &[Placement], sprite_file: &str, prefix: &str, scss: bool) -> String {
+  let mut out = String::new();
+  if scss {
+    out.push_str(&format!(
+      "%{prefix} {{\n  display: inline-block;\n  background-image: url(\"{sprite_file}\");\n  background-repeat: no-repeat;\n}}\n\n"
+    ));
+  } else {
+    out.push_str(&format!(
+      ".{prefix} {{\n  display: inline-block;\n  background-image: url(\"{sprite_file}\");\n  background-repeat: no-repeat;\n}}\n\n"
+    ));
+  }
+  for p in placements {
+    out.push_str(&format!(".{} {{\n", p.class));
+    if scss {
+      out.push_str(&format!("  @extend %{prefix};\n"));
+    }
+    out.push_str(&format!(
+      "  width: {}px;\n  height: {}px;\n  background-position: -{}px -{}px;\n}}\n\n",
+      p.width, p.height, p.x, p.y
+    ));
+  }
+  out
+}
+
+/// Packs the batch's PNG outputs into one sprite PNG and writes a matching CSS/SCSS file.
+/// Returns the stylesheet path.
+pub(crate) fn write_sprite(dir: &Path, records: &[OutputRecord], opts: &SpriteOptions) -> Result<PathBuf, String> {
+  let name = opts.name.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("sprite");
+  let prefix = opts.prefix.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("icon");
+  let scss = match opts.format.as_deref().unwrap_or("css") {
+    "css" => false,
+    "scss" => true,
+    _ => return Err("Invalid sprite stylesheet format (expected css or scss).".into()),
+  };
+  let padding = opts.padding.unwrap_or(2);
+
+  // SVG, PDF or WebP outputs in the same batch have nothing to pack.
+  let pngs: Vec<&OutputRecord> = records
+    .iter()
+    .filter(|r| r.png.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")))
+    .collect();
+  if pngs.is_empty() {
+    return Err("No PNG outputs to pack into a sprite.".into());
+  }
+
+  let mut used = HashSet::new();
+  let mut placements = Vec::with_capacity(pngs.len());
+  for r in pngs {
+    let (width, height) = png_size(&r.png)?;
+    placements.push(Placement {
+      class: class_name(prefix, &r.png, &mut used),
+      x: 0,
+      y: 0,
+      width,
+      height,
+      png: r.png.clone(),
+    });
+  }
+
+  let (sheet_w, sheet_h) = pack(&mut placements, padding);
+  if sheet_w.saturating_mul(sheet_h) > MAX_SPRITE_PIXELS {
+    return Err("Sprite sheet would be too large; convert fewer or smaller icons.".into());
+  }
+  let mut sheet = tiny_skia::Pixmap::new(sheet_w as u32, sheet_h as u32)
+    .ok_or_else(|| "Failed to allocate sprite sheet.".to_string())?;
+  // One icon decoded at a time, so only the sheet stays in memory.
+  for p in &placements {
+    let pixmap = tiny_skia::Pixmap::load_png(layout::io_path(&p.png)?).map_err(|e| e.to_string())?;
+    sheet.draw_pixmap(
+      p.x as i32,
+      p.y as i32,
+      pixmap.as_ref(),
+      &tiny_skia::PixmapPaint::default(),
+      tiny_skia::Transform::identity(),
+      None,
+    );
+  }
+
+  fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+  let sprite_file = format!("{name}.png");
+  let png = sheet.encode_png().map_err(|e| e.to_string())?;
+  fs::write(dir.join(&sprite_file), png).map_err(|e| e.to_string())?;
+
+  let css_path = dir.join(format!("{name}.{}", if scss { "scss" } else { "css" }));
+  fs::write(&css_path, stylesheet(&placements, &sprite_file, prefix, scss)).map_err(|e| e.to_string())?;
+  Ok(css_path)
+}
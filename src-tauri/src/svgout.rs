@@ -0,0 +1,119 @@
+use resvg::usvg;
+
+use crate::{
+  convert::{is_fixed_frame, parse_bg_color, ConvertRequest},
+  preprocess::root_tag,
+};
+
+// "svg" output format. By default writes usvg's normalized tree: uses are resolved,
+// styles flattened into attributes and unsupported/invisible elements dropped, which
+// makes it a lossless-for-rendering cleanup pass (SVGO-lite). Either way the root is
+// resized to the output size without rasterizing.
+
+pub(crate) fn validate_output_format(req: &ConvertRequest) -> Result<(), String> {
+  match req.output_format.as_deref().unwrap_or("png") {
+    "png" => Ok(()),
+    "svg" | "eps" if req.target.is_some() => Err("Vector output can't be combined with a platform target.".into()),
+    "svg" | "eps" => Ok(()),
+    other => Err(format!("Invalid output format: {other}")),
+  }
+}
+
+/// The file extension of a non-raster output format, if one was requested.
+pub(crate) fn vector_format(req: &ConvertRequest) -> Option<&'static str> {
+  match req.output_format.as_deref() {
+    Some("svg") => Some("svg"),
+    Some("eps") => Some("eps"),
+    _ => None,
+  }
+}
+
+// Attributes of a start tag as (name, raw value, quote) triples, in source order.
+fn parse_attributes(tag: &str) -> Vec<(&str, &str, char)> {
+  let mut attrs = Vec::new();
+  let mut rest = tag.trim_start_matches("<svg").trim_end_matches('>').trim_end_matches('/');
+  while let Some(eq) = rest.find('=') {
+    let name = rest[..eq].trim();
+    let after = rest[eq + 1..].trim_start();
+    let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+      break;
+    };
+    let Some(close) = after[1..].find(quote) else {
+      break;
+    };
+    attrs.push((name, &after[1..close + 1], quote));
+    rest = &after[close + 2..];
+  }
+  attrs
+}
+
+/// Rewrites the root element to `width`×`height`, keeping (or adding) the viewBox so the
+/// content scales with it, and optionally inserts a background rect behind everything.
+fn resize_root(text: &str, src: usvg::Size, width: u32, height: u32, req: &ConvertRequest) -> Result<String, String> {
+  let (start, end) = root_tag(text).ok_or_else(|| "Missing <svg> root element.".to_string())?;
+  let tag = &text[start..end];
+  let self_closing = tag.ends_with("/>");
+  let attrs = parse_attributes(tag);
+
+  let view_box = attrs
+    .iter()
+    .find(|(n, _, _)| *n == "viewBox")
+    .map(|(_, v, _)| v.to_string())
+    .unwrap_or_else(|| format!("0 0 {} {}", src.width(), src.height()));
+  // Match the rasterizer: exact sizes stretch unless cropping, which covers.
+  let aspect = if is_fixed_frame(req) {
+    Some(if req.crop.unwrap_or(false) { "xMidYMid slice" } else { "none" })
+  } else {
+    None
+  };
+
+  let mut out = String::with_capacity(text.len() + 128);
+  out.push_str(&text[..start]);
+  out.push_str("<svg");
+  for (name, value, quote) in &attrs {
+    if matches!(*name, "width" | "height" | "viewBox") || (aspect.is_some() && *name == "preserveAspectRatio") {
+      continue;
+    }
+    // Values are copied verbatim (entities and all), so keep their original quoting.
+    out.push_str(&format!(" {name}={quote}{value}{quote}"));
+  }
+  out.push_str(&format!(" width=\"{width}\" height=\"{height}\" viewBox=\"{view_box}\""));
+  if let Some(a) = aspect {
+    out.push_str(&format!(" preserveAspectRatio=\"{a}\""));
+  }
+  out.push('>');
+
+  if let Some(bg) = req.background.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+    parse_bg_color(bg).ok_or_else(|| "Invalid background color (expected #RRGGBB).".to_string())?;
+    let vb: Vec<&str> = view_box.split([' ', ',']).filter(|s| !s.is_empty()).collect();
+    if let [x, y, w, h] = vb[..] {
+      out.push_str(&format!("<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" fill=\"{bg}\"/>"));
+    }
+  }
+  if self_closing {
+    out.push_str("</svg>");
+  }
+  out.push_str(&text[end..]);
+  Ok(out)
+}
+
+/// SVG document for one output. `source` is the (preprocessed) input, used as-is when
+/// normalization is turned off.
+pub(crate) fn write_svg(
+  tree: &usvg::Tree,
+  source: &[u8],
+  req: &ConvertRequest,
+  width: u32,
+  height: u32,
+) -> Result<String, String> {
+  let text = if req.svg_normalize.unwrap_or(true) {
+    let opt = usvg::WriteOptions {
+      preserve_text: !req.text_to_paths.unwrap_or(false),
+      ..Default::default()
+    };
+    tree.to_string(&opt)
+  } else {
+    String::from_utf8_lossy(source).into_owned()
+  };
+  resize_root(&text, tree.size(), width, height, req)
+}
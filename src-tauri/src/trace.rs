@@ -0,0 +1,334 @@
+use std::{
+  collections::HashMap,
+  fmt::Write as _,
+  fs,
+  path::{Path, PathBuf},
+};
+
+use resvg::tiny_skia;
+use serde::Serialize;
+use zune_jpeg::{
+  zune_core::{colorspace::ColorSpace, options::DecoderOptions},
+  JpegDecoder,
+};
+
+use crate::conflict;
+
+// Raster → SVG tracing for simple logos. Each color layer becomes one even-odd path
+// traced along pixel edges and then simplified; there's no curve fitting, so "detail"
+// only trades staircase fidelity for fewer points.
+
+const DEFAULT_THRESHOLD: u8 = 128;
+const DEFAULT_COLORS: u32 = 8;
+const MAX_COLORS: u32 = 64;
+// Pixels at or below this alpha count as background.
+const ALPHA_CUTOFF: u8 = 127;
+// Layer index of pixels that belong to no layer.
+const NO_LAYER: u8 = u8::MAX;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceResult {
+  pub svg: String,
+  pub width: u32,
+  pub height: u32,
+  pub colors: Vec<String>,
+  pub points: u32,
+}
+
+type Point = (f32, f32);
+
+/// Closed outlines of the `inside` pixels, following pixel edges clockwise.
+fn trace_outlines(inside: &[bool], w: usize, h: usize) -> Vec<Vec<Point>> {
+  let at = |x: isize, y: isize| {
+    x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h && inside[y as usize * w + x as usize]
+  };
+  let key = |x: usize, y: usize| y * (w + 1) + x;
+  let mut edges: HashMap<usize, Vec<usize>> = HashMap::new();
+  let mut add = |a: (usize, usize), b: (usize, usize)| edges.entry(key(a.0, a.1)).or_default().push(key(b.0, b.1));
+  for y in 0..h {
+    for x in 0..w {
+      if !inside[y * w + x] {
+        continue;
+      }
+      let (xi, yi) = (x as isize, y as isize);
+      if !at(xi, yi - 1) {
+        add((x, y), (x + 1, y));
+      }
+      if !at(xi + 1, yi) {
+        add((x + 1, y), (x + 1, y + 1));
+      }
+      if !at(xi, yi + 1) {
+        add((x + 1, y + 1), (x, y + 1));
+      }
+      if !at(xi - 1, yi) {
+        add((x, y + 1), (x, y));
+      }
+    }
+  }
+
+  // Every boundary edge is used exactly once, so even-odd filling of the loops
+  // reproduces the region whichever way diagonal touch points get resolved.
+  let mut loops = Vec::new();
+  let starts: Vec<usize> = edges.keys().copied().collect();
+  for start in starts {
+    while edges.get(&start).is_some_and(|v| !v.is_empty()) {
+      let mut pts = Vec::new();
+      let mut cur = start;
+      loop {
+        pts.push(((cur % (w + 1)) as f32, (cur / (w + 1)) as f32));
+        let Some(next) = edges.get_mut(&cur).and_then(Vec::pop) else {
+          break;
+        };
+        cur = next;
+        if cur == start {
+          break;
+        }
+      }
+      loops.push(pts);
+    }
+  }
+  loops
+}
+
+fn segment_distance(p: Point, a: Point, b: Point) -> f32 {
+  let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+  let len2 = dx * dx + dy * dy;
+  if len2 == 0.0 {
+    return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+  }
+  ((dy * p.0 - dx * p.1 + b.0 * a.1 - b.1 * a.0) / len2.sqrt()).abs()
+}
+
+fn douglas_peucker(pts: &[Point], tolerance: f32, out: &mut Vec<Point>) {
+  let (first, last) = (pts[0], pts[pts.len() - 1]);
+  let (mut max_d, mut max_i) = (0.0, 0);
+  for (i, p) in pts.iter().enumerate().take(pts.len() - 1).skip(1) {
+    let d = segment_distance(*p, first, last);
+    if d > max_d {
+      (max_d, max_i) = (d, i);
+    }
+  }
+  if max_d > tolerance {
+    douglas_peucker(&pts[..=max_i], tolerance, out);
+    out.pop();
+    douglas_peucker(&pts[max_i..], tolerance, out);
+  } else {
+    out.push(first);
+    out.push(last);
+  }
+}
+
+fn simplify_loop(pts: &[Point], tolerance: f32) -> Vec<Point> {
+  if pts.len() < 4 {
+    return pts.to_vec();
+  }
+  // Split the loop at its farthest point from the start so both halves are open runs.
+  let far = (1..pts.len())
+    .max_by(|&a, &b| {
+      let da = (pts[a].0 - pts[0].0).powi(2) + (pts[a].1 - pts[0].1).powi(2);
+      let db = (pts[b].0 - pts[0].0).powi(2) + (pts[b].1 - pts[0].1).powi(2);
+      da.total_cmp(&db)
+    })
+    .unwrap_or(1);
+  let mut closed = pts.to_vec();
+  closed.push(pts[0]);
+  let mut out = Vec::new();
+  douglas_peucker(&closed[..=far], tolerance, &mut out);
+  out.pop();
+  douglas_peucker(&closed[far..], tolerance, &mut out);
+  out.pop();
+  out
+}
+
+fn path_data(loops: &[Vec<Point>], tolerance: f32, points: &mut u32) -> String {
+  let mut d = String::new();
+  for l in loops {
+    let simple = simplify_loop(l, tolerance);
+    if simple.len() < 3 {
+      continue;
+    }
+    *points += simple.len() as u32;
+    for (i, (x, y)) in simple.iter().enumerate() {
+      let _ = write!(d, "{}{} {}", if i == 0 { "M" } else { "L" }, x, y);
+    }
+    d.push('Z');
+  }
+  d
+}
+
+fn luma(px: tiny_skia::ColorU8) -> f32 {
+  0.2126 * px.red() as f32 + 0.7152 * px.green() as f32 + 0.0722 * px.blue() as f32
+}
+
+// Most common colors after dropping each channel to 4 bits, averaged per bucket.
+fn build_palette(pixels: &[tiny_skia::ColorU8], max: usize) -> Vec<[u8; 3]> {
+  let mut buckets: HashMap<u16, (u32, [u64; 3])> = HashMap::new();
+  for c in pixels.iter().filter(|c| c.alpha() > ALPHA_CUTOFF) {
+    let k = ((c.red() as u16 >> 4) << 8) | ((c.green() as u16 >> 4) << 4) | (c.blue() as u16 >> 4);
+    let e = buckets.entry(k).or_insert((0, [0; 3]));
+    e.0 += 1;
+    e.1[0] += c.red() as u64;
+    e.1[1] += c.green() as u64;
+    e.1[2] += c.blue() as u64;
+  }
+  let mut sorted: Vec<_> = buckets.into_values().collect();
+  sorted.sort_by_key(|e| std::cmp::Reverse(e.0));
+  sorted
+    .into_iter()
+    .take(max)
+    .map(|(n, sum)| sum.map(|s| (s / n as u64) as u8))
+    .collect()
+}
+
+fn nearest(palette: &[[u8; 3]], c: tiny_skia::ColorU8) -> usize {
+  let dist = |p: &[u8; 3]| {
+    let d = [
+      p[0] as i32 - c.red() as i32,
+      p[1] as i32 - c.green() as i32,
+      p[2] as i32 - c.blue() as i32,
+    ];
+    d[0] * d[0] + d[1] * d[1] + d[2] * d[2]
+  };
+  (0..palette.len()).min_by_key(|&i| dist(&palette[i])).unwrap_or(0)
+}
+
+fn hex(c: [u8; 3]) -> String {
+  format!("#{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+// PNGs load as they are; JPEGs are decoded to opaque RGBA, which is already premultiplied.
+fn load_image(path: &Path) -> Result<tiny_skia::Pixmap, String> {
+  let ext = path
+    .extension()
+    .and_then(|e| e.to_str())
+    .unwrap_or("")
+    .to_ascii_lowercase();
+  match ext.as_str() {
+    "png" => tiny_skia::Pixmap::load_png(path).map_err(|e| e.to_string()),
+    "jpg" | "jpeg" => decode_jpeg(&fs::read(path).map_err(|e| e.to_string())?),
+    _ => Err("Only PNG and JPEG images can be traced.".into()),
+  }
+}
+
+/// Decodes a JPEG into an (opaque) pixmap.
+pub(crate) fn decode_jpeg(data: &[u8]) -> Result<tiny_skia::Pixmap, String> {
+  let options = DecoderOptions::default().jpeg_set_out_colorspace(ColorSpace::RGBA);
+  let mut decoder = JpegDecoder::new_with_options(data, options);
+  let rgba = decoder.decode().map_err(|e| format!("Failed to decode JPEG: {e:?}"))?;
+  let info = decoder.info().ok_or_else(|| "Failed to decode JPEG.".to_string())?;
+  let size = tiny_skia::IntSize::from_wh(info.width as u32, info.height as u32)
+    .ok_or_else(|| "The JPEG has no pixels.".to_string())?;
+  tiny_skia::Pixmap::from_vec(rgba, size).ok_or_else(|| "Failed to decode JPEG.".to_string())
+}
+
+// `<name>_traced.svg` beside the image, or `<name>_traced-2.svg` and so on when that exists
+// (it may have been edited since), so tracing never replaces a file the user made.
+fn default_output(input: &Path) -> PathBuf {
+  let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("image");
+  let out = input.with_file_name(format!("{stem}_traced.svg"));
+  if out.exists() {
+    conflict::unique_path(&out, |_| false)
+  } else {
+    out
+  }
+}
+
+/// Traces a PNG or JPEG into an SVG: `threshold` mode makes one black layer from dark pixels,
+/// `color` mode one layer per palette color. `detail` (0–1) sets how closely the
+/// outlines follow pixel edges.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn vectorize_image(
+  image_path: String,
+  output_path: Option<String>,
+  mode: Option<String>,
+  threshold: Option<u8>,
+  colors: Option<u32>,
+  detail: Option<f32>,
+) -> Result<TraceResult, String> {
+  tauri::async_runtime::spawn_blocking(move || {
+    let input = PathBuf::from(&image_path);
+    if !input.is_file() {
+      return Err("Invalid image path.".into());
+    }
+    let out = match output_path {
+      Some(p) => PathBuf::from(p),
+      None => default_output(&input),
+    };
+    // Compared resolved, so a relative path or a symlink to the image is caught too.
+    if out == input || fs::canonicalize(&out).ok() == fs::canonicalize(&input).ok() {
+      return Err("The traced SVG would overwrite the source image.".into());
+    }
+    let pixmap = load_image(&input)?;
+    let (w, h) = (pixmap.width() as usize, pixmap.height() as usize);
+    let pixels: Vec<tiny_skia::ColorU8> = pixmap.pixels().iter().map(|p| p.demultiply()).collect();
+    let detail = detail.unwrap_or(0.5).clamp(0.0, 1.0);
+    // Full detail keeps every pixel corner; zero allows ~2.5px deviation.
+    let tolerance = 0.1 + (1.0 - detail) * 2.4;
+
+    let mode = mode.unwrap_or_else(|| "threshold".into());
+    // Each pixel's layer is one byte; a layer's mask is only built while it's traced, so a
+    // many-color trace of a large image holds one mask at a time.
+    let (palette, assigned): (Vec<[u8; 3]>, Vec<u8>) = match mode.as_str() {
+      "threshold" => {
+        let cutoff = threshold.unwrap_or(DEFAULT_THRESHOLD) as f32;
+        let assigned = pixels
+          .iter()
+          .map(|c| {
+            if c.alpha() > ALPHA_CUTOFF && luma(*c) < cutoff {
+              0
+            } else {
+              NO_LAYER
+            }
+          })
+          .collect();
+        (vec![[0, 0, 0]], assigned)
+      }
+      "color" => {
+        let n = colors.unwrap_or(DEFAULT_COLORS).clamp(1, MAX_COLORS) as usize;
+        let palette = build_palette(&pixels, n);
+        let assigned = pixels
+          .iter()
+          .map(|c| {
+            if c.alpha() > ALPHA_CUTOFF {
+              nearest(&palette, *c) as u8
+            } else {
+              NO_LAYER
+            }
+          })
+          .collect();
+        (palette, assigned)
+      }
+      _ => return Err("Invalid trace mode (expected threshold or color).".into()),
+    };
+    drop(pixels);
+
+    let mut points = 0;
+    let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#);
+    let mut used = Vec::new();
+    for (i, color) in palette.iter().enumerate() {
+      let mask: Vec<bool> = assigned.iter().map(|&a| a as usize == i).collect();
+      let d = path_data(&trace_outlines(&mask, w, h), tolerance, &mut points);
+      if d.is_empty() {
+        continue;
+      }
+      used.push(hex(*color));
+      let _ = write!(svg, r#"<path fill="{}" fill-rule="evenodd" d="{d}"/>"#, hex(*color));
+    }
+    svg.push_str("</svg>\n");
+
+    if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+      fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&out, svg).map_err(|e| e.to_string())?;
+    Ok(TraceResult {
+      svg: out.to_string_lossy().to_string(),
+      width: w as u32,
+      height: h as u32,
+      colors: used,
+      points,
+    })
+  })
+  .await
+  .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,189 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+};
+
+use s3::{creds::Credentials, Bucket, Region};
+use serde::Serialize;
+use tauri::State;
+use ts_rs::TS;
+
+use crate::{
+  convert::EVENT_VERSION,
+  keychain,
+  settings::{SettingsStore, UploadSettings},
+};
+
+// Post-batch upload to an S3-compatible bucket (AWS, R2, MinIO, ...): with `upload: true`, every
+// file a batch wrote is sent to `<prefix><path under the output folder>` once the batch is done,
+// so a web asset pipeline can go from SVGs to a CDN in one step. Endpoint, bucket and access key
+// are settings; the secret key lives in the system keychain, never in the settings file. A file
+// that fails to upload is reported on its own and doesn't fail the batch.
+
+const KEYCHAIN_SERVICE: &str = "svg-to-png.s3";
+const DEFAULT_REGION: &str = "us-east-1";
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct UploadProgressEvent {
+  pub version: u32, // EVENT_VERSION
+  pub batch_id: String,
+  pub done: u32,
+  pub total: u32,
+  pub key: String,
+  pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct UploadReport {
+  pub uploaded: u32,
+  pub failed: u32,
+}
+
+fn non_empty(v: &Option<String>) -> Option<&str> {
+  v.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// The configured bucket, ready to upload to.
+pub(crate) struct Target {
+  bucket: Box<Bucket>,
+  prefix: String,
+}
+
+impl Target {
+  /// Connects to the bucket in `settings` and checks it answers to the configured key, so a
+  /// batch whose uploads can't work fails before it starts.
+  pub(crate) async fn connect(settings: &UploadSettings) -> Result<Self, String> {
+    let name = non_empty(&settings.bucket).ok_or_else(|| "No upload bucket is configured.".to_string())?;
+    let key_id = non_empty(&settings.access_key_id).ok_or_else(|| "No upload access key is configured.".to_string())?;
+    let secret = keychain::get(KEYCHAIN_SERVICE, key_id)
+      .map_err(|e| format!("No secret key for {key_id} in the keychain: {e}"))?;
+    let region_name = non_empty(&settings.region).unwrap_or(DEFAULT_REGION).to_string();
+    let region = match non_empty(&settings.endpoint) {
+      Some(endpoint) => Region::Custom {
+        region: region_name,
+        endpoint: endpoint.to_string(),
+      },
+      None => region_name.parse().map_err(|e| format!("Invalid region: {e}"))?,
+    };
+    let credentials = Credentials::new(Some(key_id), Some(&secret), None, None, None).map_err(|e| e.to_string())?;
+    let mut bucket = Bucket::new(name, region, credentials).map_err(|e| e.to_string())?;
+    if settings.path_style {
+      bucket = bucket.with_path_style();
+    }
+    // Keys are joined with '/', so a prefix like "icons" still lands in a folder.
+    let prefix = match non_empty(&settings.prefix).map(|p| p.trim_matches('/')) {
+      Some(p) if !p.is_empty() => format!("{p}/"),
+      _ => String::new(),
+    };
+    // A HEAD on the bucket itself: cheap, signed, and it tells a missing bucket from a bad key.
+    let (_, code) = bucket
+      .head_object("/")
+      .await
+      .map_err(|e| format!("Failed to reach bucket {name}: {e}"))?;
+    match code {
+      200..=299 => Ok(Self { bucket, prefix }),
+      404 => Err(format!("Bucket {name} doesn't exist.")),
+      401 | 403 => Err(format!(
+        "Bucket {name} refused the access key {key_id}; check its secret and that it may list the bucket."
+      )),
+      code => Err(format!("Bucket {name} answered HTTP {code}.")),
+    }
+  }
+
+  fn key_for(&self, root: Option<&Path>, file: &Path) -> String {
+    let rel = root
+      .and_then(|r| file.strip_prefix(r).ok())
+      .unwrap_or_else(|| file.file_name().map(Path::new).unwrap_or(file));
+    let parts: Vec<_> = rel.iter().map(|c| c.to_string_lossy()).collect();
+    format!("{}{}", self.prefix, parts.join("/"))
+  }
+
+  async fn put(&self, key: &str, file: &Path) -> Result<(), String> {
+    let data = fs::read(file).map_err(|e| e.to_string())?;
+    let resp = self
+      .bucket
+      .put_object_with_content_type(key, &data, content_type(file))
+      .await
+      .map_err(|e| e.to_string())?;
+    match resp.status_code() {
+      200..=299 => Ok(()),
+      code => Err(format!("HTTP {code}: {}", String::from_utf8_lossy(resp.bytes()))),
+    }
+  }
+
+  /// Uploads `files`, keyed by their path under `root`, reporting each through `progress`.
+  pub(crate) async fn upload_all(
+    &self,
+    batch_id: &str,
+    root: Option<&Path>,
+    files: &[PathBuf],
+    progress: impl Fn(UploadProgressEvent),
+  ) -> UploadReport {
+    // Outputs replaced or removed later in the batch (e.g. deduplicated) aren't there to send.
+    let files: Vec<&PathBuf> = files.iter().filter(|f| f.is_file()).collect();
+    let mut report = UploadReport::default();
+    for (i, file) in files.iter().enumerate() {
+      let key = self.key_for(root, file);
+      let error = self.put(&key, file).await.err();
+      match &error {
+        None => report.uploaded += 1,
+        Some(err) => {
+          log::warn!("failed to upload {}: {err}", file.display());
+          report.failed += 1;
+        }
+      }
+      progress(UploadProgressEvent {
+        version: EVENT_VERSION,
+        batch_id: batch_id.to_string(),
+        done: i as u32 + 1,
+        total: files.len() as u32,
+        key,
+        error,
+      });
+    }
+    report
+  }
+}
+
+fn content_type(file: &Path) -> &'static str {
+  let ext = file.extension().and_then(|e| e.to_str()).unwrap_or("");
+  match ext.to_ascii_lowercase().as_str() {
+    "png" => "image/png",
+    "webp" => "image/webp",
+    "avif" => "image/avif",
+    "svg" => "image/svg+xml",
+    "ico" => "image/x-icon",
+    "pdf" => "application/pdf",
+    "json" => "application/json",
+    "css" => "text/css",
+    "html" => "text/html",
+    _ => "application/octet-stream",
+  }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_upload_settings(store: State<'_, SettingsStore>) -> UploadSettings {
+  store.get().upload
+}
+
+/// Replaces the upload target. A given secret key goes to the keychain under the access key id;
+/// without one, the key already stored for that id is kept.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_upload_settings(
+  store: State<'_, SettingsStore>,
+  settings: UploadSettings,
+  secret_access_key: Option<String>,
+) -> Result<UploadSettings, String> {
+  if let Some(secret) = secret_access_key.filter(|s| !s.is_empty()) {
+    let key_id =
+      non_empty(&settings.access_key_id).ok_or_else(|| "A secret key needs an access key id.".to_string())?;
+    keychain::set(KEYCHAIN_SERVICE, key_id, &secret).map_err(|e| format!("Failed to store the secret key: {e}"))?;
+  }
+  store.update(|s| s.upload = settings.clone());
+  store.save()?;
+  Ok(settings)
+}
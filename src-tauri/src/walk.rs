@@ -0,0 +1,133 @@
+use std::{
+  cmp::Ordering,
+  fs,
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::convert::{is_input, is_svg};
+
+const SVG_IGNORE_FILE: &str = ".svgignore";
+// Dependency folders are skipped along with dotfiles; they're never worth converting.
+const SKIPPED_DIRS: [&str; 1] = ["node_modules"];
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WalkOptions {
+  pub skip_hidden: Option<bool>,    // dotfiles/dot-dirs and node_modules
+  pub respect_ignore: Option<bool>, // .gitignore and .svgignore files
+}
+
+/// Every SVG file under `root`, honoring the hidden-file and ignore-file options.
+pub(crate) fn svg_files(root: &Path, opts: WalkOptions) -> impl Iterator<Item = PathBuf> {
+  files(root, opts, is_svg)
+}
+
+/// Like [`svg_files`], but also picks up the other supported inputs (Lottie).
+pub(crate) fn input_files(root: &Path, opts: WalkOptions) -> impl Iterator<Item = PathBuf> {
+  files(root, opts, is_input)
+}
+
+/// Every PNG under `root`, e.g. existing outputs to compare new ones against.
+pub(crate) fn png_files(root: &Path, opts: WalkOptions) -> impl Iterator<Item = PathBuf> {
+  files(root, opts, |p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("png")))
+}
+
+fn files(root: &Path, opts: WalkOptions, keep: fn(&Path) -> bool) -> impl Iterator<Item = PathBuf> {
+  let skip_hidden = opts.skip_hidden.unwrap_or(false);
+  let respect_ignore = opts.respect_ignore.unwrap_or(false);
+
+  let mut builder = WalkBuilder::new(root);
+  builder
+    .standard_filters(false)
+    .hidden(skip_hidden)
+    .git_ignore(respect_ignore)
+    // Design folders are often not git checkouts; honor .gitignore anyway.
+    .require_git(false)
+    .parents(respect_ignore);
+  if respect_ignore {
+    builder.add_custom_ignore_filename(SVG_IGNORE_FILE);
+  }
+  if skip_hidden {
+    builder.filter_entry(|e| !SKIPPED_DIRS.iter().any(|d| e.file_name() == *d));
+  }
+
+  builder
+    .build()
+    .filter_map(Result::ok)
+    .filter(|e| e.file_type().is_some_and(|t| t.is_file()) && keep(e.path()))
+    .map(|e| e.into_path())
+}
+
+// Compares strings treating digit runs as numbers, so "frame_2" sorts before "frame_10".
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+  let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+  loop {
+    match (a.peek().copied(), b.peek().copied()) {
+      (None, None) => return Ordering::Equal,
+      (None, Some(_)) => return Ordering::Less,
+      (Some(_), None) => return Ordering::Greater,
+      (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+        let mut na = String::new();
+        while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+          na.push(c);
+        }
+        let mut nb = String::new();
+        while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+          nb.push(c);
+        }
+        let (ta, tb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+        let ord = ta.len().cmp(&tb.len()).then_with(|| ta.cmp(tb));
+        if ord != Ordering::Equal {
+          return ord;
+        }
+      }
+      (Some(x), Some(y)) => {
+        let ord = x.to_lowercase().cmp(y.to_lowercase());
+        if ord != Ordering::Equal {
+          return ord;
+        }
+        a.next();
+        b.next();
+      }
+    }
+  }
+}
+
+fn file_name_of(p: &Path) -> String {
+  p.file_name().map(|s| s.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Orders batch inputs by `sort_by` ("name" | "path" | "size" | "mtime" | "natural"),
+/// falling back to the path so the order is always deterministic.
+pub(crate) fn sort_paths(paths: &mut [PathBuf], sort_by: Option<&str>, descending: bool) -> Result<(), String> {
+  match sort_by.unwrap_or("path") {
+    "path" => paths.sort(),
+    "name" => paths.sort_by(|a, b| {
+      file_name_of(a)
+        .to_lowercase()
+        .cmp(&file_name_of(b).to_lowercase())
+        .then_with(|| a.cmp(b))
+    }),
+    "natural" => paths.sort_by(|a, b| {
+      natural_cmp(&file_name_of(a), &file_name_of(b)).then_with(|| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()))
+    }),
+    "size" => paths.sort_by_cached_key(|p| (fs::metadata(p).map(|m| m.len()).unwrap_or(0), p.clone())),
+    "mtime" => paths.sort_by_cached_key(|p| {
+      let mtime = fs::metadata(p)
+        .and_then(|m| m.modified())
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+      (mtime, p.clone())
+    }),
+    _ => return Err("Invalid sort order (expected name, path, size, mtime or natural).".into()),
+  }
+  if descending {
+    paths.reverse();
+  }
+  Ok(())
+}
@@ -0,0 +1,159 @@
+use std::{
+  collections::VecDeque,
+  fs,
+  path::Path,
+  sync::{Arc, Mutex},
+};
+
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::{convert::is_svg, icons::render_icon};
+
+const DEFAULT_SCALE: f32 = 0.25;
+const DEFAULT_OPACITY: f32 = 0.5;
+// Default inset from the anchored edges, as a fraction of the shorter output side.
+const DEFAULT_MARGIN: f32 = 0.03;
+// Renders of an SVG overlay kept at once; a batch mostly reuses a handful of output widths.
+const MAX_RENDERS: usize = 4;
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct WatermarkOptions {
+  pub path: String,             // PNG or SVG overlay
+  pub position: Option<String>, // "topLeft" | "top" | ... | "center" | ... | "bottomRight" (default)
+  pub scale: Option<f32>,       // overlay width as a fraction of the output width
+  pub opacity: Option<f32>,     // 0..1
+  pub margin: Option<u32>,      // px from the anchored edges
+  #[serde(skip)]
+  pub overlay: Option<Arc<Overlay>>, // Decoded once by `validate` and shared by the batch's outputs
+}
+
+#[derive(Debug)]
+enum Source {
+  Svg(usvg::Tree),
+  Png(Arc<tiny_skia::Pixmap>),
+}
+
+/// A decoded overlay. SVG overlays are rendered once per width they're drawn at, keeping
+/// the most recently used few.
+#[derive(Debug)]
+pub struct Overlay {
+  source: Source,
+  renders: Mutex<VecDeque<(u32, Arc<tiny_skia::Pixmap>)>>, // most recently used first
+}
+
+impl Overlay {
+  fn decode(path: &Path) -> Result<Self, String> {
+    let source = if is_svg(path) {
+      let data = fs::read(path).map_err(|e| e.to_string())?;
+      let tree = usvg::Tree::from_data(&data, &usvg::Options::default()).map_err(|e| format!("Watermark: {e}"))?;
+      Source::Svg(tree)
+    } else {
+      let png = tiny_skia::Pixmap::load_png(path).map_err(|e| format!("Watermark: {e}"))?;
+      Source::Png(Arc::new(png))
+    };
+    Ok(Self {
+      source,
+      renders: Mutex::new(VecDeque::with_capacity(MAX_RENDERS)),
+    })
+  }
+
+  // The overlay to draw `width` px wide, keeping its aspect ratio, and the scale to draw it
+  // at. SVG overlays are rendered at that size; PNGs are resampled when drawn.
+  fn at_width(&self, width: u32) -> Result<(Arc<tiny_skia::Pixmap>, f32), String> {
+    match &self.source {
+      Source::Svg(tree) => {
+        let mut renders = self.renders.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(hit) = renders
+          .iter()
+          .position(|(w, _)| *w == width)
+          .and_then(|i| renders.remove(i))
+        {
+          let pixmap = hit.1.clone();
+          renders.push_front(hit);
+          return Ok((pixmap, 1.0));
+        }
+        let size = tree.size();
+        let height = ((width as f32 * size.height() / size.width()).round() as u32).max(1);
+        let pixmap = Arc::new(render_icon(tree, width, height, 0.0, None)?);
+        renders.truncate(MAX_RENDERS - 1);
+        renders.push_front((width, pixmap.clone()));
+        Ok((pixmap, 1.0))
+      }
+      Source::Png(png) => Ok((png.clone(), width as f32 / png.width() as f32)),
+    }
+  }
+}
+
+// Horizontal and vertical anchor as 0 (start), 0.5 (center) or 1 (end).
+fn anchor(position: &str) -> Option<(f32, f32)> {
+  Some(match position {
+    "topLeft" => (0.0, 0.0),
+    "top" => (0.5, 0.0),
+    "topRight" => (1.0, 0.0),
+    "left" => (0.0, 0.5),
+    "center" => (0.5, 0.5),
+    "right" => (1.0, 0.5),
+    "bottomLeft" => (0.0, 1.0),
+    "bottom" => (0.5, 1.0),
+    "bottomRight" => (1.0, 1.0),
+    _ => return None,
+  })
+}
+
+/// Checks the options and decodes the overlay, keeping it for every output drawn with them.
+pub(crate) fn validate(opts: &mut WatermarkOptions) -> Result<(), String> {
+  let path = Path::new(&opts.path);
+  if !path.is_file() {
+    return Err("Watermark file not found.".into());
+  }
+  if anchor(opts.position.as_deref().unwrap_or("bottomRight")).is_none() {
+    return Err("Invalid watermark position.".into());
+  }
+  if opts.scale.is_some_and(|s| !(f32::EPSILON..=1.0).contains(&s)) {
+    return Err("Watermark scale must be between 0 and 1.".into());
+  }
+  if opts.opacity.is_some_and(|o| !(0.0..=1.0).contains(&o)) {
+    return Err("Watermark opacity must be between 0 and 1.".into());
+  }
+  opts.overlay = Some(Arc::new(Overlay::decode(path)?));
+  Ok(())
+}
+
+/// Composites the overlay onto `pixmap`, decoding it first if `validate` hasn't.
+pub(crate) fn apply(pixmap: &mut tiny_skia::Pixmap, opts: &WatermarkOptions) -> Result<(), String> {
+  let (w, h) = (pixmap.width() as f32, pixmap.height() as f32);
+  let target_w = ((w * opts.scale.unwrap_or(DEFAULT_SCALE)).round() as u32).max(1);
+  let overlay = match &opts.overlay {
+    Some(overlay) => overlay.clone(),
+    None => Arc::new(Overlay::decode(Path::new(&opts.path))?),
+  };
+  let (overlay, scale) = overlay.at_width(target_w)?;
+  let (ow, oh) = (overlay.width() as f32 * scale, overlay.height() as f32 * scale);
+  let margin = opts
+    .margin
+    .map(|m| m as f32)
+    .unwrap_or_else(|| (w.min(h) * DEFAULT_MARGIN).round());
+  let (ax, ay) = anchor(opts.position.as_deref().unwrap_or("bottomRight")).unwrap_or((1.0, 1.0));
+  // Start-anchored sides get +margin, end-anchored -margin, centered none.
+  let x = (w - ow) * ax + margin * (1.0 - 2.0 * ax);
+  let y = (h - oh) * ay + margin * (1.0 - 2.0 * ay);
+
+  let paint = tiny_skia::PixmapPaint {
+    opacity: opts.opacity.unwrap_or(DEFAULT_OPACITY),
+    quality: tiny_skia::FilterQuality::Bicubic,
+    ..Default::default()
+  };
+  pixmap.draw_pixmap(
+    0,
+    0,
+    overlay.as_ref(),
+    &paint,
+    tiny_skia::Transform::from_row(scale, 0.0, 0.0, scale, x, y),
+    None,
+  );
+  Ok(())
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::{
+  convert::{ConvertSummary, EVENT_VERSION},
+  settings::{SettingsStore, WebhookSettings},
+};
+
+// Batch-completion webhook: when a URL is set, every batch (finished or failed) POSTs a short
+// JSON summary to it, so chat-ops bots and CI jobs can react to conversions run on a desktop.
+// Sent from its own thread, fire and forget: a slow or unreachable endpoint never holds up the
+// batch's result, and a failed delivery is only logged.
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WebhookPayload {
+  version: u32,        // EVENT_VERSION
+  event: &'static str, // "batch.finished" | "batch.failed"
+  batch_id: Option<String>,
+  input_path: String,
+  output_dir: Option<String>,
+  duration_ms: u64,
+  total: Option<u32>,
+  ok: Option<u32>,
+  failed: Option<u32>,
+  run_dir: Option<String>,
+  manifest: Option<String>,
+  contact_sheet: Option<String>,
+  review_pdf: Option<String>,
+  error: Option<String>,
+}
+
+/// Posts the batch's outcome to `url` in the background.
+pub(crate) fn notify(
+  url: &str,
+  input_path: &str,
+  output_dir: Option<&str>,
+  duration: Duration,
+  result: &Result<ConvertSummary, String>,
+) {
+  let url = url.trim().to_string();
+  if url.is_empty() {
+    return;
+  }
+  let summary = result.as_ref().ok();
+  let payload = WebhookPayload {
+    version: EVENT_VERSION,
+    event: if summary.is_some() {
+      "batch.finished"
+    } else {
+      "batch.failed"
+    },
+    batch_id: summary.map(|s| s.batch_id.clone()),
+    input_path: input_path.to_string(),
+    output_dir: output_dir.map(str::to_string),
+    duration_ms: duration.as_millis() as u64,
+    total: summary.map(|s| s.total),
+    ok: summary.map(|s| s.ok),
+    failed: summary.map(|s| s.failed),
+    run_dir: summary.and_then(|s| s.run_dir.clone()),
+    manifest: summary.and_then(|s| s.manifest.clone()),
+    contact_sheet: summary.and_then(|s| s.contact_sheet.clone()),
+    review_pdf: summary.and_then(|s| s.review_pdf.clone()),
+    error: result.as_ref().err().cloned(),
+  };
+  std::thread::spawn(move || {
+    if let Err(err) = ureq::post(&url).timeout(TIMEOUT).send_json(&payload) {
+      log::warn!("webhook delivery to {url} failed: {err}");
+    }
+  });
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+  if url.starts_with("https://") || url.starts_with("http://") {
+    Ok(())
+  } else {
+    Err("The webhook URL must start with http:// or https://.".into())
+  }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_webhook_settings(store: State<'_, SettingsStore>) -> WebhookSettings {
+  store.get().webhook
+}
+
+/// Sets the webhook URL; blank or missing turns the webhook off.
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_webhook_settings(store: State<'_, SettingsStore>, url: Option<String>) -> Result<WebhookSettings, String> {
+  let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+  if let Some(u) = &url {
+    validate_url(u)?;
+  }
+  let webhook = WebhookSettings { url };
+  store.update(|s| s.webhook = webhook.clone());
+  store.save()?;
+  Ok(webhook)
+}
@@ -0,0 +1,178 @@
+use tauri::{Emitter, Manager, State, Theme, WindowEvent};
+
+use crate::{
+  dropzone::{self, DropZoneState},
+  settings::{SettingsStore, WindowSettings},
+};
+
+const DEFAULT_WIDTH: f64 = 1240.0;
+const DEFAULT_HEIGHT: f64 = 830.0;
+
+fn position_is_visible(win: &tauri::WebviewWindow, x: f64, y: f64) -> bool {
+  let Ok(monitors) = win.available_monitors() else {
+    return false;
+  };
+  monitors.iter().any(|m| {
+    let scale = m.scale_factor();
+    let left = m.position().x as f64 / scale;
+    let top = m.position().y as f64 / scale;
+    let right = left + m.size().width as f64 / scale;
+    let bottom = top + m.size().height as f64 / scale;
+    x >= left && x < right && y >= top && y < bottom
+  })
+}
+
+/// Applies the persisted geometry on startup, falling back to the default centered size.
+pub fn restore_geometry(win: &tauri::WebviewWindow, ws: &WindowSettings) {
+  let _ = win.set_resizable(ws.resizable);
+  let _ = win.set_min_size(Some(tauri::Size::Logical(tauri::LogicalSize::<f64> {
+    width: ws.min_width,
+    height: ws.min_height,
+  })));
+
+  let width = ws.width.unwrap_or(DEFAULT_WIDTH).max(ws.min_width);
+  let height = ws.height.unwrap_or(DEFAULT_HEIGHT).max(ws.min_height);
+  let _ = win.set_size(tauri::Size::Logical(tauri::LogicalSize::<f64> { width, height }));
+
+  match (ws.x, ws.y) {
+    // Only restore the position if it is still on a connected monitor.
+    (Some(x), Some(y)) if position_is_visible(win, x, y) => {
+      let _ = win.set_position(tauri::Position::Logical(tauri::LogicalPosition::<f64> { x, y }));
+    }
+    _ => {
+      let _ = win.center();
+    }
+  }
+}
+
+/// Turns the macOS vibrancy effect on or off. Low-end GPUs can struggle with it, so it's a setting.
+pub fn apply_vibrancy(win: &tauri::WebviewWindow, enabled: bool) -> Result<(), String> {
+  // Note: Some variants are deprecated upstream, but still work on current Tauri.
+  let effects = if enabled {
+    Some(
+      tauri::window::EffectsBuilder::new()
+        .effect(tauri::window::Effect::UnderWindowBackground)
+        .state(tauri::window::EffectState::Active)
+        .radius(22.0)
+        .build(),
+    )
+  } else {
+    None
+  };
+  win.set_effects(effects).map_err(|e| e.to_string())
+}
+
+fn theme_name(theme: Theme) -> &'static str {
+  match theme {
+    Theme::Dark => "dark",
+    _ => "light",
+  }
+}
+
+/// Tracks the main window geometry and writes it out when the window closes.
+pub fn on_window_event(window: &tauri::Window, event: &WindowEvent) {
+  if window.label() != "main" {
+    return;
+  }
+  if let WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) = event {
+    dropzone::on_drop(window, paths);
+    return;
+  }
+  let Some(store) = window.try_state::<SettingsStore>() else {
+    return;
+  };
+  // The compact drop-zone geometry is temporary; keep the saved one intact.
+  let in_drop_zone = window
+    .try_state::<DropZoneState>()
+    .is_some_and(|dz| dz.is_active());
+  let scale = window.scale_factor().unwrap_or(1.0);
+  match event {
+    WindowEvent::Resized(_) | WindowEvent::Moved(_) if in_drop_zone => {}
+    WindowEvent::Resized(size) => {
+      // Minimizing reports a 0×0 size on some platforms; don't persist that.
+      if size.width == 0 || size.height == 0 || window.is_minimized().unwrap_or(false) {
+        return;
+      }
+      store.update(|s| {
+        s.window.width = Some(size.width as f64 / scale);
+        s.window.height = Some(size.height as f64 / scale);
+      });
+    }
+    WindowEvent::Moved(pos) => {
+      if window.is_minimized().unwrap_or(false) {
+        return;
+      }
+      store.update(|s| {
+        s.window.x = Some(pos.x as f64 / scale);
+        s.window.y = Some(pos.y as f64 / scale);
+      });
+    }
+    WindowEvent::ThemeChanged(theme) => {
+      let _ = window.emit("theme-changed", theme_name(*theme));
+    }
+    WindowEvent::CloseRequested { .. } | WindowEvent::Destroyed => {
+      if let Err(err) = store.save() {
+        log::warn!("failed to save settings: {err}");
+      }
+    }
+    _ => {}
+  }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_window_options(store: State<'_, SettingsStore>) -> WindowSettings {
+  store.get().window
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_window_options(
+  window: tauri::WebviewWindow,
+  store: State<'_, SettingsStore>,
+  resizable: Option<bool>,
+  min_width: Option<f64>,
+  min_height: Option<f64>,
+) -> Result<WindowSettings, String> {
+  if min_width.is_some_and(|w| !w.is_finite() || w <= 0.0)
+    || min_height.is_some_and(|h| !h.is_finite() || h <= 0.0)
+  {
+    return Err("Minimum size must be a positive number.".into());
+  }
+  store.update(|s| {
+    if let Some(r) = resizable {
+      s.window.resizable = r;
+    }
+    if let Some(w) = min_width {
+      s.window.min_width = w;
+    }
+    if let Some(h) = min_height {
+      s.window.min_height = h;
+    }
+  });
+  let ws = store.get().window;
+  window.set_resizable(ws.resizable).map_err(|e| e.to_string())?;
+  window
+    .set_min_size(Some(tauri::Size::Logical(tauri::LogicalSize::<f64> {
+      width: ws.min_width,
+      height: ws.min_height,
+    })))
+    .map_err(|e| e.to_string())?;
+  store.save()?;
+  Ok(ws)
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn get_system_theme(window: tauri::WebviewWindow) -> Result<String, String> {
+  let theme = window.theme().map_err(|e| e.to_string())?;
+  Ok(theme_name(theme).into())
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub fn set_vibrancy(
+  window: tauri::WebviewWindow,
+  store: State<'_, SettingsStore>,
+  enabled: bool,
+) -> Result<(), String> {
+  apply_vibrancy(&window, enabled)?;
+  store.update(|s| s.window.vibrancy = enabled);
+  store.save()
+}
@@ -1,6 +1,6 @@
 import { useEffect, useMemo, useRef, useState } from 'react'
 import { listen } from '@tauri-apps/api/event'
-import { invoke } from '@tauri-apps/api/core'
+import { Channel, invoke } from '@tauri-apps/api/core'
 import { getCurrentWindow } from '@tauri-apps/api/window'
 import { open } from '@tauri-apps/plugin-dialog'
 import { Link2, Link2Off, Play, Settings2, XCircle } from 'lucide-react'
@@ -13,39 +13,19 @@ import AppIcon from '@/assets/AppIcon.svg'
 import { HoverFolderIcon } from '@/components/icons/HoverFolderIcon'
 import { HoverImageMoonIcon } from '@/components/icons/HoverImageMoonIcon'
 import { WandSparkleIcon } from '@/components/icons/WandSparkleIcon'
+// Generated from the Rust structs by ts-rs; regenerate with `npm run bindings`.
+import type { BatchMessage } from '@/bindings/BatchMessage'
+import type { ConvertConflictEvent } from '@/bindings/ConvertConflictEvent'
+import type { ConvertItemEvent } from '@/bindings/ConvertItemEvent'
+import type { ConvertProgressEvent } from '@/bindings/ConvertProgressEvent'
+import type { FolderSizeInfo } from '@/bindings/FolderSizeInfo'
+import type { SvgSize } from '@/bindings/SvgSize'
+import type { UploadProgressEvent } from '@/bindings/UploadProgressEvent'
 
 type InputMode = 'file' | 'folder'
 type SizeMode = 'scale' | 'exact'
 
-type SvgSize = { width: number; height: number }
-type FolderSizeInfo = {
-  total: number
-  allSame: boolean
-  baseSize?: SvgSize | null
-  uniqueSizes: SvgSize[]
-}
-
-type ConvertProgressEvent = {
-  phase: string
-  current: number
-  active?: number | null
-  total: number
-  ok: number
-  failed: number
-  last_svg?: string | null
-}
-
-type ConvertItemEvent = {
-  index: number
-  total: number
-  svg: string
-  png: string
-  out_width?: number | null
-  out_height?: number | null
-  ok: boolean
-  engine?: string | null
-  error?: string | null
-}
+type ConflictAction = 'overwrite' | 'skip' | 'rename'
 
 const MAX_PIXELS = 80_000_000
 const MAX_MP = MAX_PIXELS / 1_000_000
@@ -189,10 +169,17 @@ export default function App() {
   const [sourceSizes, setSourceSizes] = useState<Array<{ path: string; size: SvgSize }>>([])
   const [folderSizeInfo, setFolderSizeInfo] = useState<FolderSizeInfo | null>(null)
 
-  const [isConverting, setIsConverting] = useState(false)
-  const [progress, setProgress] = useState<ConvertProgressEvent | null>(null)
+  // Several batches can run at once; progress is kept per batch id.
+  const [running, setRunning] = useState(0)
+  const isConverting = running > 0
+  const [progress, setProgress] = useState<
+    Record<string, ConvertProgressEvent & { finished?: boolean; upload?: UploadProgressEvent }>
+  >({})
   const [items, setItems] = useState<Array<ConvertItemEvent & { receivedAt: number; runId: number }>>([])
   const [runs, setRuns] = useState<Array<{ id: number; startedAt: number }>>([])
+  const [conflicts, setConflicts] = useState<ConvertConflictEvent[]>([])
+  // Lottie input needs a build with the `lottie` feature; the picker only offers it then.
+  const [lottieSupported, setLottieSupported] = useState(false)
   const currentRunIdRef = useRef<number>(0)
 
   const containerRef = useRef<HTMLDivElement | null>(null)
@@ -276,7 +263,6 @@ export default function App() {
   }, [height, inputMode, scale, sizeMode, sourceSize, sourceSizes, width])
 
   const canConvert = useMemo(() => {
-    if (isConverting) return false
     if (inputMode === 'folder') {
       if (!inputPath.trim()) return false
     } else {
@@ -297,7 +283,7 @@ export default function App() {
     }
     if (bgColor.trim() && !isHexColor(bgColor)) return false
     return true
-  }, [bgColor, inputMode, inputPath, inputPaths, isConverting, scale, sizeMode, sizeValidationError, sourceSize, height, width])
+  }, [bgColor, inputMode, inputPath, inputPaths, scale, sizeMode, sizeValidationError, sourceSize, height, width])
 
   const exactDisabled = useMemo(() => {
     if (inputMode === 'folder') {
@@ -308,6 +294,12 @@ export default function App() {
     return inputPaths.length > 1 && !allSelectedSameSize
   }, [allSelectedSameSize, folderSizeInfo?.allSame, inputMode, inputPaths.length])
 
+  useEffect(() => {
+    invoke<boolean>('lottie_supported')
+      .then(setLottieSupported)
+      .catch(() => setLottieSupported(false))
+  }, [])
+
   useEffect(() => {
     if (exactDisabled && sizeMode === 'exact') {
       setSizeMode('scale')
@@ -368,7 +360,7 @@ export default function App() {
   }
 
   function resetRunUi() {
-    setProgress(null)
+    setProgress({})
   }
 
   function resetAll() {
@@ -384,8 +376,7 @@ export default function App() {
     setSourceSize(null)
     setSourceSizes([])
     setFolderSizeInfo(null)
-    setIsConverting(false)
-    setProgress(null)
+    setProgress({})
     setItems([])
     setRuns([])
     currentRunIdRef.current = 0
@@ -395,7 +386,14 @@ export default function App() {
     const picked = await open({
       directory: inputMode === 'folder',
       multiple: inputMode === 'file',
-      filters: inputMode === 'file' ? [{ name: 'SVG', extensions: ['svg'] }] : undefined,
+      filters:
+        inputMode === 'file'
+          ? [
+              { name: 'SVG', extensions: ['svg', 'svgz'] },
+              ...(lottieSupported ? [{ name: 'Lottie', extensions: ['json'] }] : []),
+              { name: 'Archive', extensions: ['zip', 'tar', 'tgz', 'gz'] },
+            ]
+          : undefined,
     })
     if (inputMode === 'folder') {
       const p = typeof picked === 'string' ? picked : ''
@@ -427,43 +425,86 @@ export default function App() {
 
   async function startConvert() {
     if (!canConvert) return
-    setIsConverting(true)
-    setProgress(null)
+    setRunning((n) => n + 1)
     const rid = currentRunIdRef.current + 1
     currentRunIdRef.current = rid
     setRuns((prev) => [{ id: rid, startedAt: Date.now() }, ...prev].slice(0, 30))
+    // Messages on this channel belong to this run only, whichever window started it.
+    const onEvent = new Channel<BatchMessage>()
+    onEvent.onmessage = (msg) => {
+      if (msg.event === 'progress') updateProgress(msg.data)
+      else if (msg.event === 'item') addItem(msg.data, rid)
+      else if (msg.event === 'upload') {
+        const u = msg.data
+        setProgress((prev) => (prev[u.batchId] ? { ...prev, [u.batchId]: { ...prev[u.batchId], upload: u } } : prev))
+      }
+      else if (msg.event === 'summary') {
+        setProgress((prev) =>
+          prev[msg.data.batchId] ? { ...prev, [msg.data.batchId]: { ...prev[msg.data.batchId], finished: true } } : prev
+        )
+        setConflicts((prev) => prev.filter((c) => c.batchId !== msg.data.batchId))
+      }
+    }
     try {
-      await invoke('convert_svg_to_png', {
-        inputMode,
-        inputPath,
-        inputPaths: inputMode === 'file' ? inputPaths : null,
-        outputDir: outputDir.trim() ? outputDir.trim() : null,
-        sizeMode,
-        crop: sizeMode === 'exact' ? !lockAspect : false,
-        scale: sizeMode === 'scale' ? Number(scale || '1') : null,
-        width: sizeMode === 'exact' ? (tryEvalMathExpr(width) ?? null) : null,
-        height: sizeMode === 'exact' ? (tryEvalMathExpr(height) ?? null) : null,
-        background: bgColor.trim() ? bgColor.trim() : null,
+      await invoke('convert_svg_to_png_streamed', {
+        onEvent,
+        req: {
+          // A picked .zip or tarball is converted as an archive of SVGs.
+          inputMode: inputMode !== 'file' ? inputMode : /\.zip$/i.test(inputPath) ? 'zip' : /\.(tar|tar\.gz|tgz)$/i.test(inputPath) ? 'tar' : inputMode,
+          inputPath,
+          inputPaths: inputMode === 'file' ? inputPaths : null,
+          outputDir: outputDir.trim() ? outputDir.trim() : null,
+          sizeMode,
+          crop: sizeMode === 'exact' ? !lockAspect : false,
+          scale: sizeMode === 'scale' ? Number(scale || '1') : null,
+          width: sizeMode === 'exact' ? (tryEvalMathExpr(width) ?? null) : null,
+          height: sizeMode === 'exact' ? (tryEvalMathExpr(height) ?? null) : null,
+          background: bgColor.trim() ? bgColor.trim() : null,
+        },
       })
     } finally {
-      setIsConverting(false)
+      setRunning((n) => n - 1)
+    }
+  }
+
+  function updateProgress(p: ConvertProgressEvent) {
+    setProgress((prev) => ({ ...prev, [p.batchId]: { ...p, finished: prev[p.batchId]?.finished, upload: prev[p.batchId]?.upload } }))
+  }
+
+  function addItem(item: ConvertItemEvent, runId: number) {
+    setItems((prev) => [{ ...item, receivedAt: Date.now(), runId }, ...prev].slice(0, 400))
+  }
+
+  async function resolveConflict(c: ConvertConflictEvent, action: ConflictAction, applyToAll: boolean) {
+    // Once answered (or timed out on the Rust side) the item is no longer waiting.
+    const answered = (o: ConvertConflictEvent) =>
+      o.batchId === c.batchId && (applyToAll || o.index === c.index)
+    setConflicts((prev) => prev.filter((o) => !answered(o)))
+    const rest = applyToAll ? conflicts.filter((o) => answered(o) && o.index !== c.index) : []
+    for (const o of [c, ...rest]) {
+      try {
+        await invoke('resolve_conflict', { batchId: o.batchId, index: o.index, action, applyToAll })
+      } catch {
+        // ignore
+      }
     }
   }
 
+  // Batches started from the drop zone still report through window events.
   useEffect(() => {
     let cancelled = false
     const unsubs: Array<() => void> = []
     ;(async () => {
-      const u1 = await listen<ConvertProgressEvent>('convert-progress', (e) => setProgress(e.payload))
-      const u2 = await listen<ConvertItemEvent>('convert-item', (e) =>
-        setItems((prev) => [{ ...e.payload, receivedAt: Date.now(), runId: currentRunIdRef.current }, ...prev].slice(0, 400))
-      )
+      const u1 = await listen<ConvertProgressEvent>('convert-progress', (e) => updateProgress(e.payload))
+      const u2 = await listen<ConvertItemEvent>('convert-item', (e) => addItem(e.payload, currentRunIdRef.current))
+      const u3 = await listen<ConvertConflictEvent>('convert-conflict', (e) => setConflicts((prev) => [...prev, e.payload]))
       if (cancelled) {
         u1()
         u2()
+        u3()
         return
       }
-      unsubs.push(u1, u2)
+      unsubs.push(u1, u2, u3)
     })()
     return () => {
       cancelled = true
@@ -850,7 +891,7 @@ export default function App() {
               {canConvert ? (
                 <button className="cta-plain cta-pill transition-opacity" disabled={!canConvert} onClick={startConvert} type="button">
                   <WandSparkleIcon className="h-5 w-5" active={canConvert || isConverting} />
-                  {isConverting ? 'Converting…' : 'Convert'}
+                  {isConverting ? `Convert (${running} running)` : 'Convert'}
                 </button>
               ) : (
                 <Button className="h-16 w-full text-lg font-semibold bg-white/10 text-white/45 hover:bg-white/10" disabled>
@@ -860,30 +901,79 @@ export default function App() {
               )}
             </div>
 
-            {progress ? (
-              <div className="rounded-md bg-white/5 p-4 text-sm">
+            {Object.values(progress).map((p) => (
+              <div key={p.batchId} className="rounded-md bg-white/5 p-4 text-sm">
                 <div className="flex items-center justify-between">
                   <div className="font-semibold">
-                    Phase: <span style={{ color: highlightColor }}>{progress.phase}</span>
-                    {progress.last_svg ? <span className="ml-2 text-white/45">({progress.last_svg})</span> : null}
+                    Phase: <span style={{ color: highlightColor }}>{p.finished ? 'finished' : p.phase}</span>
+                    {p.lastSvg ? <span className="ml-2 text-white/45">({p.lastSvg})</span> : null}
                   </div>
-                  <div className="text-white/60">
-                    {progress.ok}/{progress.total} ok · {progress.failed} failed
+                  <div className="flex items-center gap-3 text-white/60">
+                    {p.ok}/{p.total} ok · {p.failed} failed
+                    {p.upload ? ` · ${p.upload.done}/${p.upload.total} uploaded` : null}
+                    {!p.finished && running > 1 ? (
+                      <button
+                        type="button"
+                        className="text-xs underline-offset-2 hover:underline"
+                        onClick={() => invoke('bump_job', { batchId: p.batchId })}
+                      >
+                        Run first
+                      </button>
+                    ) : null}
+                    {!p.finished ? (
+                      <button
+                        type="button"
+                        className="text-xs underline-offset-2 hover:underline"
+                        onClick={() => invoke('open_progress_window', { batchId: p.batchId })}
+                      >
+                        Pop out
+                      </button>
+                    ) : null}
                   </div>
                 </div>
                 <div className="mt-2 h-2 w-full overflow-hidden rounded-full bg-white/10">
                   <motion.div
                     className="h-full rounded-full"
-                    animate={isConverting ? { opacity: [0.75, 1, 0.75] } : { opacity: 1 }}
-                    transition={isConverting ? { duration: 1.1, repeat: Infinity, ease: 'easeInOut' } : undefined}
+                    animate={!p.finished ? { opacity: [0.75, 1, 0.75] } : { opacity: 1 }}
+                    transition={!p.finished ? { duration: 1.1, repeat: Infinity, ease: 'easeInOut' } : undefined}
                     style={{
-                      width: `${progress.total ? Math.round(((progress.ok + progress.failed) / progress.total) * 100) : 0}%`,
+                      width: `${p.total ? Math.round(((p.ok + p.failed) / p.total) * 100) : 0}%`,
                       background: progressColor,
                     }}
                   />
                 </div>
               </div>
-            ) : null}
+            ))}
+
+            {conflicts.map((c) => (
+              <div key={`${c.batchId}-${c.index}`} className="rounded-md bg-white/5 p-4 text-sm">
+                <div className="font-semibold">Output already exists</div>
+                <div className="mt-1 truncate text-white/45">{c.png}</div>
+                <div className="mt-3 flex flex-wrap items-center gap-2">
+                  <Button variant="ghost" onClick={() => resolveConflict(c, 'overwrite', false)}>
+                    Overwrite
+                  </Button>
+                  <Button variant="ghost" onClick={() => resolveConflict(c, 'skip', false)}>
+                    Keep existing
+                  </Button>
+                  <Button variant="ghost" onClick={() => resolveConflict(c, 'rename', false)}>
+                    Keep both
+                  </Button>
+                  <div className="ml-auto flex items-center gap-3 text-xs text-white/60">
+                    For the rest of this batch:
+                    <button type="button" className="underline-offset-2 hover:underline" onClick={() => resolveConflict(c, 'overwrite', true)}>
+                      overwrite
+                    </button>
+                    <button type="button" className="underline-offset-2 hover:underline" onClick={() => resolveConflict(c, 'skip', true)}>
+                      keep existing
+                    </button>
+                    <button type="button" className="underline-offset-2 hover:underline" onClick={() => resolveConflict(c, 'rename', true)}>
+                      keep both
+                    </button>
+                  </div>
+                </div>
+              </div>
+            ))}
 
             {items.length ? (
               <div className="max-h-44 overflow-auto rounded-md bg-black/20 p-3 text-xs no-scrollbar">
@@ -903,7 +993,7 @@ export default function App() {
                                 [{it.ok ? 'OK' : 'FAIL'}] {it.svg}
                               </div>
                               <div className="shrink-0 text-white/40">
-                                {it.out_width && it.out_height ? `${it.out_width}×${it.out_height}` : ''}
+                                {it.outWidth && it.outHeight ? `${it.outWidth}×${it.outHeight}` : ''}
                               </div>
                             </div>
                           ))}
@@ -0,0 +1,47 @@
+import { useEffect, useState } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { getCurrentWindow } from '@tauri-apps/api/window'
+
+import type { BatchMessage } from '@/bindings/BatchMessage'
+import type { ConvertProgressEvent } from '@/bindings/ConvertProgressEvent'
+
+// Follows one batch in its own window; the backend forwards that batch's messages as `batch-monitor`.
+export default function ProgressMonitor({ batchId }: { batchId: string }) {
+  const [progress, setProgress] = useState<ConvertProgressEvent | null>(null)
+  const [done, setDone] = useState(false)
+
+  useEffect(() => {
+    const unlisten = listen<BatchMessage>('batch-monitor', (e) => {
+      const msg = e.payload
+      if (msg.event === 'progress' && msg.data.batchId === batchId) setProgress(msg.data)
+      else if (msg.event === 'summary') setDone(true)
+    })
+    return () => {
+      unlisten.then((u) => u())
+    }
+  }, [batchId])
+
+  const finished = progress ? progress.ok + progress.failed : 0
+  const pct = progress?.total ? Math.round((finished / progress.total) * 100) : 0
+
+  return (
+    <div className="flex h-screen flex-col justify-center gap-3 p-5 text-sm text-white">
+      <div className="flex items-center justify-between font-semibold">
+        <span>{done ? 'Finished' : (progress?.phase ?? 'Waiting…')}</span>
+        <span className="text-white/60">{pct}%</span>
+      </div>
+      <div className="h-2 w-full overflow-hidden rounded-full bg-white/10">
+        <div className="h-full rounded-full bg-green-500" style={{ width: `${pct}%` }} />
+      </div>
+      <div className="truncate text-xs text-white/60">
+        {progress ? `${progress.ok}/${progress.total} ok · ${progress.failed} failed` : null}
+        {progress?.lastSvg && !done ? ` · ${progress.lastSvg}` : null}
+      </div>
+      {done ? (
+        <button type="button" className="self-end text-xs underline" onClick={() => getCurrentWindow().close()}>
+          Close
+        </button>
+      ) : null}
+    </div>
+  )
+}
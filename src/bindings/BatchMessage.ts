@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConvertItemEvent } from "./ConvertItemEvent";
+import type { ConvertProgressEvent } from "./ConvertProgressEvent";
+import type { ConvertStatsEvent } from "./ConvertStatsEvent";
+import type { ConvertSummary } from "./ConvertSummary";
+import type { UploadProgressEvent } from "./UploadProgressEvent";
+
+/**
+ * One message on a batch's channel, mirroring the window events.
+ */
+export type BatchMessage = { "event": "progress", "data": ConvertProgressEvent } | { "event": "item", "data": ConvertItemEvent } | { "event": "stats", "data": ConvertStatsEvent } | { "event": "upload", "data": UploadProgressEvent } | { "event": "summary", "data": ConvertSummary };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DuplicateOutput } from "./DuplicateOutput";
+
+export type ConvertItemEvent = { version: number, batchId: string, index: number, total: number, svg: string, png: string, outWidth: number | null, outHeight: number | null, ok: boolean, engine: string | null, checksum: string | null, extraPngs: Array<string>, skipped: boolean, warnings: Array<string>, renamedFrom: string | null, duplicates: Array<DuplicateOutput>, error: string | null, };
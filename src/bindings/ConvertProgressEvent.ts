@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ConvertProgressEvent = { version: number, batchId: string, phase: string, current: number, active: number | null, total: number, ok: number, failed: number, lastSvg: string | null, itemPercent: number | null, elapsedMs: number, filesPerSec: number | null, etaMs: number | null, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConversionRule } from "./ConversionRule";
+import type { DuotoneOptions } from "./DuotoneOptions";
+import type { FinderTags } from "./FinderTags";
+import type { FontAxes } from "./FontAxes";
+import type { HdrOptions } from "./HdrOptions";
+import type { HslAdjust } from "./HslAdjust";
+import type { OutlineOptions } from "./OutlineOptions";
+import type { PhysicalSize } from "./PhysicalSize";
+import type { SftpTarget } from "./SftpTarget";
+import type { ShadowOptions } from "./ShadowOptions";
+import type { SpriteOptions } from "./SpriteOptions";
+import type { TileOptions } from "./TileOptions";
+import type { WalkOptions } from "./WalkOptions";
+import type { WatermarkOptions } from "./WatermarkOptions";
+
+export type ConvertRequest = { inputMode: string, inputPath: string, inputPaths: Array<string> | null, archiveInMemory: boolean | null, outputDir: string | null, sizeMode: string, scale: number | null, width: number | null, height: number | null, edge: number | null, megapixels: number | null, physical: PhysicalSize | null, crop: boolean | null, sizePreset: string | null, background: string | null, verify: boolean | null, contactSheet: boolean | null, sprite: SpriteOptions | null, manifest: boolean | null, densitySet: boolean | null, target: string | null, onConflict: string | null, exclude: Array<string> | null, walk: WalkOptions | null, sortBy: string | null, sortDesc: boolean | null, languages: Array<string> | null, colorScheme: string | null, fontAxes: FontAxes | null, shapeRendering: string | null, textRendering: string | null, forceRenderingHints: boolean | null, linearLight: boolean | null, alphaMode: string | null, matte: string | null, resampleFilter: string | null, shadow: ShadowOptions | null, outline: OutlineOptions | null, cornerRadius: number | null, mask: string | null, watermark: WatermarkOptions | null, tile: TileOptions | null, invert: boolean | null, hsl: HslAdjust | null, tint: string | null, duotone: DuotoneOptions | null, posterize: number | null, threshold: number | null, optimizePng: boolean | null, outputFormat: string | null, textToPaths: boolean | null, svgNormalize: boolean | null, rasterPassthrough: boolean | null, time: number | null, colorSpace: string | null, hdr: HdrOptions | null, colorVision: Array<string> | null, reviewPdf: boolean | null, contactSheetPdf: boolean | null, rules: Array<ConversionRule> | null, sizeFromFileName: boolean | null, groupBy: string | null, runFolder: boolean | null, fileNameForm: string | null, preserveMtime: boolean | null, fileMode: string | null, readOnly: boolean | null, finderTags: FinderTags | null, trashReplaced: boolean | null, detectDuplicates: boolean | null, progressFile: boolean | null, progressPath: string | null, priority: number | null, lowPower: boolean | null, engine: string | null, upload: boolean | null, sftp: SftpTarget | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SlowFile } from "./SlowFile";
+
+export type ConvertStats = { bytesWritten: number, bytesRead: number, avgItemMs: number | null, slowest: Array<SlowFile>, durationMs: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ConvertStats } from "./ConvertStats";
+import type { DiskUsage } from "./DiskUsage";
+import type { DuplicateOutput } from "./DuplicateOutput";
+import type { UploadReport } from "./UploadReport";
+
+export type ConvertSummary = { version: number, batchId: string, total: number, ok: number, failed: number, contactSheet: string | null, sprite: string | null, manifest: string | null, reviewPdf: string | null, contactSheetPdf: string | null, runDir: string | null, diskUsage: DiskUsage, duplicates: Array<DuplicateOutput>, upload: UploadReport | null, } & ConvertStats;
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileUsage } from "./FileUsage";
+import type { FolderUsage } from "./FolderUsage";
+
+/**
+ * Where a batch's bytes went: every file it wrote, reports included.
+ */
+export type DiskUsage = { totalBytes: number, files: number, largest: Array<FileUsage>, folders: Array<FolderUsage>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FlaggedSvg } from "./FlaggedSvg";
+import type { InvalidSvg } from "./InvalidSvg";
+import type { SizeCount } from "./SizeCount";
+import type { SvgSize } from "./SvgSize";
+
+export type FolderSizeInfo = { total: number, allSame: boolean, baseSize: SvgSize | null, uniqueSizes: Array<SvgSize>, histogram: Array<SizeCount> | null, flagged: Array<FlaggedSvg>, invalid: Array<InvalidSvg>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Font weight and width applied to all text, as `font-weight` and `font-stretch`. They
+ * steer font matching to the closest installed face; the renderer doesn't set variable-font
+ * coordinates, so a variable font is drawn at its default instance.
+ */
+export type FontAxes = { weight: number | null, width: number | null, };
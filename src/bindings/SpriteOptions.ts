@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type SpriteOptions = { name: string | null, format: string | null, padding: number | null, prefix: string | null, };
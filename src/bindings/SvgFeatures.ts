@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Content in an SVG whose static render may not match what the user sees in a browser.
+ */
+export type SvgFeatures = { animated: boolean, scripted: boolean, embeddedRaster: boolean, externalRefs: boolean, };
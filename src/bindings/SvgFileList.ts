@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SvgFileEntry } from "./SvgFileEntry";
+
+export type SvgFileList = { total: number, offset: number, items: Array<SvgFileEntry>, };
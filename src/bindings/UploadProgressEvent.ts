@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UploadProgressEvent = { version: number, batchId: string, done: number, total: number, key: string, error: string | null, };
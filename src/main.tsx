@@ -2,9 +2,13 @@ import { StrictMode } from 'react'
 import { createRoot } from 'react-dom/client'
 import './index.css'
 import App from './App.tsx'
+import ProgressMonitor from './ProgressMonitor.tsx'
+
+// Detached progress windows load the same page with `?monitor=<batch id>`.
+const monitorBatchId = new URLSearchParams(window.location.search).get('monitor')
 
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
-    <App />
+    {monitorBatchId ? <ProgressMonitor batchId={monitorBatchId} /> : <App />}
   </StrictMode>,
 )